@@ -8,7 +8,8 @@ bitcoind() {
   bitcoind-"${BITCOIN_VERSION}" -conf=/opt/bitcoin/bitcoin.conf
 }
 
-bitcoind >> /var/log/stackify/bitcoind.log 2>&1 &
+# Also write bitcoind's output to the container's output, for `stackify env logs`.
+bitcoind 2>&1 | tee -a /var/log/stackify/bitcoind.log &
 
 if [ "${BITCOIN_MINER}" = "true" ]; then
   # Give bitcoind time to start before making RPC calls
@@ -18,16 +19,41 @@ if [ "${BITCOIN_MINER}" = "true" ]; then
   #bitcoin-cli restorewallet "stacks-regtest-miner" wallet.dat true
   bitcoin_cli -named createwallet wallet_name="default" descriptors=false load_on_startup=true
 
-  BTC_ADDRESS="bcrt1qng0yt0xgn40lykjj5q6xmgxfrqmdruy36kxgul"
-
-  # Generate 100 blocks to fund the wallet
-  bitcoin_cli generatetoaddress 100 "$BTC_ADDRESS"
-
+  BTC_ADDRESS="${BITCOIN_MINING_ADDRESS:-bcrt1qng0yt0xgn40lykjj5q6xmgxfrqmdruy36kxgul}"
+  BLOCK_SPEED="${BITCOIN_BLOCK_SPEED:-10}"
+
+  # Bootstrap the chain with 100 blocks. The environment's keychains (Stacks
+  # miners included) get a coinbase each first, which will have matured by the
+  # time the bootstrap is over, and the remaining blocks go to the mining
+  # address.
+  BOOTSTRAP_BLOCKS=100
+  for address in ${BITCOIN_FUNDING_ADDRESSES}; do
+    echo "Funding ${address}"
+    bitcoin_cli generatetoaddress 1 "$address"
+    BOOTSTRAP_BLOCKS=$((BOOTSTRAP_BLOCKS - 1))
+  done
+  if [ "$BOOTSTRAP_BLOCKS" -gt 0 ]; then
+    bitcoin_cli generatetoaddress "$BOOTSTRAP_BLOCKS" "$BTC_ADDRESS"
+  fi
+
+  # Mine a block every BLOCK_SPEED seconds. `stackify env bitcoin` can pause
+  # the miner or change its speed by writing the control files below, which
+  # are checked every second.
+  ELAPSED=0
   while :
   do
-    echo "Generate a new block $( date '+%d/%m/%Y %H:%M:%S' )"
-    bitcoin_cli generatetoaddress 1 $BTC_ADDRESS
-    sleep 10
+    sleep 1
+    if [ "$(cat /opt/bitcoin/miner-paused 2>/dev/null)" = "true" ]; then
+      ELAPSED=0
+      continue
+    fi
+    ELAPSED=$((ELAPSED + 1))
+    SPEED="$(cat /opt/bitcoin/miner-block-speed 2>/dev/null || echo "$BLOCK_SPEED")"
+    if [ "$ELAPSED" -ge "$SPEED" ]; then
+      echo "Generate a new block $( date '+%d/%m/%Y %H:%M:%S' )"
+      bitcoin_cli generatetoaddress 1 "$BTC_ADDRESS"
+      ELAPSED=0
+    fi
   done
 else
   while : ; do sleep 1; done
@@ -0,0 +1,30 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE environment_keychain_old (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    environment_id INTEGER NOT NULL,
+    stx_address TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    mnemonic TEXT NOT NULL,
+    private_key TEXT NOT NULL,
+    public_key TEXT NOT NULL,
+    btc_address TEXT NOT NULL,
+    nonce INTEGER NOT NULL DEFAULT 0,
+    remark TEXT NULL,
+
+    UNIQUE (stx_address),
+    UNIQUE (btc_address),
+    UNIQUE (private_key),
+    UNIQUE (mnemonic),
+
+    FOREIGN KEY (environment_id) REFERENCES environment (id)
+);
+
+-- Keychains which are duplicated across environments can't be restored.
+INSERT OR IGNORE INTO environment_keychain_old
+    SELECT id, environment_id, stx_address, amount, mnemonic, private_key,
+        public_key, btc_address, nonce, remark
+    FROM environment_keychain;
+
+DROP TABLE environment_keychain;
+
+ALTER TABLE environment_keychain_old RENAME TO environment_keychain;
@@ -0,0 +1,30 @@
+-- Keychains were unique across all environments, which prevented the same
+-- well-known accounts (e.g. the Clarinet devnet wallets) from being imported
+-- into more than one environment. They are now unique per environment, and
+-- keychains imported from a private key have no mnemonic.
+CREATE TABLE environment_keychain_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    environment_id INTEGER NOT NULL,
+    stx_address TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    mnemonic TEXT NOT NULL,
+    private_key TEXT NOT NULL,
+    public_key TEXT NOT NULL,
+    btc_address TEXT NOT NULL,
+    nonce INTEGER NOT NULL DEFAULT 0,
+    remark TEXT NULL,
+
+    UNIQUE (environment_id, stx_address),
+    UNIQUE (environment_id, private_key),
+
+    FOREIGN KEY (environment_id) REFERENCES environment (id)
+);
+
+INSERT INTO environment_keychain_new
+    SELECT id, environment_id, stx_address, amount, mnemonic, private_key,
+        public_key, btc_address, nonce, remark
+    FROM environment_keychain;
+
+DROP TABLE environment_keychain;
+
+ALTER TABLE environment_keychain_new RENAME TO environment_keychain;
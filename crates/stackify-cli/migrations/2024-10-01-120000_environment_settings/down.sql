@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE environment_service_port DROP COLUMN is_auto_published;
+ALTER TABLE environment DROP COLUMN publish_ports;
+ALTER TABLE environment DROP COLUMN description;
@@ -0,0 +1,8 @@
+-- A free-form description of the environment, and whether its services'
+-- default ports are published on the host when their containers are created.
+ALTER TABLE environment ADD COLUMN description TEXT NULL;
+ALTER TABLE environment ADD COLUMN publish_ports BOOLEAN NOT NULL DEFAULT 0;
+
+-- Ports published because of the environment's `publish_ports` setting, as
+-- opposed to those published by the user.
+ALTER TABLE environment_service_port ADD COLUMN is_auto_published BOOLEAN NOT NULL DEFAULT 0;
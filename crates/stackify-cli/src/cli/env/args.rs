@@ -21,7 +21,7 @@ pub enum EnvSubCommands {
     /// used for runtime. The environment will not be started, however.
     Build(super::build::BuildArgs),
     /// Displays detailed information about the specified environment.
-    Inspect(InspectArgs),
+    Inspect(super::inspect::InspectArgs),
     /// Removes the specified environment and all associated resources. This
     /// action is irreversible.
     #[clap(visible_alias = "rm")]
@@ -34,6 +34,16 @@ pub enum EnvSubCommands {
     Start(StartArgs),
     /// Stops the specified environment.
     Stop(StopArgs),
+    /// Displays the live state of the specified environment's services and
+    /// chains.
+    Status(super::status::StatusArgs),
+    /// Displays the combined logs of the specified environment's services.
+    Logs(super::logs::LogsArgs),
+    /// Runs a command in one of the environment's service containers.
+    Exec(super::exec::ExecArgs),
+    /// Opens an interactive shell in one of the environment's service
+    /// containers.
+    Shell(super::exec::ShellArgs),
     /// Stops the specified environment if it is running and removes all
     /// associated resources, without actually deleting the environment.
     Down(DownArgs),
@@ -44,24 +54,17 @@ pub enum EnvSubCommands {
     Epoch(EpochArgs),
     /// Update the environment's configuration. Note that changes made here will
     /// not affect the environment until it is restarted.
-    Set(SetArgs),
+    Set(super::set::SetArgs),
     /// Manage keychains (Stacks accounts) for the environment.
     Keychain(KeychainArgs),
     /// Manage contracts for the environment.
     Contract(super::contract::ContractArgs),
-}
-
-#[derive(Debug, Args)]
-pub struct SetArgs {
-    /// The name of the environment.
-    #[arg(
-        required = false,
-        value_name = "NAME",
-        short = 'e',
-        long = "environment",
-        visible_alias = "env"
-    )]
-    pub env_name: String,
+    /// Funds a principal with STX from the environment's keychains.
+    Faucet(super::faucet::FaucetArgs),
+    /// Control the environment's Bitcoin chain: mine blocks, pause the miner or
+    /// cause reorgs.
+    #[clap(visible_alias = "btc")]
+    Bitcoin(super::bitcoin::BitcoinArgs),
 }
 
 #[derive(Debug, Args)]
@@ -70,13 +73,6 @@ pub struct DownArgs {
     pub env_name: String,
 }
 
-#[derive(Debug, Args)]
-pub struct InspectArgs {
-    /// The name of the environment to inspect.
-    #[arg(required = true, value_name = "ENVIRONMENT")]
-    pub env_name: String,
-}
-
 #[derive(Debug, Args)]
 pub struct NewArgs {
     /// The name of the environment to create.
@@ -98,6 +94,10 @@ pub struct NewArgs {
 pub struct RemoveArgs {
     #[arg(required = true, value_name = "NAME")]
     pub env_name: String,
+
+    /// Removes the environment without asking for confirmation.
+    #[arg(required = false, short, long)]
+    pub yes: bool,
 }
 
 #[derive(Debug, Args)]
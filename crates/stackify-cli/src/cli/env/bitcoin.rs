@@ -0,0 +1,303 @@
+use std::path::Path;
+
+use clap::{Args, Subcommand};
+use color_eyre::{eyre::bail, Result};
+use stackify_common::{
+    bitcoin::rpc::BitcoinRpcClient,
+    types::{Environment, EnvironmentService},
+    ServiceType,
+};
+
+use crate::{
+    cli::{context::CliContext, env::bitcoin_rpc_client, theme::ThemedObject},
+    db::cli_db::CliDatabase,
+    docker::api::DEFAULT_BITCOIN_MINING_ADDRESS,
+    errors::CliError,
+    util::names::service_container_name,
+};
+
+/// Files read by the miner loop in `assets/bitcoin-entrypoint.sh` before each
+/// block, which let us pause it and change its speed while it's running.
+const MINER_PAUSED_FILE: &str = "/opt/bitcoin/miner-paused";
+const MINER_BLOCK_SPEED_FILE: &str = "/opt/bitcoin/miner-block-speed";
+
+#[derive(Debug, Args)]
+pub struct BitcoinArgs {
+    #[command(subcommand)]
+    pub commands: BitcoinSubCommands,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BitcoinSubCommands {
+    /// Mines the given number of Bitcoin blocks right away.
+    Mine(BitcoinMineArgs),
+    /// Pauses the Bitcoin miner, so that blocks are only mined using the
+    /// `mine` command.
+    Pause(BitcoinMinerArgs),
+    /// Resumes the Bitcoin miner after it has been paused.
+    Resume(BitcoinMinerArgs),
+    /// Changes the number of seconds between Bitcoin blocks while the
+    /// environment is running. Use `stackify env set` to change it permanently.
+    SetSpeed(BitcoinSetSpeedArgs),
+    /// Reorganizes the Bitcoin chain by invalidating the most recent blocks and
+    /// mining a longer fork in their place.
+    Reorg(BitcoinReorgArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct BitcoinMinerArgs {
+    /// The name of the environment.
+    #[arg(required = true, value_name = "ENVIRONMENT")]
+    pub env_name: String,
+
+    /// The name of the Bitcoin miner service. Only needed if the environment
+    /// has more than one Bitcoin miner.
+    #[arg(required = false, short, long, value_name = "SERVICE")]
+    pub service: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct BitcoinMineArgs {
+    #[command(flatten)]
+    pub miner: BitcoinMinerArgs,
+
+    /// The number of blocks to mine.
+    #[arg(required = false, value_name = "BLOCKS", default_value_t = 1)]
+    pub blocks: u64,
+
+    /// The address to mine the blocks to. Defaults to the miner's mining
+    /// address.
+    #[arg(required = false, short, long, value_name = "BTC_ADDRESS")]
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct BitcoinSetSpeedArgs {
+    #[command(flatten)]
+    pub miner: BitcoinMinerArgs,
+
+    /// The number of seconds between blocks.
+    #[arg(required = true, value_name = "SECONDS", value_parser = clap::value_parser!(u32).range(1..))]
+    pub seconds: u32,
+}
+
+#[derive(Debug, Args)]
+pub struct BitcoinReorgArgs {
+    #[command(flatten)]
+    pub miner: BitcoinMinerArgs,
+
+    /// The number of blocks to invalidate. A fork of one block more is mined in
+    /// their place.
+    #[arg(required = true, short, long, value_name = "BLOCKS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub depth: u64,
+}
+
+pub async fn exec(ctx: &CliContext, args: BitcoinArgs) -> Result<()> {
+    match args.commands {
+        BitcoinSubCommands::Mine(inner_args) => exec_mine(ctx, inner_args).await,
+        BitcoinSubCommands::Pause(inner_args) => exec_pause(ctx, inner_args, true).await,
+        BitcoinSubCommands::Resume(inner_args) => exec_pause(ctx, inner_args, false).await,
+        BitcoinSubCommands::SetSpeed(inner_args) => exec_set_speed(ctx, inner_args).await,
+        BitcoinSubCommands::Reorg(inner_args) => exec_reorg(ctx, inner_args).await,
+    }
+}
+
+async fn exec_mine(ctx: &CliContext, args: BitcoinMineArgs) -> Result<()> {
+    cliclack::intro("Mine Bitcoin blocks".bold())?;
+    let env = ctx.db.as_clidb().load_environment(&args.miner.env_name)?;
+    let miner = find_miner(&env, args.miner.service.as_deref())?;
+    let client = bitcoin_rpc_client(&env, Some(&miner.name)).await?;
+
+    let address = match args.address.as_deref() {
+        Some(address) => address,
+        None => mining_address(miner),
+    };
+    let hashes = generate(&client, args.blocks, address).await?;
+    let height = client.get_block_count().await?;
+
+    cliclack::outro(format!(
+        "Mined {} blocks to {}, the chain tip is now at height {}",
+        hashes.len(),
+        address,
+        height
+    ))?;
+
+    Ok(())
+}
+
+async fn exec_pause(ctx: &CliContext, args: BitcoinMinerArgs, paused: bool) -> Result<()> {
+    let action = if paused { "Pause" } else { "Resume" };
+    cliclack::intro(format!("{} the Bitcoin miner", action).bold())?;
+    let env = ctx.db.as_clidb().load_environment(&args.env_name)?;
+    let miner = find_miner(&env, args.service.as_deref())?;
+
+    write_miner_file(ctx, miner, MINER_PAUSED_FILE, &paused.to_string()).await?;
+
+    cliclack::outro(if paused {
+        format!(
+            "The Bitcoin miner '{}' is paused, use `{}` to mine blocks",
+            miner.name,
+            "stackify env bitcoin mine".bold()
+        )
+    } else {
+        format!("The Bitcoin miner '{}' has resumed", miner.name)
+    })?;
+
+    Ok(())
+}
+
+async fn exec_set_speed(ctx: &CliContext, args: BitcoinSetSpeedArgs) -> Result<()> {
+    cliclack::intro("Set the Bitcoin block speed".bold())?;
+    let env = ctx.db.as_clidb().load_environment(&args.miner.env_name)?;
+    let miner = find_miner(&env, args.miner.service.as_deref())?;
+
+    write_miner_file(
+        ctx,
+        miner,
+        MINER_BLOCK_SPEED_FILE,
+        &args.seconds.to_string(),
+    )
+    .await?;
+
+    cliclack::outro(format!(
+        "The Bitcoin miner '{}' now mines a block every {} seconds",
+        miner.name, args.seconds
+    ))?;
+
+    Ok(())
+}
+
+async fn exec_reorg(ctx: &CliContext, args: BitcoinReorgArgs) -> Result<()> {
+    cliclack::intro("Reorganize the Bitcoin chain".bold())?;
+    let env = ctx.db.as_clidb().load_environment(&args.miner.env_name)?;
+    let miner = find_miner(&env, args.miner.service.as_deref())?;
+    let client = bitcoin_rpc_client(&env, Some(&miner.name)).await?;
+
+    let height = client.get_block_count().await?;
+    if args.depth >= height {
+        bail!(CliError::Graceful {
+            title: "Reorg is too deep".to_string(),
+            message: format!(
+                "The chain tip is at height {}, so at most {} blocks can be invalidated",
+                height,
+                height.saturating_sub(1)
+            )
+        });
+    }
+
+    // Invalidating the first block of the range takes its descendants with it.
+    let fork_height = height - args.depth + 1;
+    let fork_hash = client.get_block_hash(fork_height).await?;
+    if let Err(e) = client.invalidate_block(&fork_hash).await {
+        bail!(CliError::Graceful {
+            title: "Failed to invalidate blocks".to_string(),
+            message: e.to_string()
+        });
+    }
+    cliclack::log::info(format!(
+        "Invalidated {} blocks from height {} ({})",
+        args.depth,
+        fork_height,
+        fork_hash.gray()
+    ))?;
+
+    // Mine the fork to a fresh address, so that its blocks can't be identical
+    // to the ones we just invalidated.
+    let address = client.get_new_address().await?;
+    generate(&client, args.depth + 1, &address).await?;
+    let new_height = client.get_block_count().await?;
+    let new_tip = client.get_block_hash(new_height).await?;
+
+    cliclack::outro(format!(
+        "Reorged {} blocks, the chain tip is now at height {} ({})",
+        args.depth, new_height, new_tip
+    ))?;
+
+    Ok(())
+}
+
+/// Returns the environment's Bitcoin miner, either the one named `name` or the
+/// only one in the environment.
+fn find_miner<'a>(env: &'a Environment, name: Option<&str>) -> Result<&'a EnvironmentService> {
+    let miners = env
+        .services
+        .iter()
+        .filter(|svc| svc.service_type.id == ServiceType::BitcoinMiner as i32)
+        .filter(|svc| name.is_none_or(|name| svc.name == name))
+        .collect::<Vec<_>>();
+
+    match miners.as_slice() {
+        [miner] => Ok(miner),
+        [] => bail!(CliError::Graceful {
+            title: "No Bitcoin miner found".to_string(),
+            message: match name {
+                Some(name) => format!(
+                    "The environment '{}' has no Bitcoin miner named '{}'.",
+                    env.name, name
+                ),
+                None => format!("The environment '{}' has no Bitcoin miner.", env.name),
+            }
+        }),
+        _ => bail!(CliError::Graceful {
+            title: "Multiple Bitcoin miners found".to_string(),
+            message: format!(
+                "Please specify the miner using the `{}` argument.",
+                "--service".bold()
+            )
+        }),
+    }
+}
+
+/// The address a Bitcoin miner mines its blocks to.
+fn mining_address(miner: &EnvironmentService) -> &str {
+    miner
+        .param_value("bitcoin_mining_address")
+        .unwrap_or(DEFAULT_BITCOIN_MINING_ADDRESS)
+}
+
+/// Mines `blocks` blocks to `address` with a spinner, returning their hashes.
+async fn generate(client: &BitcoinRpcClient, blocks: u64, address: &str) -> Result<Vec<String>> {
+    let spinner = cliclack::spinner();
+    spinner.start(format!("Mining {} blocks...", blocks));
+    match client.generate_to_address(blocks, address).await {
+        Ok(hashes) => {
+            spinner.stop(format!("{} Mined {} blocks", "✔".green(), hashes.len()));
+            Ok(hashes)
+        }
+        Err(e) => {
+            spinner.stop(format!("{} {}", "✖".red(), "Mining failed"));
+            bail!(CliError::Graceful {
+                title: "Failed to mine blocks".to_string(),
+                message: e.to_string()
+            })
+        }
+    }
+}
+
+/// Writes one of the miner loop's control files into the miner's container.
+async fn write_miner_file(
+    ctx: &CliContext,
+    miner: &EnvironmentService,
+    path: &str,
+    contents: &str,
+) -> Result<()> {
+    let container_name = service_container_name(miner);
+    let Some((id, _)) = ctx.docker().find_container_by_name(&container_name).await? else {
+        bail!(CliError::Graceful {
+            title: "Bitcoin miner container not found".to_string(),
+            message: format!(
+                "Make sure the environment is running (`{}`).",
+                "stackify env start".bold()
+            )
+        });
+    };
+
+    ctx.docker()
+        .api()
+        .containers()
+        .get(id)
+        .copy_file_into(Path::new(path), contents.as_bytes())
+        .await?;
+
+    Ok(())
+}
@@ -1,11 +1,38 @@
-use clap::{Args, Subcommand};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use clap::{Args, Subcommand, ValueEnum};
 use cliclack::intro;
-use color_eyre::Result;
-use stackify_common::types::EnvironmentName;
+use color_eyre::{eyre::bail, Result};
+use stackify_common::{
+    stacks::{
+        api::{
+            clarinet::ClarinetProject,
+            clarity::{
+                parse_value_typed, ClarityVersion, PrincipalData, QualifiedContractIdentifier,
+                TypeSignature, Value,
+            },
+            client::StacksNodeClient,
+            transactions::{StacksTransactionBuilder, TransactionPostConditionMode},
+            v2::{ContractInterfaceFunction, ContractInterfaceFunctionAccess},
+        },
+        StacksAddress,
+    },
+    types::{Environment, EnvironmentKeychain, EnvironmentName},
+};
 
 use crate::{
-    cli::{context::CliContext, theme::ThemedObject},
+    cli::{
+        context::CliContext,
+        env::{
+            estimate_fee, keychain::select_keychain, next_nonce, stacks_node_client, wait_for_nonce,
+        },
+        theme::ThemedObject,
+    },
     db::cli_db::CliDatabase,
+    errors::CliError,
 };
 
 #[derive(Debug, Args)]
@@ -16,7 +43,9 @@ pub struct ContractArgs {
 
 #[derive(Debug, Subcommand)]
 pub enum ContractSubCommands {
+    /// Deploys one or more Clarity contracts to a running environment.
     Deploy(ContractDeployArgs),
+    /// Calls a public or read-only function of a deployed contract.
     Call(ContractCallArgs),
 }
 
@@ -25,6 +54,88 @@ pub struct ContractDeployArgs {
     /// The name of the environment in which to deploy the contract.
     #[arg(required = true, value_name = "ENVIRONMENT")]
     pub env_name: String,
+
+    /// The `.clar` file to deploy, or a directory whose `.clar` files should
+    /// all be deployed (in alphabetical order). Contracts are named after their
+    /// file name.
+    #[arg(
+        required_unless_present = "clarinet",
+        conflicts_with = "clarinet",
+        value_name = "PATH"
+    )]
+    pub path: Option<PathBuf>,
+
+    /// Deploys all contracts of a Clarinet project instead, given its
+    /// `Clarinet.toml`. The project's devnet deployment plan is followed if it
+    /// has one, otherwise the contracts are deployed by the `deployer` account
+    /// in dependency order. Clarinet accounts must exist as keychains in the
+    /// environment. Each deployment is waited for before the next.
+    #[arg(required = false, long, value_name = "MANIFEST")]
+    pub clarinet: Option<PathBuf>,
+
+    /// The deployment plan to follow instead of the project's default devnet
+    /// plan.
+    #[arg(required = false, long, value_name = "FILE", requires = "clarinet")]
+    pub plan: Option<PathBuf>,
+
+    /// Ignore the project's deployment plan and deploy the contracts in
+    /// dependency order.
+    #[arg(required = false, long, requires = "clarinet", conflicts_with = "plan")]
+    pub ignore_plan: bool,
+
+    /// The Stacks address of the environment keychain which should deploy the
+    /// contract. You will be prompted to select one if omitted. For Clarinet
+    /// projects this deploys all contracts from this keychain instead of the
+    /// Clarinet accounts.
+    #[arg(required = false, short, long, value_name = "STX_ADDRESS")]
+    pub deployer: Option<String>,
+
+    /// The Clarity version of the contract. Defaults to the version of the
+    /// node's current epoch, or to the version set in the Clarinet project.
+    #[arg(required = false, short, long, value_enum, value_name = "VERSION")]
+    pub clarity_version: Option<ClarityVersionArg>,
+
+    /// The fee per contract, in micro-STX. Estimated from the node's fee rate
+    /// if omitted.
+    #[arg(required = false, short, long, value_name = "MICRO_STX")]
+    pub fee: Option<u64>,
+
+    /// The name of the Stacks node service to broadcast to. Defaults to the
+    /// first node with a published RPC port.
+    #[arg(required = false, short, long, value_name = "SERVICE")]
+    pub node: Option<String>,
+
+    /// Wait for the deployments to be anchored in a block.
+    #[arg(required = false, short, long)]
+    pub wait: bool,
+}
+
+/// A contract to deploy and the keychain deploying it.
+struct Deployment<'a> {
+    name: String,
+    source: String,
+    clarity_version: Option<ClarityVersion>,
+    deployer: &'a EnvironmentKeychain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ClarityVersionArg {
+    #[value(name = "1")]
+    Clarity1,
+    #[value(name = "2")]
+    Clarity2,
+    #[value(name = "3")]
+    Clarity3,
+}
+
+impl From<ClarityVersionArg> for ClarityVersion {
+    fn from(value: ClarityVersionArg) -> Self {
+        match value {
+            ClarityVersionArg::Clarity1 => ClarityVersion::Clarity1,
+            ClarityVersionArg::Clarity2 => ClarityVersion::Clarity2,
+            ClarityVersionArg::Clarity3 => ClarityVersion::Clarity3,
+        }
+    }
 }
 
 #[derive(Debug, Args)]
@@ -32,6 +143,45 @@ pub struct ContractCallArgs {
     /// The name of the environment in which the contract is deployed.
     #[arg(required = true, value_name = "ENVIRONMENT")]
     pub env_name: String,
+
+    /// The contract to call, e.g. `ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter`.
+    #[arg(required = true, value_name = "CONTRACT")]
+    pub contract: String,
+
+    /// The public or read-only function to call. You will be prompted to
+    /// select one if omitted.
+    #[arg(required = false, value_name = "FUNCTION")]
+    pub function: Option<String>,
+
+    /// The function arguments as Clarity literals, e.g. `u10` or `"hello"`. You
+    /// will be prompted for each argument if omitted.
+    #[arg(required = false, value_name = "ARGS", allow_hyphen_values = true)]
+    pub args: Vec<String>,
+
+    /// The Stacks address of the environment keychain which should sign the
+    /// call. For read-only calls this may be any address and defaults to the
+    /// contract's deployer. You will be prompted to select a keychain for
+    /// public calls if omitted.
+    #[arg(required = false, short, long, value_name = "STX_ADDRESS")]
+    pub sender: Option<String>,
+
+    /// The fee, in micro-STX. Estimated from the node's fee rate if omitted.
+    #[arg(required = false, short, long, value_name = "MICRO_STX")]
+    pub fee: Option<u64>,
+
+    /// Allow the call to transfer assets without any post-conditions, i.e.
+    /// use the `allow` post-condition mode.
+    #[arg(required = false, long)]
+    pub allow_transfers: bool,
+
+    /// The name of the Stacks node service to call. Defaults to the first node
+    /// with a published RPC port.
+    #[arg(required = false, short, long, value_name = "SERVICE")]
+    pub node: Option<String>,
+
+    /// Wait for the transaction to be anchored in a block.
+    #[arg(required = false, short, long)]
+    pub wait: bool,
 }
 
 pub async fn exec(ctx: &CliContext, args: ContractArgs) -> Result<()> {
@@ -48,9 +198,476 @@ async fn exec_deploy(ctx: &CliContext, args: ContractDeployArgs) -> Result<()> {
 
     intro(format!("{}", "Deploy Contract".bold()))?;
 
-    todo!()
+    let clarity_version = args.clarity_version.map(Into::into);
+    let (deployments, wait_each) = match (&args.clarinet, &args.path) {
+        (Some(manifest), _) => (clarinet_deployments(&env, manifest, &args)?, true),
+        (None, Some(path)) => {
+            let contracts = read_contracts(path)?;
+            let deployer = select_keychain(&env, args.deployer.as_deref(), "Select the deployer")?;
+            let deployments = contracts
+                .into_iter()
+                .map(|(name, source)| Deployment {
+                    name,
+                    source,
+                    clarity_version,
+                    deployer,
+                })
+                .collect::<Vec<_>>();
+            (deployments, false)
+        }
+        (None, None) => unreachable!("clap requires either a path or a Clarinet manifest"),
+    };
+    let client = stacks_node_client(&env, args.node.as_deref()).await?;
+    let chain_id = client.get_info().await?.network_id;
+
+    // The next nonce of each deployer, keyed by their address.
+    let mut nonces = HashMap::new();
+    let mut deployed = vec![];
+    for deployment in deployments.iter() {
+        let builder = StacksTransactionBuilder::from_keychain(deployment.deployer, chain_id)?;
+        let deployer_address = builder.address();
+        let nonce = match nonces.get(&deployer_address) {
+            Some(nonce) => *nonce,
+            None => next_nonce(&client, deployment.deployer).await?,
+        };
+        let builder = builder.nonce(nonce);
+
+        let clarity_version = deployment.clarity_version.or(clarity_version);
+        let fee = match args.fee {
+            Some(fee) => fee,
+            None => {
+                let tx = builder.smart_contract(
+                    &deployment.name,
+                    &deployment.source,
+                    clarity_version,
+                )?;
+                estimate_fee(&client, &tx).await?
+            }
+        };
+        let tx = builder.fee(fee).smart_contract(
+            &deployment.name,
+            &deployment.source,
+            clarity_version,
+        )?;
+
+        let txid = match client.broadcast_transaction(&tx).await {
+            Ok(txid) => txid,
+            Err(e) => bail!(CliError::Graceful {
+                title: format!("Failed to deploy contract '{}'", deployment.name),
+                message: e.to_string()
+            }),
+        };
+        ctx.db.set_environment_keychain_nonce(
+            deployment.deployer.environment_id,
+            &deployment.deployer.stx_address,
+            nonce + 1,
+        )?;
+        let contract_id =
+            QualifiedContractIdentifier::new(deployer_address.clone().into(), &deployment.name)?;
+
+        cliclack::log::success(format!(
+            "{}\n{} {}\n{} {}",
+            contract_id.to_string().bold(),
+            "txid:".gray(),
+            txid,
+            "fee: ".gray(),
+            fee
+        ))?;
+
+        if wait_each {
+            wait_for_nonce(&client, &deployer_address.clone().into(), nonce + 1).await?;
+            check_deployed(&client, &contract_id).await?;
+        }
+        nonces.insert(deployer_address, nonce + 1);
+        deployed.push(contract_id);
+    }
+
+    if args.wait && !wait_each {
+        for (deployer_address, nonce) in nonces {
+            wait_for_nonce(&client, &deployer_address.into(), nonce).await?;
+        }
+        for contract in deployed.iter() {
+            check_deployed(&client, contract).await?;
+        }
+    }
+
+    cliclack::outro(format!(
+        "{} contract(s) {}",
+        deployed.len(),
+        if args.wait || wait_each {
+            "deployed"
+        } else {
+            "broadcast"
+        }
+    ))?;
+
+    Ok(())
+}
+
+/// Resolves the contracts of a Clarinet project and the environment keychains
+/// deploying them, following its deployment plan if it has one.
+fn clarinet_deployments<'a>(
+    env: &'a Environment,
+    manifest: &Path,
+    args: &ContractDeployArgs,
+) -> Result<Vec<Deployment<'a>>> {
+    let project = match ClarinetProject::load(manifest) {
+        Ok(project) => project,
+        Err(e) => bail!(CliError::Graceful {
+            title: "Failed to load the Clarinet project".to_string(),
+            message: e.to_string()
+        }),
+    };
+
+    let plan = match (&args.plan, args.ignore_plan) {
+        (Some(plan), _) => Some(plan.clone()),
+        (None, false) => project.default_devnet_plan(),
+        (None, true) => None,
+    };
+    let planned = match &plan {
+        Some(plan) => project.load_plan(plan),
+        None => project
+            .contracts_in_dependency_order()
+            .map(|contracts| (contracts, vec![])),
+    };
+    let (contracts, unsupported) = match planned {
+        Ok(planned) => planned,
+        Err(e) => bail!(CliError::Graceful {
+            title: format!(
+                "Failed to plan the deployment of '{}'",
+                project.manifest.project.name
+            ),
+            message: e.to_string()
+        }),
+    };
+    for kind in unsupported {
+        cliclack::log::warning(format!(
+            "Skipping unsupported '{}' transaction in the deployment plan",
+            kind
+        ))?;
+    }
+    if contracts.is_empty() {
+        bail!(CliError::Graceful {
+            title: "No contracts found".to_string(),
+            message: format!(
+                "The Clarinet project '{}' has no contracts to deploy",
+                project.manifest.project.name
+            )
+        });
+    }
+    cliclack::log::info(format!(
+        "Deploying {} contract(s) from '{}'{}",
+        contracts.len(),
+        project.manifest.project.name,
+        plan.map(|plan| format!(" following {}", plan.display()))
+            .unwrap_or_default()
+    ))?;
+
+    let deployer_override = match &args.deployer {
+        Some(deployer) => Some(select_keychain(env, Some(deployer.as_str()), "")?),
+        None => None,
+    };
+    // Used to name the Clarinet account of a missing keychain.
+    let accounts = project.devnet_accounts().unwrap_or_default();
+
+    contracts
+        .into_iter()
+        .map(|contract| -> Result<Deployment<'a>> {
+            let sender = contract.sender.to_string();
+            let deployer = match deployer_override {
+                Some(deployer) => deployer,
+                None => match env.keychains.iter().find(|kc| kc.stx_address == sender) {
+                    Some(deployer) => deployer,
+                    None => {
+                        let account = accounts
+                            .iter()
+                            .find(|(_, account)| {
+                                account
+                                    .address()
+                                    .is_ok_and(|address| address == contract.sender)
+                            })
+                            .map(|(name, _)| format!("The Clarinet account '{}' ", name))
+                            .unwrap_or_else(|| "The account ".to_string());
+                        bail!(CliError::Graceful {
+                            title: format!("No keychain for '{}'", sender),
+                            message: format!(
+                                "{}which deploys '{}' is not a keychain of the environment. Import it using `{}`, or deploy from another keychain using `{}`.",
+                                account,
+                                contract.name,
+                                "stackify env keychain import".bold(),
+                                "--deployer".bold()
+                            )
+                        });
+                    }
+                },
+            };
+            Ok(Deployment {
+                name: contract.name,
+                source: contract.source,
+                clarity_version: contract.clarity_version,
+                deployer,
+            })
+        })
+        .collect()
 }
 
 async fn exec_call(ctx: &CliContext, args: ContractCallArgs) -> Result<()> {
-    todo!()
+    let db = ctx.db.as_clidb();
+    let env_name = EnvironmentName::new(&args.env_name)?;
+    let env = db.load_environment(&env_name)?;
+
+    intro(format!("{}", "Call Contract".bold()))?;
+
+    let contract = match args.contract.parse::<QualifiedContractIdentifier>() {
+        Ok(contract) => contract,
+        Err(e) => bail!(CliError::Graceful {
+            title: "Invalid contract".to_string(),
+            message: format!(
+                "Expected a contract identifier like `<address>.<name>`: {}",
+                e
+            )
+        }),
+    };
+    let client = stacks_node_client(&env, args.node.as_deref()).await?;
+    let interface = match client.get_contract_interface(&contract).await {
+        Ok(interface) => interface,
+        Err(e) => bail!(CliError::Graceful {
+            title: format!("Contract '{}' not found", contract),
+            message: e.to_string()
+        }),
+    };
+
+    let function = match args.function {
+        Some(name) => match interface
+            .function(&name)
+            .filter(|f| f.access != ContractInterfaceFunctionAccess::Private)
+        {
+            Some(function) => function,
+            None => bail!(CliError::Graceful {
+                title: format!("Function '{}' not found", name),
+                message: format!(
+                    "The contract '{}' has no public or read-only function named '{}'",
+                    contract, name
+                )
+            }),
+        },
+        None => {
+            let items = interface
+                .functions
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| f.access != ContractInterfaceFunctionAccess::Private)
+                .map(|(i, f)| {
+                    let hint = match f.access {
+                        ContractInterfaceFunctionAccess::ReadOnly => "read-only",
+                        _ => "public",
+                    };
+                    (i, f.name.as_str(), hint)
+                })
+                .collect::<Vec<_>>();
+            if items.is_empty() {
+                bail!(CliError::Graceful {
+                    title: "No callable functions".to_string(),
+                    message: format!(
+                        "The contract '{}' has no public or read-only functions",
+                        contract
+                    )
+                });
+            }
+            let index = cliclack::select("Select a function")
+                .items(&items)
+                .interact()?;
+            &interface.functions[index]
+        }
+    };
+
+    let arguments = if args.args.is_empty() && !function.args.is_empty() {
+        prompt_arguments(function)?
+    } else {
+        parse_arguments(function, &args.args)?
+    };
+
+    if function.access == ContractInterfaceFunctionAccess::ReadOnly {
+        let sender: PrincipalData = match args.sender {
+            Some(sender) => match sender.parse::<StacksAddress>() {
+                Ok(address) => address.into(),
+                Err(e) => bail!(CliError::Graceful {
+                    title: "Invalid sender".to_string(),
+                    message: format!("'{}' is not a valid Stacks address: {}", sender, e)
+                }),
+            },
+            None => contract.issuer.clone().into(),
+        };
+        let result = match client
+            .call_read_only(&contract, &function.name, &sender, &arguments)
+            .await
+        {
+            Ok(result) => result,
+            Err(e) => bail!(CliError::Graceful {
+                title: format!("Call to '{}' failed", function.name),
+                message: e.to_string()
+            }),
+        };
+        cliclack::log::success(result.to_string())?;
+        cliclack::outro(format!("Called read-only function '{}'", function.name))?;
+        return Ok(());
+    }
+
+    let sender = select_keychain(&env, args.sender.as_deref(), "Select the sender")?;
+    let chain_id = client.get_info().await?.network_id;
+    let mut builder = StacksTransactionBuilder::from_keychain(sender, chain_id)?;
+    if args.allow_transfers {
+        builder = builder.post_condition_mode(TransactionPostConditionMode::Allow);
+    }
+    let sender_address = builder.address();
+    let nonce = next_nonce(&client, sender).await?;
+    let builder = builder.nonce(nonce);
+
+    let fee = match args.fee {
+        Some(fee) => fee,
+        None => {
+            let tx = builder.contract_call(&contract, &function.name, arguments.clone())?;
+            estimate_fee(&client, &tx).await?
+        }
+    };
+    let tx = builder
+        .fee(fee)
+        .contract_call(&contract, &function.name, arguments)?;
+
+    let txid = match client.broadcast_transaction(&tx).await {
+        Ok(txid) => txid,
+        Err(e) => bail!(CliError::Graceful {
+            title: format!("Failed to call '{}'", function.name),
+            message: e.to_string()
+        }),
+    };
+    ctx.db
+        .set_environment_keychain_nonce(sender.environment_id, &sender.stx_address, nonce + 1)?;
+    cliclack::log::success(format!(
+        "{}\n{} {}\n{} {}",
+        format!("{}::{}", contract, function.name).bold(),
+        "txid:".gray(),
+        txid,
+        "fee: ".gray(),
+        fee
+    ))?;
+
+    if args.wait {
+        wait_for_nonce(&client, &sender_address.into(), nonce + 1).await?;
+    }
+
+    cliclack::outro(format!(
+        "Transaction {}",
+        if args.wait { "anchored" } else { "broadcast" }
+    ))?;
+
+    Ok(())
+}
+
+/// Prompts for each of the function's arguments, validating the input against
+/// the argument's type.
+fn prompt_arguments(function: &ContractInterfaceFunction) -> Result<Vec<Value>> {
+    function
+        .args
+        .iter()
+        .map(|arg| -> Result<Value> {
+            let arg_type = TypeSignature::try_from(&arg.arg_type)?;
+            let expected = arg_type.clone();
+            let input: String = cliclack::input(format!("{} {}", arg.name.bold(), arg_type.gray()))
+                .validate(move |input: &String| {
+                    parse_value_typed(input, &expected)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .interact()?;
+            Ok(parse_value_typed(&input, &arg_type)?)
+        })
+        .collect()
+}
+
+/// Parses the function's arguments from Clarity literals given on the
+/// command line.
+fn parse_arguments(function: &ContractInterfaceFunction, args: &[String]) -> Result<Vec<Value>> {
+    if args.len() != function.args.len() {
+        bail!(CliError::Graceful {
+            title: "Wrong number of arguments".to_string(),
+            message: format!(
+                "'{}' expects {} argument(s), but {} were given",
+                function.name,
+                function.args.len(),
+                args.len()
+            )
+        });
+    }
+
+    function
+        .args
+        .iter()
+        .zip(args)
+        .map(|(arg, input)| -> Result<Value> {
+            let arg_type = TypeSignature::try_from(&arg.arg_type)?;
+            match parse_value_typed(input, &arg_type) {
+                Ok(value) => Ok(value),
+                Err(e) => bail!(CliError::Graceful {
+                    title: format!("Invalid argument '{}'", arg.name),
+                    message: format!("Expected a value of type {}: {}", arg_type, e)
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Reads the contract at `path`, or all contracts in the directory at `path`,
+/// returning their names and source code.
+fn read_contracts(path: &Path) -> Result<Vec<(String, String)>> {
+    let files = if path.is_dir() {
+        let mut files = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|file| file.is_file() && file.extension().is_some_and(|ext| ext == "clar"))
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    } else if path.is_file() {
+        vec![path.to_path_buf()]
+    } else {
+        bail!(CliError::Graceful {
+            title: "Contract not found".to_string(),
+            message: format!("'{}' does not exist", path.display())
+        });
+    };
+
+    if files.is_empty() {
+        bail!(CliError::Graceful {
+            title: "No contracts found".to_string(),
+            message: format!("'{}' does not contain any .clar files", path.display())
+        });
+    }
+
+    files
+        .iter()
+        .map(|file| {
+            let name = file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            Ok((name, std::fs::read_to_string(file)?))
+        })
+        .collect()
+}
+
+/// Checks that a contract exists once its deployment has been anchored, as
+/// the deployment may have aborted.
+async fn check_deployed(
+    client: &StacksNodeClient,
+    contract: &QualifiedContractIdentifier,
+) -> Result<()> {
+    if client.get_contract_interface(contract).await.is_err() {
+        bail!(CliError::Graceful {
+            title: format!("Contract '{}' was not deployed", contract),
+            message: "The deployment was anchored, but the contract does not exist. The transaction likely aborted.".to_string()
+        });
+    }
+    Ok(())
 }
@@ -13,10 +13,15 @@ use super::args::DownArgs;
 pub async fn exec(ctx: &CliContext, args: DownArgs) -> Result<()> {
     intro("Tear-Down Environment".bold())?;
     let env_name = EnvironmentName::new(&args.env_name)?;
+    let env = ctx.db.get_environment_by_name(env_name.as_ref())?;
 
     remove_containers(ctx, &env_name).await?;
     remove_network(ctx, &env_name).await?;
 
+    // The chain is gone along with the containers, so any nonces we've been
+    // tracking for the keychains no longer apply.
+    ctx.db.reset_environment_keychain_nonces(env.id)?;
+
     outro("Finished!".bold().green())?;
 
     Ok(())
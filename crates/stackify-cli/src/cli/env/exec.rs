@@ -0,0 +1,257 @@
+use std::io::{IsTerminal, Write};
+
+use clap::Args;
+use color_eyre::{eyre::bail, Result};
+use futures_util::{pin_mut, AsyncWriteExt, StreamExt};
+use stackify_common::{
+    types::{Environment, EnvironmentService},
+    ServiceType,
+};
+use tokio::{
+    io::AsyncReadExt,
+    signal::unix::{signal, SignalKind},
+};
+
+use crate::{
+    cli::{context::CliContext, theme::ThemedObject},
+    db::cli_db::CliDatabase,
+    docker::ContainerState,
+    docker_api::{
+        conn::TtyChunk,
+        opts::{ExecCreateOpts, ExecResizeOpts, ExecStartOpts},
+        Exec,
+    },
+    errors::CliError,
+    util::names::service_container_name,
+};
+
+/// The Bitcoin config path, see `assets/bitcoin-entrypoint.sh`.
+const BITCOIN_CONF: &str = "/opt/bitcoin/bitcoin.conf";
+
+#[derive(Debug, Args)]
+pub struct ExecArgs {
+    /// The name of the environment.
+    #[arg(required = true, value_name = "ENVIRONMENT")]
+    pub env_name: String,
+
+    /// The name of the service to run the command in.
+    #[arg(required = true, value_name = "SERVICE")]
+    pub service: String,
+
+    /// The command to run, optionally following `--`. The service's binaries
+    /// can be called without their version, e.g. `bitcoin-cli
+    /// getblockchaininfo`, `stacks-node version` or `stacks-signer --help`.
+    #[arg(
+        required = true,
+        value_name = "COMMAND",
+        trailing_var_arg = true,
+        allow_hyphen_values = true
+    )]
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ShellArgs {
+    /// The name of the environment.
+    #[arg(required = true, value_name = "ENVIRONMENT")]
+    pub env_name: String,
+
+    /// The name of the service to open a shell in.
+    #[arg(required = true, value_name = "SERVICE")]
+    pub service: String,
+}
+
+pub async fn exec(ctx: &CliContext, args: ExecArgs) -> Result<()> {
+    let env = ctx.db.as_clidb().load_environment(&args.env_name)?;
+    let service = find_service(&env, &args.service)?;
+
+    let command = expand_shortcut(service, args.command);
+    let exit_code = run_in_container(ctx, service, command).await?;
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+
+    Ok(())
+}
+
+pub async fn shell(ctx: &CliContext, args: ShellArgs) -> Result<()> {
+    let env = ctx.db.as_clidb().load_environment(&args.env_name)?;
+    let service = find_service(&env, &args.service)?;
+
+    // Not every image has bash, so fall back to sh.
+    let command = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "command -v bash > /dev/null && exec bash || exec sh".to_string(),
+    ];
+    run_in_container(ctx, service, command).await?;
+
+    Ok(())
+}
+
+fn find_service<'a>(env: &'a Environment, name: &str) -> Result<&'a EnvironmentService> {
+    match env.services.iter().find(|svc| svc.name == name) {
+        Some(service) => Ok(service),
+        None => bail!(CliError::Graceful {
+            title: "Service not found".to_string(),
+            message: format!(
+                "The environment '{}' has no service named '{}'.",
+                env.name, name
+            )
+        }),
+    }
+}
+
+/// Expands calls to a service's binaries, which are installed with their
+/// version in their name, e.g. `bitcoin-cli` to `bitcoin-cli-<version>` with
+/// the node's config. Other commands are returned as-is.
+fn expand_shortcut(service: &EnvironmentService, command: Vec<String>) -> Vec<String> {
+    let Some((program, args)) = command.split_first() else {
+        return command;
+    };
+
+    let expanded = match (ServiceType::from(service.service_type.id), program.as_str()) {
+        (ServiceType::BitcoinMiner | ServiceType::BitcoinFollower, "bitcoin-cli") => vec![
+            format!("bitcoin-cli-{}", service.version.version),
+            format!("-conf={}", BITCOIN_CONF),
+            "-rpcconnect=127.0.0.1".to_string(),
+        ],
+        (ServiceType::StacksMiner | ServiceType::StacksFollower, "stacks-node")
+        | (ServiceType::StacksSigner, "stacks-signer") => match &service.version.git_target {
+            Some(git_target) => vec![format!("{}-{}", program, git_target.target)],
+            None => return command,
+        },
+        _ => return command,
+    };
+
+    expanded.into_iter().chain(args.iter().cloned()).collect()
+}
+
+/// Runs a command in a service's container, with a TTY if we're attached to a
+/// terminal, and returns its exit code.
+async fn run_in_container(
+    ctx: &CliContext,
+    service: &EnvironmentService,
+    command: Vec<String>,
+) -> Result<i32> {
+    let container_name = service_container_name(service);
+    let id = match ctx.docker().find_container_by_name(&container_name).await? {
+        Some((id, summary))
+            if summary
+                .state
+                .as_deref()
+                .map(ContainerState::parse)
+                .transpose()?
+                == Some(ContainerState::Running) =>
+        {
+            id
+        }
+        _ => bail!(CliError::Graceful {
+            title: format!("The service '{}' is not running", service.name),
+            message: format!(
+                "Make sure the environment is running (`{}`).",
+                "stackify env start".bold()
+            )
+        }),
+    };
+
+    let tty = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    let exec = Exec::create(
+        ctx.docker().api().clone(),
+        id,
+        &ExecCreateOpts::builder()
+            .command(command)
+            .attach_stdin(tty)
+            .attach_stdout(true)
+            .attach_stderr(true)
+            .tty(tty)
+            .build(),
+    )
+    .await?;
+    let multiplexer = exec
+        .start(&ExecStartOpts::builder().tty(tty).build())
+        .await?;
+
+    // Put the local terminal into raw mode, so that keystrokes (Ctrl+C
+    // included) are passed on to the container as they are typed.
+    let _raw_mode = if tty {
+        resize(&exec).await;
+        Some(RawMode::enable()?)
+    } else {
+        None
+    };
+
+    let (output, input) = multiplexer.split();
+    pin_mut!(output, input);
+    let mut stdin = tokio::io::stdin();
+    let mut stdin_open = tty;
+    let mut buf = [0u8; 1024];
+    let mut window_changes = signal(SignalKind::window_change())?;
+    let mut shutdown = ctx.tx.as_ref().map(|tx| tx.subscribe());
+
+    loop {
+        tokio::select! {
+            chunk = output.next() => match chunk {
+                Some(Ok(TtyChunk::StdOut(bytes))) => {
+                    let mut stdout = std::io::stdout();
+                    stdout.write_all(&bytes)?;
+                    stdout.flush()?;
+                }
+                Some(Ok(TtyChunk::StdErr(bytes))) => std::io::stderr().write_all(&bytes)?,
+                Some(Ok(TtyChunk::StdIn(_))) => {}
+                Some(Err(e)) => return Err(e.into()),
+                None => break,
+            },
+            read = stdin.read(&mut buf), if stdin_open => match read {
+                Ok(0) | Err(_) => stdin_open = false,
+                Ok(n) => input.write_all(&buf[..n]).await?,
+            },
+            _ = window_changes.recv(), if tty => resize(&exec).await,
+            // Only reached without a TTY, as Ctrl+C is passed on otherwise.
+            Some(_) = async { shutdown.as_mut()?.recv().await.ok() } => break,
+        }
+    }
+
+    let exit_code = exec.inspect().await?.exit_code.unwrap_or_default();
+    Ok(exit_code as i32)
+}
+
+/// Resizes the TTY of an exec to the size of the local terminal. Failures are
+/// ignored, as the exec may already have finished.
+async fn resize(exec: &Exec) {
+    let (rows, cols) = console::Term::stdout().size();
+    let opts = ExecResizeOpts::builder()
+        .height(rows as u64)
+        .width(cols as u64)
+        .build();
+    let _ = exec.resize(&opts).await;
+}
+
+/// Keeps the local terminal in raw mode, restoring its previous settings when
+/// dropped.
+struct RawMode(libc::termios);
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        unsafe {
+            let mut termios = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            let original = termios;
+            libc::cfmakeraw(&mut termios);
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) != 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            Ok(RawMode(original))
+        }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0);
+        }
+    }
+}
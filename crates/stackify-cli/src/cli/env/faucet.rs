@@ -0,0 +1,166 @@
+use clap::Args;
+use color_eyre::{eyre::bail, Result};
+use stackify_common::{
+    stacks::{
+        api::{
+            clarity::PrincipalData, client::StacksNodeClient,
+            transactions::StacksTransactionBuilder,
+        },
+        StacksAddress,
+    },
+    types::{Environment, EnvironmentKeychain},
+};
+
+use crate::{
+    cli::{
+        context::CliContext,
+        env::{
+            estimate_fee,
+            keychain::{parse_recipient, select_keychain, transfer_stx, StxTransfer},
+            stacks_node_client,
+        },
+        theme::ThemedObject,
+    },
+    db::cli_db::CliDatabase,
+    errors::CliError,
+};
+
+/// 500 STX.
+const DEFAULT_FAUCET_AMOUNT: u64 = 500_000_000;
+const FAUCET_MEMO: &str = "stackify faucet";
+
+#[derive(Debug, Args)]
+pub struct FaucetArgs {
+    /// The name of the environment.
+    #[arg(required = true, value_name = "ENVIRONMENT")]
+    pub env_name: String,
+
+    /// The principal to fund, either a Stacks address or a contract
+    /// identifier.
+    #[arg(required = true, value_name = "RECIPIENT")]
+    pub recipient: String,
+
+    /// The amount to send, in micro-STX.
+    #[arg(required = false, short, long, value_name = "MICRO_STX", default_value_t = DEFAULT_FAUCET_AMOUNT)]
+    pub amount: u64,
+
+    /// The Stacks address of the keychain to fund the recipient from. Defaults
+    /// to the keychain with the highest balance.
+    #[arg(required = false, long, value_name = "STX_ADDRESS")]
+    pub from: Option<String>,
+
+    /// The name of the Stacks node to broadcast the transaction to. Defaults to
+    /// the first node with its RPC port published.
+    #[arg(required = false, short, long, value_name = "SERVICE")]
+    pub node: Option<String>,
+
+    /// Don't wait for the transfer to be anchored.
+    #[arg(required = false, long)]
+    pub no_wait: bool,
+}
+
+pub async fn exec(ctx: &CliContext, args: FaucetArgs) -> Result<()> {
+    cliclack::intro("Faucet".bold())?;
+    let env = ctx.db.as_clidb().load_environment(&args.env_name)?;
+    let recipient = parse_recipient(&args.recipient)?;
+    let client = stacks_node_client(&env, args.node.as_deref()).await?;
+
+    let (from, fee) = match args.from.as_deref() {
+        Some(from) => (
+            select_keychain(&env, Some(from), "Select the keychain to send from")?,
+            None,
+        ),
+        None => {
+            let (from, fee) = richest_keychain(&env, &client, &recipient, args.amount).await?;
+            (from, Some(fee))
+        }
+    };
+
+    transfer_stx(
+        ctx,
+        &client,
+        StxTransfer {
+            from,
+            recipient,
+            amount: args.amount,
+            memo: Some(FAUCET_MEMO.to_string()),
+            fee,
+            wait: !args.no_wait,
+        },
+    )
+    .await?;
+
+    cliclack::outro(format!("Funded {}", args.recipient))?;
+
+    Ok(())
+}
+
+/// Returns the keychain with the highest balance on the node, together with
+/// the estimated fee for the transfer, which the balance must be able to cover
+/// along with `amount`. Keychains whose balance can't be looked up are skipped.
+async fn richest_keychain<'a>(
+    env: &'a Environment,
+    client: &StacksNodeClient,
+    recipient: &PrincipalData,
+    amount: u64,
+) -> Result<(&'a EnvironmentKeychain, u64)> {
+    let mut richest: Option<(&EnvironmentKeychain, u128)> = None;
+    for kc in env.keychains.iter() {
+        let address = match kc.stx_address.parse::<StacksAddress>() {
+            Ok(address) => address,
+            Err(e) => {
+                cliclack::log::warning(format!(
+                    "Skipping keychain {}, its address is invalid: {}",
+                    kc.stx_address, e
+                ))?;
+                continue;
+            }
+        };
+        let balance = match client.get_account(&address.into()).await {
+            Ok(account) => account.balance,
+            Err(e) => {
+                cliclack::log::warning(format!(
+                    "Skipping keychain {}, its balance could not be looked up: {}",
+                    kc.stx_address, e
+                ))?;
+                continue;
+            }
+        };
+        if richest.is_none_or(|(_, max)| balance > max) {
+            richest = Some((kc, balance));
+        }
+    }
+
+    match richest {
+        Some((kc, balance)) => {
+            let chain_id = client.get_info().await?.network_id;
+            let tx = StacksTransactionBuilder::from_keychain(kc, chain_id)?
+                .memo(FAUCET_MEMO)
+                .token_transfer(recipient.clone(), amount)?;
+            let fee = estimate_fee(client, &tx).await?;
+            if balance < amount as u128 + fee as u128 {
+                bail!(CliError::Graceful {
+                    title: "Insufficient funds".to_string(),
+                    message: format!(
+                        "None of the environment's keychains has a balance of at least {} micro-STX, the amount plus a fee of {}",
+                        amount as u128 + fee as u128,
+                        fee
+                    )
+                });
+            }
+            Ok((kc, fee))
+        }
+        None if !env.keychains.is_empty() => bail!(CliError::Graceful {
+            title: "No keychain balances available".to_string(),
+            message: "The balances of the environment's keychains could not be looked up."
+                .to_string()
+        }),
+        None => bail!(CliError::Graceful {
+            title: "No keychains are configured".to_string(),
+            message: format!(
+                "Please add a keychain to the environment using the `{}` command first.",
+                "stackify env keychain new".bold()
+            )
+        }),
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::BTreeMap;
+
+use clap::{Args, ValueEnum};
+use color_eyre::Result;
+use prettytable::{row, Table};
+use serde::Serialize;
+use stackify_common::types::{Environment, EnvironmentService};
+
+use crate::{
+    cli::{context::CliContext, env::keychain::format_thousands, theme::ThemedObject},
+    db::cli_db::CliDatabase,
+    docker::network_name,
+    util::names::service_container_name,
+};
+
+/// Shown in place of keychain secrets unless `--show-secrets` is given.
+const MASKED: &str = "********";
+
+#[derive(Debug, Args)]
+pub struct InspectArgs {
+    /// The name of the environment to inspect.
+    #[arg(required = true, value_name = "ENVIRONMENT")]
+    pub env_name: String,
+
+    /// Print the report in a machine-readable format instead.
+    #[arg(required = false, short, long, value_name = "FORMAT")]
+    pub output: Option<InspectOutputFormat>,
+
+    /// Show the keychains' private keys and mnemonics.
+    #[arg(required = false, long)]
+    pub show_secrets: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InspectOutputFormat {
+    Json,
+    Yaml,
+}
+
+/// The full report of an environment, as printed by `env inspect`.
+#[derive(Debug, Serialize)]
+struct EnvironmentReport {
+    name: String,
+    description: Option<String>,
+    bitcoin_block_speed: u32,
+    publish_ports: bool,
+    network: Option<NetworkReport>,
+    epochs: Vec<EpochReport>,
+    services: Vec<ServiceReport>,
+    keychains: Vec<KeychainReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct NetworkReport {
+    name: String,
+    id: Option<String>,
+    driver: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct EpochReport {
+    name: String,
+    starts_at_block_height: u32,
+    ends_at_block_height: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct ServiceReport {
+    name: String,
+    service_type: String,
+    version: String,
+    git_target: Option<String>,
+    last_build_commit: Option<String>,
+    last_built_at: Option<String>,
+    remark: Option<String>,
+    params: Vec<ParamReport>,
+    files: Vec<FileReport>,
+    ports: Vec<PortReport>,
+    container: Option<ContainerReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct ParamReport {
+    key: String,
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, Serialize)]
+struct FileReport {
+    filename: String,
+    destination: String,
+    /// Whether the environment uses its own version of the file rather than
+    /// the service type's default.
+    overridden: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct PortReport {
+    host_port: u16,
+    container_port: u16,
+    protocol: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ContainerReport {
+    name: String,
+    id: String,
+    state: Option<String>,
+    status: Option<String>,
+    /// The container's IP address in each of the networks it's attached to.
+    networks: BTreeMap<String, Option<String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct KeychainReport {
+    stx_address: String,
+    btc_address: String,
+    public_key: String,
+    private_key: String,
+    mnemonic: String,
+    balance: u64,
+    remark: Option<String>,
+}
+
+pub async fn exec(ctx: &CliContext, args: InspectArgs) -> Result<()> {
+    let env = ctx.db.as_clidb().load_environment(&args.env_name)?;
+    let report = build_report(ctx, &env, args.show_secrets).await?;
+
+    match args.output {
+        Some(InspectOutputFormat::Json) => println!("{}", serde_json::to_string_pretty(&report)?),
+        Some(InspectOutputFormat::Yaml) => print!("{}", serde_yaml::to_string(&report)?),
+        None => print_report(&report, args.show_secrets)?,
+    }
+
+    Ok(())
+}
+
+async fn build_report(
+    ctx: &CliContext,
+    env: &Environment,
+    show_secrets: bool,
+) -> Result<EnvironmentReport> {
+    let network = ctx
+        .docker()
+        .find_network_for_environment(&env.name)
+        .await?
+        .map(|(_, network)| NetworkReport {
+            name: network.name.unwrap_or_else(|| network_name(&env.name)),
+            id: network.id,
+            driver: network.driver,
+        });
+
+    let epochs = env
+        .epochs
+        .iter()
+        .map(|epoch| EpochReport {
+            name: epoch.epoch.name.clone(),
+            starts_at_block_height: epoch.starts_at_block_height,
+            ends_at_block_height: epoch.ends_at_block_height,
+        })
+        .collect();
+
+    let mut services = vec![];
+    for service in env.services.iter() {
+        services.push(service_report(ctx, service).await?);
+    }
+
+    let keychains = env
+        .keychains
+        .iter()
+        .map(|kc| KeychainReport {
+            stx_address: kc.stx_address.clone(),
+            btc_address: kc.btc_address.clone(),
+            public_key: kc.public_key.clone(),
+            private_key: mask(&kc.private_key, show_secrets),
+            mnemonic: mask(&kc.mnemonic, show_secrets),
+            balance: kc.amount,
+            remark: kc.remark.clone().filter(|remark| !remark.is_empty()),
+        })
+        .collect();
+
+    Ok(EnvironmentReport {
+        name: env.name.to_string(),
+        description: env.description.clone(),
+        bitcoin_block_speed: env.bitcoin_block_speed,
+        publish_ports: env.publish_ports,
+        network,
+        epochs,
+        services,
+        keychains,
+    })
+}
+
+async fn service_report(ctx: &CliContext, service: &EnvironmentService) -> Result<ServiceReport> {
+    // Files are overridden per environment; the others use the service type's
+    // default contents.
+    let overrides = ctx.db.list_environment_service_files(service.id)?;
+    let files = service
+        .file_headers
+        .iter()
+        .map(|file| FileReport {
+            filename: file.filename.clone(),
+            destination: file
+                .destination_dir
+                .join(&file.filename)
+                .to_string_lossy()
+                .into_owned(),
+            overridden: overrides
+                .iter()
+                .any(|esf| esf.service_type_file_id == file.id),
+        })
+        .collect();
+
+    let container_name = service_container_name(service);
+    let container = ctx
+        .docker()
+        .find_container_by_name(&container_name)
+        .await?
+        .map(|(id, summary)| ContainerReport {
+            name: container_name,
+            id: id.to_string(),
+            state: summary.state,
+            status: summary.status,
+            networks: summary
+                .network_settings
+                .and_then(|settings| settings.networks)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, endpoint)| (name, endpoint.ip_address))
+                .collect(),
+        });
+
+    Ok(ServiceReport {
+        name: service.name.clone(),
+        service_type: service.service_type.name.clone(),
+        version: service.version.version.clone(),
+        git_target: service
+            .version
+            .git_target
+            .as_ref()
+            .map(|target| format!("{} ({})", target.target, target.target_type)),
+        last_build_commit: service.version.last_build_commit_hash.clone(),
+        last_built_at: service.version.last_built_at.map(|at| at.to_string()),
+        remark: service.remark.clone().filter(|remark| !remark.is_empty()),
+        params: service
+            .params
+            .iter()
+            .map(|param| ParamReport {
+                key: param.param.key.clone(),
+                name: param.param.name.clone(),
+                value: param.value.clone(),
+            })
+            .collect(),
+        files,
+        ports: service
+            .port_mappings
+            .iter()
+            .map(|port| PortReport {
+                host_port: port.host_port,
+                container_port: port.container_port,
+                protocol: format!("{:?}", port.protocol).to_lowercase(),
+            })
+            .collect(),
+        container,
+    })
+}
+
+fn print_report(report: &EnvironmentReport, show_secrets: bool) -> Result<()> {
+    cliclack::intro(format!("Inspect Environment '{}'", report.name.magenta()).bold())?;
+
+    if let Some(description) = &report.description {
+        cliclack::log::info(description)?;
+    }
+    cliclack::log::info(format!(
+        "Bitcoin block speed: {} seconds\nPublish default ports: {}\nNetwork: {}",
+        report.bitcoin_block_speed,
+        report.publish_ports,
+        match &report.network {
+            Some(network) => format!(
+                "{} {}",
+                network.name,
+                network.driver.as_deref().unwrap_or_default().gray()
+            ),
+            None => "not created".gray().to_string(),
+        }
+    ))?;
+
+    cliclack::log::step("Epochs".bold())?;
+    let mut epochs_table = Table::new();
+    epochs_table.set_format(*prettytable::format::consts::FORMAT_BOX_CHARS);
+    epochs_table.set_titles(row![
+        "Epoch".table_header(),
+        "Starts At".table_header(),
+        "Ends At".table_header(),
+    ]);
+    for epoch in report.epochs.iter() {
+        epochs_table.add_row(row![
+            epoch.name,
+            epoch.starts_at_block_height,
+            epoch
+                .ends_at_block_height
+                .map_or("-".gray().to_string(), |height| height.to_string()),
+        ]);
+    }
+    print_table(&epochs_table)?;
+
+    cliclack::log::step("Services".bold())?;
+    let mut services_table = Table::new();
+    services_table.set_format(*prettytable::format::consts::FORMAT_BOX_CHARS);
+    services_table.set_titles(row!["Service".table_header(), "Details".table_header()]);
+    for service in report.services.iter() {
+        services_table.add_row(row![
+            format!("{}\n{}", service.name.bold(), service.service_type.gray()),
+            service_details_table(service),
+        ]);
+    }
+    print_table(&services_table)?;
+
+    cliclack::log::step("Keychains".bold())?;
+    let mut keychains_table = Table::new();
+    keychains_table.set_format(*prettytable::format::consts::FORMAT_BOX_CHARS);
+    keychains_table.set_titles(row![
+        "Address".table_header(),
+        "Balance".table_header(),
+        "Secrets".table_header(),
+    ]);
+    for kc in report.keychains.iter() {
+        keychains_table.add_row(row![
+            format!("{}\n{}", kc.stx_address, kc.btc_address.gray()),
+            format!("STX {}", format_thousands(kc.balance)),
+            format!("{}\n{}", kc.private_key, textwrap::fill(&kc.mnemonic, 60)),
+        ]);
+    }
+    print_table(&keychains_table)?;
+
+    if show_secrets {
+        cliclack::outro(format!("Environment '{}'", report.name))?;
+    } else {
+        cliclack::outro(format!(
+            "Keychain secrets are masked, use `{}` to show them",
+            "--show-secrets".bold()
+        ))?;
+    }
+
+    Ok(())
+}
+
+fn service_details_table(service: &ServiceReport) -> Table {
+    let mut table = Table::new();
+    table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+
+    table.add_row(row!["Version".bold(), service.version]);
+    table.add_row(row![
+        "Git Target".bold(),
+        or_none(service.git_target.as_deref())
+    ]);
+    table.add_row(row![
+        "Last Build".bold(),
+        match (&service.last_build_commit, &service.last_built_at) {
+            (Some(commit), Some(at)) => format!("{} {}", commit, format!("({})", at).gray()),
+            (Some(commit), None) => commit.clone(),
+            _ => "<never>".gray().to_string(),
+        }
+    ]);
+    table.add_row(row![
+        "Container".bold(),
+        match &service.container {
+            Some(container) => {
+                let mut text = format!(
+                    "{} {}",
+                    container.name,
+                    container.status.as_deref().unwrap_or_default().gray()
+                );
+                for (network, ip) in container.networks.iter() {
+                    text.push_str(&format!(
+                        "\n{} {}",
+                        network,
+                        ip.as_deref().unwrap_or_default().gray()
+                    ));
+                }
+                text
+            }
+            None => "<not created>".gray().to_string(),
+        }
+    ]);
+    table.add_row(row![
+        "Ports".bold(),
+        if service.ports.is_empty() {
+            "<none>".gray().to_string()
+        } else {
+            service
+                .ports
+                .iter()
+                .map(|port| {
+                    format!(
+                        "{} → {}/{}",
+                        port.host_port, port.container_port, port.protocol
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    ]);
+    table.add_row(row![
+        "Params".bold(),
+        if service.params.is_empty() {
+            "<none>".gray().to_string()
+        } else {
+            service
+                .params
+                .iter()
+                .map(|param| format!("{} = {}", param.key, param.value))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    ]);
+    table.add_row(row![
+        "Files".bold(),
+        service
+            .files
+            .iter()
+            .map(|file| {
+                if file.overridden {
+                    format!("{} {}", file.destination, "(overridden)".yellow())
+                } else {
+                    file.destination.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    ]);
+    table.add_row(row!["Remark".bold(), or_none(service.remark.as_deref())]);
+
+    table
+}
+
+fn print_table(table: &Table) -> Result<()> {
+    let mut lines = vec![];
+    table.print(&mut lines)?;
+
+    let table_str = String::from_utf8_lossy(&lines);
+    for line in table_str.lines() {
+        println!("{} {}", "│".bright_black(), line);
+    }
+
+    Ok(())
+}
+
+fn mask(secret: &str, show_secrets: bool) -> String {
+    if show_secrets {
+        secret.to_string()
+    } else {
+        MASKED.to_string()
+    }
+}
+
+fn or_none(value: Option<&str>) -> String {
+    value.map_or("<none>".gray().to_string(), str::to_string)
+}
@@ -1,26 +1,38 @@
-use clap::{Args, Subcommand};
-use color_eyre::{
-    eyre::{bail, eyre},
-    Result,
-};
-use futures_util::StreamExt;
+use std::{collections::HashSet, path::PathBuf};
+
+use clap::{Args, Subcommand, ValueEnum};
+use color_eyre::{eyre::bail, Result};
 use prettytable::row;
-use stackify_common::types::EnvironmentName;
+use serde::Serialize;
+use stackify_common::{
+    bitcoin::rpc::format_sats,
+    stacks::{
+        api::{
+            clarity::PrincipalData, client::StacksNodeClient, keychain::MakeKeychainResult,
+            transactions::StacksTransactionBuilder, v2::AccountInfo,
+        },
+        StacksAddress,
+    },
+    types::{Environment, EnvironmentKeychain, EnvironmentName},
+};
 use textwrap::Options;
 
 use crate::{
     cli::{
         context::CliContext,
-        env::prompt_environment_name,
-        log::clilog,
-        theme::{ThemedObject, THEME},
+        env::{
+            bitcoin_rpc_client, estimate_fee, next_nonce, prompt_environment_name,
+            stacks_node_client, wait_for_nonce,
+        },
+        theme::ThemedObject,
     },
     db::cli_db::CliDatabase,
-    docker_api::conn::TtyChunk,
     errors::CliError,
-    util::stacks_cli::MakeKeychainResult,
 };
 
+/// The maximum length of an STX transfer memo, in bytes.
+const MAX_MEMO_LEN: usize = 34;
+
 #[derive(Debug, Args)]
 pub struct KeychainArgs {
     #[command(subcommand)]
@@ -36,6 +48,13 @@ pub enum KeychainSubCommands {
     Remove(KeychainRemoveArgs),
     /// List all keychains for the environment.
     List(KeychainListArgs),
+    /// Imports an existing keychain from a mnemonic, private key or WIF.
+    Import(KeychainImportArgs),
+    /// Exports the environment's keychains as JSON or as Clarinet `Devnet.toml`
+    /// accounts.
+    Export(KeychainExportArgs),
+    /// Transfers STX from one of the environment's keychains to any principal.
+    Transfer(KeychainTransferArgs),
 }
 
 #[derive(Debug, Args)]
@@ -50,6 +69,123 @@ pub struct KeychainRemoveArgs {
     /// The stack address of the keychain to remove.
     #[arg(required = true, value_name = "STX_ADDRESS")]
     pub stx_address: String,
+
+    /// The name of the environment from which the keychain should be removed.
+    /// You can omit this argument if the keychain is unique across all
+    /// environments, otherwise you will receive an error.
+    #[arg(
+        required = false,
+        value_name = "NAME",
+        short = 'e',
+        long = "environment",
+        visible_alias = "env"
+    )]
+    pub env_name: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct KeychainImportArgs {
+    /// The name of the environment.
+    #[arg(required = false, value_name = "ENV_NAME")]
+    pub env_name: Option<String>,
+
+    /// A BIP39 mnemonic, hex-encoded private key or WIF. You will be prompted
+    /// for it if omitted.
+    #[arg(required = false, short, long, value_name = "SECRET")]
+    pub secret: Option<String>,
+
+    /// The starting balance of the keychain, in micro-STX.
+    #[arg(required = false, short, long, value_name = "MICRO_STX")]
+    pub balance: Option<u64>,
+
+    /// A short remark about the keychain.
+    #[arg(required = false, short, long)]
+    pub remark: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct KeychainExportArgs {
+    /// The name of the environment.
+    #[arg(required = true, value_name = "ENVIRONMENT")]
+    pub env_name: String,
+
+    /// The format to export the keychains in.
+    #[arg(required = false, short, long, value_enum, default_value_t = KeychainExportFormat::Json)]
+    pub format: KeychainExportFormat,
+
+    /// The file to write the keychains to. Prints to stdout if omitted.
+    #[arg(required = false, short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct KeychainTransferArgs {
+    /// The name of the environment.
+    #[arg(required = true, value_name = "ENVIRONMENT")]
+    pub env_name: String,
+
+    /// The principal to send the STX to, either a Stacks address or a
+    /// contract identifier.
+    #[arg(required = true, value_name = "RECIPIENT")]
+    pub recipient: String,
+
+    /// The amount to send, in micro-STX.
+    #[arg(required = true, value_name = "MICRO_STX")]
+    pub amount: u64,
+
+    /// The Stacks address of the keychain to send the STX from. You will be
+    /// prompted to select a keychain if omitted.
+    #[arg(required = false, long, value_name = "STX_ADDRESS")]
+    pub from: Option<String>,
+
+    /// A memo to attach to the transfer, at most 34 bytes.
+    #[arg(required = false, short, long)]
+    pub memo: Option<String>,
+
+    /// The fee to pay, in micro-STX. Estimated from the node's fee rate if
+    /// omitted.
+    #[arg(required = false, short, long, value_name = "MICRO_STX")]
+    pub fee: Option<u64>,
+
+    /// The name of the Stacks node to broadcast the transaction to. Defaults to
+    /// the first node with its RPC port published.
+    #[arg(required = false, short, long, value_name = "SERVICE")]
+    pub node: Option<String>,
+
+    /// Don't wait for the transfer to be anchored.
+    #[arg(required = false, long)]
+    pub no_wait: bool,
+}
+
+/// An STX transfer from one of the environment's keychains.
+pub struct StxTransfer<'a> {
+    pub from: &'a EnvironmentKeychain,
+    pub recipient: PrincipalData,
+    pub amount: u64,
+    pub memo: Option<String>,
+    pub fee: Option<u64>,
+    pub wait: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum KeychainExportFormat {
+    Json,
+    /// The `[accounts]` section of a Clarinet `Devnet.toml`.
+    Devnet,
+}
+
+/// A keychain as written by `env keychain export`.
+#[derive(Debug, Serialize)]
+struct ExportedKeychain<'a> {
+    stx_address: &'a str,
+    btc_address: &'a str,
+    public_key: &'a str,
+    private_key: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    mnemonic: &'a str,
+    balance: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remark: Option<&'a str>,
 }
 
 #[derive(Debug, Args)]
@@ -65,6 +201,9 @@ pub async fn exec(ctx: &CliContext, args: KeychainArgs) -> Result<()> {
         KeychainSubCommands::List(inner_args) => exec_list(ctx, inner_args).await,
         KeychainSubCommands::New(inner_args) => exec_new(ctx, inner_args).await,
         KeychainSubCommands::Remove(inner_args) => exec_remove(ctx, inner_args).await,
+        KeychainSubCommands::Import(inner_args) => exec_import(ctx, inner_args).await,
+        KeychainSubCommands::Export(inner_args) => exec_export(ctx, inner_args).await,
+        KeychainSubCommands::Transfer(inner_args) => exec_transfer(ctx, inner_args).await,
     }
 }
 
@@ -75,6 +214,16 @@ async fn exec_list(ctx: &CliContext, args: KeychainListArgs) -> Result<()> {
         &args.env_name.bold().magenta()
     ))?;
 
+    // Live balances are only available while the environment is running, in
+    // which case we query the nodes; otherwise we fall back to what we know.
+    let stacks = stacks_node_client(&env, None).await.ok();
+    let bitcoin = bitcoin_rpc_client(&env, None).await.ok();
+    if stacks.is_none() && bitcoin.is_none() && !env.keychains.is_empty() {
+        cliclack::log::warning(
+            "The environment's nodes could not be reached, only starting balances are shown",
+        )?;
+    }
+
     let mut table = prettytable::Table::new();
     table.set_format(*prettytable::format::consts::FORMAT_BOX_CHARS);
     table.set_titles(row!["Wallet".cyan().bold(), "Details".cyan().bold(),]);
@@ -82,16 +231,7 @@ async fn exec_list(ctx: &CliContext, args: KeychainListArgs) -> Result<()> {
     for kc in env.keychains.iter() {
         let mut details_table = prettytable::Table::new();
         details_table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
-        let starting_balance = kc
-            .amount
-            .to_string()
-            .as_bytes()
-            .rchunks(3)
-            .rev()
-            .map(std::str::from_utf8)
-            .collect::<Result<Vec<&str>, _>>()
-            .unwrap()
-            .join(",");
+        let starting_balance = format_thousands(kc.amount);
         let starting_balance_qualifier = if kc.amount >= 1_000_000_000_000_000 {
             "(Quadrillion)"
         } else if kc.amount >= 1_000_000_000_000 {
@@ -113,6 +253,19 @@ async fn exec_list(ctx: &CliContext, args: KeychainListArgs) -> Result<()> {
                 starting_balance_qualifier.gray()
             )
         ]);
+        if let (Some(client), Ok(address)) = (&stacks, kc.stx_address.parse::<StacksAddress>()) {
+            if let Ok(account) = client.get_account(&address.into()).await {
+                add_account_rows(&mut details_table, kc, &account);
+            }
+        }
+        if let Some(client) = &bitcoin {
+            if let Ok(sats) = client.get_address_balance(&kc.btc_address).await {
+                details_table.add_row(row![
+                    "BTC Balance".bold(),
+                    format!("BTC {}", format_sats(sats))
+                ]);
+            }
+        }
         details_table.add_row(row!["Public Key".bold(), kc.public_key]);
         details_table.add_row(row!["Private Key".bold(), kc.private_key]);
         details_table.add_row(row!["Mnemonic".bold(), textwrap::fill(&kc.mnemonic, 60)]);
@@ -158,7 +311,7 @@ async fn exec_new(ctx: &CliContext, args: KeychainNewArgs) -> Result<()> {
     };
     let env = ctx.db.load_environment(env_name.as_ref())?;
 
-    let keychain = generate_stacks_keychain(ctx).await?;
+    let keychain = generate_stacks_keychain()?;
 
     let balance: u64 = cliclack::input("Balance:")
         .required(false)
@@ -190,10 +343,39 @@ async fn exec_new(ctx: &CliContext, args: KeychainNewArgs) -> Result<()> {
 async fn exec_remove(ctx: &CliContext, args: KeychainRemoveArgs) -> Result<()> {
     cliclack::intro(format!("Remove keychain '{}'", &args.stx_address.bold()))?;
 
-    let keychain = match ctx
-        .db
-        .get_environment_keychain_by_stx_address(&args.stx_address)?
-    {
+    if let Err(e) = args.stx_address.parse::<StacksAddress>() {
+        bail!(CliError::Graceful {
+            title: "Invalid Stacks address".to_string(),
+            message: format!(
+                "'{}' is not a valid Stacks address: {}",
+                args.stx_address, e
+            )
+        });
+    }
+
+    let keychain = match args.env_name {
+        Some(env_name) => {
+            let env = ctx.db.load_environment(&env_name)?;
+            ctx.db
+                .get_environment_keychain_by_stx_address(env.id, &args.stx_address)?
+        }
+        None => {
+            let mut keychains = ctx
+                .db
+                .find_environment_keychains_by_stx_address(&args.stx_address)?;
+            if keychains.len() > 1 {
+                bail!(CliError::Graceful {
+                    title: "Keychain exists in multiple environments".to_string(),
+                    message: format!(
+                        "Please specify the environment using the `{}` argument.",
+                        "--environment".bold()
+                    )
+                });
+            }
+            keychains.pop()
+        }
+    };
+    let keychain = match keychain {
         Some(kc) => kc,
         None => {
             bail!(CliError::Graceful {
@@ -206,7 +388,8 @@ async fn exec_remove(ctx: &CliContext, args: KeychainRemoveArgs) -> Result<()> {
     let confirm = cliclack::confirm("Are you sure you want to remove this keychain?").interact()?;
 
     if confirm {
-        ctx.db.delete_environment_keychain(&keychain.stx_address)?;
+        ctx.db
+            .delete_environment_keychain(keychain.environment_id, &keychain.stx_address)?;
         cliclack::outro("Keychain has been successfully removed from the environment")?;
     } else {
         cliclack::outro("Keychain removal has been cancelled")?;
@@ -215,69 +398,408 @@ async fn exec_remove(ctx: &CliContext, args: KeychainRemoveArgs) -> Result<()> {
     Ok(())
 }
 
-async fn generate_stacks_keychain(ctx: &CliContext) -> Result<MakeKeychainResult> {
-    let generate_keychain_spinner = cliclack::spinner();
-    generate_keychain_spinner.start("Generating new keychain...");
-    let cli = ctx
-        .docker()
-        .api()
-        .containers()
-        .create(&ctx.docker().opts_for().generate_stacks_keychain())
-        .await?;
-    let mut cli_attach = cli.attach().await?;
-    let mut cli_stdout = vec![];
-    let mut cli_stderr = vec![];
-    cli.start().await?;
-    while let Some(result) = cli_attach.next().await {
-        match result {
-            Ok(chunk) => match chunk {
-                TtyChunk::StdOut(data) => {
-                    let str = String::from_utf8(data)?;
-                    cli_stdout.push(str);
-                }
-                TtyChunk::StdErr(data) => {
-                    let str = String::from_utf8(data)?;
-                    cli_stderr.push(str);
-                }
-                TtyChunk::StdIn(_) => {
-                    clilog!("received stdin tty chunk while executing stacks cli, this shouldn't happen");
-                }
-            },
-            Err(e) => {
-                cliclack::log::error(format!("Error: {}", e))?;
-            }
+async fn exec_import(ctx: &CliContext, args: KeychainImportArgs) -> Result<()> {
+    cliclack::intro("Import keychain".bold())?;
+    let env_name = match args.env_name {
+        Some(env_name) => EnvironmentName::new(&env_name)?,
+        None => prompt_environment_name(ctx)?,
+    };
+    let env = ctx.db.load_environment(env_name.as_ref())?;
+
+    let secret = match args.secret {
+        Some(secret) => secret,
+        None => cliclack::password("Mnemonic, private key or WIF:")
+            .mask('▪')
+            .interact()?,
+    };
+    let keychain = match MakeKeychainResult::from_secret(&secret, false) {
+        Ok(keychain) => keychain,
+        Err(e) => bail!(CliError::Graceful {
+            title: "Invalid keychain".to_string(),
+            message: format!(
+                "Expected a BIP39 mnemonic, a hex-encoded private key or a WIF: {}",
+                e
+            )
+        }),
+    };
+
+    if ctx
+        .db
+        .get_environment_keychain_by_stx_address(env.id, &keychain.key_info.address)?
+        .is_some()
+    {
+        bail!(CliError::Graceful {
+            title: "Keychain already exists".to_string(),
+            message: format!(
+                "The keychain '{}' already exists in the environment",
+                keychain.key_info.address
+            )
+        });
+    }
+    cliclack::log::info(format!("STX Address: {}", keychain.key_info.address))?;
+
+    let balance: u64 = match args.balance {
+        Some(balance) => balance,
+        None => cliclack::input("Balance:")
+            .required(false)
+            .placeholder("10000000000000000")
+            .default_input("10000000000000000")
+            .interact()?,
+    };
+
+    let remark: String = match args.remark {
+        Some(remark) => remark,
+        None => cliclack::input("Comment:")
+            .placeholder("Write a short remark about this keychain")
+            .required(false)
+            .interact()?,
+    };
+
+    ctx.db.add_environment_keychain(
+        env.id,
+        &keychain.key_info.address,
+        &keychain.key_info.btc_address,
+        &keychain.key_info.public_key,
+        &keychain.key_info.private_key,
+        &keychain.mnemonic,
+        balance,
+        &remark,
+    )?;
+
+    cliclack::outro("Keychain has been successfully imported into the environment")?;
+
+    Ok(())
+}
+
+async fn exec_export(ctx: &CliContext, args: KeychainExportArgs) -> Result<()> {
+    let env = ctx.db.as_clidb().load_environment(&args.env_name)?;
+
+    let output = match args.format {
+        KeychainExportFormat::Json => {
+            let keychains = env
+                .keychains
+                .iter()
+                .map(|kc| ExportedKeychain {
+                    stx_address: &kc.stx_address,
+                    btc_address: &kc.btc_address,
+                    public_key: &kc.public_key,
+                    private_key: &kc.private_key,
+                    mnemonic: &kc.mnemonic,
+                    balance: kc.amount,
+                    remark: kc.remark.as_deref().filter(|r| !r.is_empty()),
+                })
+                .collect::<Vec<_>>();
+            serde_json::to_string_pretty(&keychains)? + "\n"
         }
+        KeychainExportFormat::Devnet => devnet_accounts_toml(&env.keychains),
+    };
+
+    match args.output {
+        Some(path) => {
+            std::fs::write(&path, output)?;
+            cliclack::outro(format!(
+                "Exported {} keychains to '{}'",
+                env.keychains.len(),
+                path.display()
+            ))?;
+        }
+        None => print!("{}", output),
     }
 
-    let cli_result = cli.wait().await?;
-
-    if cli_result.status_code == 0 {
-        let keychain = MakeKeychainResult::from_json(&cli_stdout.join(""))?;
-        generate_keychain_spinner.stop(format!(
-            "{} Generate keychain",
-            THEME.read().unwrap().success_symbol()
-        ));
-        let mut msg_lines = vec![];
-        let wrapped_mnemonic = textwrap::wrap(
-            &keychain.mnemonic,
-            Options::new(80).subsequent_indent("                "),
-        );
-        msg_lines.push(
-            "A new keychain has been generated for this service. Here are the details:\n"
-                .cyan()
-                .to_string(),
-        );
-        msg_lines.push(format!("‣ Mnemonic:     {}", wrapped_mnemonic.join("\n")));
-        msg_lines.push(format!("‣ Private Key:  {}", keychain.key_info.private_key));
-        msg_lines.push(format!("‣ Public Key:   {}", keychain.key_info.public_key));
-        msg_lines.push(format!("‣ STX Address:  {}", keychain.key_info.address));
-        msg_lines.push(format!("‣ BTC Address:  {}", keychain.key_info.btc_address));
-        msg_lines.push(format!("‣ WIF:          {}", keychain.key_info.wif));
-        msg_lines.push(format!("‣ Index:        {}", keychain.key_info.index));
-        cliclack::note("Keychain", msg_lines.join("\n"))?;
-
-        Ok(keychain)
-    } else {
-        bail!("Failed to generate keychain: {:?}", cli_stderr);
+    Ok(())
+}
+
+async fn exec_transfer(ctx: &CliContext, args: KeychainTransferArgs) -> Result<()> {
+    cliclack::intro("Transfer STX".bold())?;
+    let env = ctx.db.as_clidb().load_environment(&args.env_name)?;
+    let recipient = parse_recipient(&args.recipient)?;
+    let from = select_keychain(
+        &env,
+        args.from.as_deref(),
+        "Select the keychain to send from",
+    )?;
+    let client = stacks_node_client(&env, args.node.as_deref()).await?;
+
+    transfer_stx(
+        ctx,
+        &client,
+        StxTransfer {
+            from,
+            recipient,
+            amount: args.amount,
+            memo: args.memo,
+            fee: args.fee,
+            wait: !args.no_wait,
+        },
+    )
+    .await?;
+
+    cliclack::outro(format!(
+        "Transfer {}",
+        if args.no_wait {
+            "broadcast"
+        } else {
+            "anchored"
+        }
+    ))?;
+
+    Ok(())
+}
+
+/// Parses the recipient of an STX transfer, failing gracefully.
+pub fn parse_recipient(recipient: &str) -> Result<PrincipalData> {
+    match recipient.parse::<PrincipalData>() {
+        Ok(principal) => Ok(principal),
+        Err(e) => bail!(CliError::Graceful {
+            title: "Invalid recipient".to_string(),
+            message: format!(
+                "'{}' is not a valid Stacks address or contract identifier: {}",
+                recipient, e
+            )
+        }),
     }
 }
+
+/// Signs and broadcasts an STX transfer, recording the keychain's next nonce
+/// so that transfers made in quick succession don't collide, and optionally
+/// waits for the transfer to be anchored.
+pub async fn transfer_stx(
+    ctx: &CliContext,
+    client: &StacksNodeClient,
+    transfer: StxTransfer<'_>,
+) -> Result<()> {
+    let memo = transfer.memo.unwrap_or_default();
+    if memo.len() > MAX_MEMO_LEN {
+        bail!(CliError::Graceful {
+            title: "Memo is too long".to_string(),
+            message: format!(
+                "The memo is {} bytes, but may be at most {} bytes",
+                memo.len(),
+                MAX_MEMO_LEN
+            )
+        });
+    }
+
+    let chain_id = client.get_info().await?.network_id;
+    let builder = StacksTransactionBuilder::from_keychain(transfer.from, chain_id)?.memo(memo);
+    let nonce = next_nonce(client, transfer.from).await?;
+    let builder = builder.nonce(nonce);
+
+    let fee = match transfer.fee {
+        Some(fee) => fee,
+        None => {
+            let tx = builder.token_transfer(transfer.recipient.clone(), transfer.amount)?;
+            estimate_fee(client, &tx).await?
+        }
+    };
+    let tx = builder
+        .fee(fee)
+        .token_transfer(transfer.recipient.clone(), transfer.amount)?;
+
+    let txid = match client.broadcast_transaction(&tx).await {
+        Ok(txid) => txid,
+        Err(e) => bail!(CliError::Graceful {
+            title: "Failed to transfer STX".to_string(),
+            message: e.to_string()
+        }),
+    };
+    ctx.db.set_environment_keychain_nonce(
+        transfer.from.environment_id,
+        &transfer.from.stx_address,
+        nonce + 1,
+    )?;
+    cliclack::log::success(format!(
+        "{} {} {} {}\n{} {}\n{} {}\n{} {}",
+        "STX".bold(),
+        format_thousands(transfer.amount),
+        "→".gray(),
+        transfer.recipient,
+        "from:".gray(),
+        transfer.from.stx_address,
+        "txid:".gray(),
+        txid,
+        "fee: ".gray(),
+        fee
+    ))?;
+
+    if transfer.wait {
+        let sender: StacksAddress = transfer.from.stx_address.parse()?;
+        wait_for_nonce(client, &sender.into(), nonce + 1).await?;
+    }
+
+    Ok(())
+}
+
+/// Returns the environment's keychain with the given Stacks address, or prompts
+/// the user to select one if no address was given.
+pub fn select_keychain<'a>(
+    env: &'a Environment,
+    stx_address: Option<&str>,
+    prompt: &str,
+) -> Result<&'a EnvironmentKeychain> {
+    if env.keychains.is_empty() {
+        bail!(CliError::Graceful {
+            title: "No keychains are configured".to_string(),
+            message: format!(
+                "Please add a keychain to the environment using the `{}` command first.",
+                "stackify env keychain new".bold()
+            )
+        });
+    }
+
+    match stx_address {
+        Some(stx_address) => match env
+            .keychains
+            .iter()
+            .find(|kc| kc.stx_address == stx_address)
+        {
+            Some(kc) => Ok(kc),
+            None => bail!(CliError::Graceful {
+                title: "Keychain not found".to_string(),
+                message: format!(
+                    "The keychain '{}' was not found in the environment",
+                    stx_address
+                )
+            }),
+        },
+        None => {
+            let items = env
+                .keychains
+                .iter()
+                .enumerate()
+                .map(|(i, kc)| {
+                    (
+                        i,
+                        kc.stx_address.as_str(),
+                        kc.remark.as_deref().unwrap_or(""),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let index = cliclack::select(prompt).items(&items).interact()?;
+            Ok(&env.keychains[index])
+        }
+    }
+}
+
+/// Renders keychains as Clarinet `[accounts.<name>]` tables. Accounts are named
+/// after their remark where it makes for a valid, unique name, e.g. `deployer`
+/// or `wallet_1`, and `wallet_<n>` otherwise. Clarinet can only derive accounts
+/// from mnemonics, so keychains imported from a private key are left out.
+fn devnet_accounts_toml(keychains: &[EnvironmentKeychain]) -> String {
+    let mut names = HashSet::new();
+    let mut toml = String::new();
+
+    for (i, kc) in keychains.iter().enumerate() {
+        if kc.mnemonic.is_empty() {
+            toml.push_str(&format!(
+                "# {} was imported from a private key and has no mnemonic\n\n",
+                kc.stx_address
+            ));
+            continue;
+        }
+
+        let name = kc
+            .remark
+            .as_deref()
+            .map(str::trim)
+            .filter(|r| {
+                !r.is_empty()
+                    && r.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    && !names.contains(*r)
+            })
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                (i + 1..)
+                    .map(|n| format!("wallet_{n}"))
+                    .find(|name| !names.contains(name))
+                    .unwrap()
+            });
+
+        toml.push_str(&format!("[accounts.{}]\n", name));
+        toml.push_str(&format!("mnemonic = \"{}\"\n", kc.mnemonic));
+        toml.push_str(&format!("balance = {}\n", kc.amount));
+        toml.push_str(&format!("# stx_address: {}\n", kc.stx_address));
+        toml.push_str(&format!("# btc_address: {}\n\n", kc.btc_address));
+        names.insert(name);
+    }
+
+    toml
+}
+
+/// Adds the live balance, locked amount, unlock height and nonce of a
+/// keychain's account to its details.
+fn add_account_rows(
+    table: &mut prettytable::Table,
+    kc: &EnvironmentKeychain,
+    account: &AccountInfo,
+) {
+    table.add_row(row![
+        "Balance".bold(),
+        format!("STX {}", format_thousands(account.balance))
+    ]);
+    table.add_row(row![
+        "Locked".bold(),
+        format!("STX {}", format_thousands(account.locked))
+    ]);
+    table.add_row(row![
+        "Unlock Height".bold(),
+        if account.unlock_height > 0 {
+            account.unlock_height.to_string()
+        } else {
+            "<none>".gray().to_string()
+        }
+    ]);
+    // Transactions we've broadcast but which haven't been anchored yet put our
+    // own nonce ahead of the node's.
+    table.add_row(row![
+        "Nonce".bold(),
+        if kc.nonce > account.nonce {
+            format!(
+                "{} {}",
+                account.nonce,
+                format!("({} pending)", kc.nonce - account.nonce).gray()
+            )
+        } else {
+            account.nonce.to_string()
+        }
+    ]);
+}
+
+/// Formats an amount with thousands separators, e.g. `1,000,000`.
+pub fn format_thousands(amount: impl ToString) -> String {
+    amount
+        .to_string()
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(std::str::from_utf8)
+        .collect::<Result<Vec<&str>, _>>()
+        .unwrap()
+        .join(",")
+}
+
+fn generate_stacks_keychain() -> Result<MakeKeychainResult> {
+    let keychain = MakeKeychainResult::generate(false)?;
+
+    let mut msg_lines = vec![];
+    let wrapped_mnemonic = textwrap::wrap(
+        &keychain.mnemonic,
+        Options::new(80).subsequent_indent("                "),
+    );
+    msg_lines.push(
+        "A new keychain has been generated for this service. Here are the details:\n"
+            .cyan()
+            .to_string(),
+    );
+    msg_lines.push(format!("‣ Mnemonic:     {}", wrapped_mnemonic.join("\n")));
+    msg_lines.push(format!("‣ Private Key:  {}", keychain.key_info.private_key));
+    msg_lines.push(format!("‣ Public Key:   {}", keychain.key_info.public_key));
+    msg_lines.push(format!("‣ STX Address:  {}", keychain.key_info.address));
+    msg_lines.push(format!("‣ BTC Address:  {}", keychain.key_info.btc_address));
+    msg_lines.push(format!("‣ WIF:          {}", keychain.key_info.wif));
+    msg_lines.push(format!("‣ Index:        {}", keychain.key_info.index));
+    cliclack::note("Keychain", msg_lines.join("\n"))?;
+
+    Ok(keychain)
+}
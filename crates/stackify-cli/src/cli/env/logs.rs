@@ -0,0 +1,370 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{LineWriter, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use clap::Args;
+use color_eyre::{eyre::bail, Result};
+use futures_util::{stream::select_all, StreamExt};
+use regex::Regex;
+use stackify_common::types::Environment;
+
+use crate::{
+    cli::{context::CliContext, theme::ThemedObject},
+    db::cli_db::CliDatabase,
+    docker::{opts::ListContainers, LabelKey},
+    docker_api::{
+        conn::TtyChunk,
+        opts::{ContainerListOpts, LogsOpts},
+        Container,
+    },
+    errors::CliError,
+};
+
+#[derive(Debug, Args)]
+pub struct LogsArgs {
+    /// The name of the environment.
+    #[arg(required = true, value_name = "ENVIRONMENT")]
+    pub env_name: String,
+
+    /// Only show the logs of the given service. Can be specified multiple
+    /// times, defaults to all services.
+    #[arg(required = false, short, long = "service", value_name = "SERVICE")]
+    pub services: Vec<String>,
+
+    /// Keep streaming new log lines until interrupted with Ctrl+C.
+    #[arg(required = false, short, long)]
+    pub follow: bool,
+
+    /// Only show lines logged within the given period, e.g. `30s`, `10m`, `2h`
+    /// or `1d`.
+    #[arg(required = false, long, value_name = "DURATION", value_parser = parse_duration)]
+    pub since: Option<Duration>,
+
+    /// Only show lines matching the given regular expression.
+    #[arg(required = false, short, long, value_name = "REGEX")]
+    pub grep: Option<String>,
+
+    /// Also write the combined log, without colours, to the given file.
+    #[arg(required = false, short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// A service container whose logs are being read.
+struct LogSource {
+    name: String,
+    /// The name, padded to the width of the longest one and coloured.
+    prefix: String,
+    container: Container,
+}
+
+/// A single log line, as returned by Docker with a timestamp.
+struct LogLine {
+    /// The timestamp, normalized so that lines sort in time order.
+    timestamp: String,
+    source: usize,
+    text: String,
+}
+
+/// Writes log lines to the terminal and, optionally, a file.
+struct LogPrinter<'a> {
+    sources: &'a [LogSource],
+    grep: Option<Regex>,
+    file: Option<LineWriter<File>>,
+}
+
+pub async fn exec(ctx: &CliContext, args: LogsArgs) -> Result<()> {
+    let env = ctx.db.as_clidb().load_environment(&args.env_name)?;
+
+    let grep = match args.grep.as_deref().map(Regex::new).transpose() {
+        Ok(grep) => grep,
+        Err(e) => bail!(CliError::Graceful {
+            title: "Invalid regular expression".to_string(),
+            message: e.to_string()
+        }),
+    };
+
+    let sources = log_sources(ctx, &env, &args.services).await?;
+    if sources.is_empty() {
+        bail!(CliError::Graceful {
+            title: "No containers found".to_string(),
+            message: format!(
+                "The environment has no service containers yet, use `{}` to create them.",
+                "stackify env start".bold()
+            )
+        });
+    }
+
+    let file = match &args.output {
+        Some(path) => Some(LineWriter::new(File::create(path)?)),
+        None => None,
+    };
+    let mut printer = LogPrinter {
+        sources: &sources,
+        grep,
+        file,
+    };
+
+    // Following picks up from when the backlog was read. `since` only has a
+    // resolution of seconds, so lines already printed are skipped then.
+    let started_at = time::OffsetDateTime::now_utc().unix_timestamp();
+    let since = args
+        .since
+        .map(|since| started_at.saturating_sub(i64::try_from(since.as_secs()).unwrap_or(i64::MAX)));
+
+    // The backlog is read in full first, so that lines from all containers
+    // can be sorted by their timestamps.
+    let mut backlog = vec![];
+    for (i, source) in sources.iter().enumerate() {
+        let mut stream = source.container.logs(&logs_opts(false, since));
+        while let Some(chunk) = stream.next().await {
+            backlog.extend(parse_chunk(i, &chunk?));
+        }
+    }
+    backlog.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    let mut last_seen = HashMap::new();
+    for line in backlog {
+        last_seen.insert(line.source, line.timestamp.clone());
+        printer.print(&line)?;
+    }
+
+    if args.follow {
+        follow(ctx, &sources, &mut printer, &last_seen, started_at).await?;
+    }
+
+    if let Some(path) = &args.output {
+        cliclack::log::success(format!("Saved the log to {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Streams new lines from all sources until they're stopped or the user
+/// presses Ctrl+C, skipping those already printed from the backlog.
+async fn follow(
+    ctx: &CliContext,
+    sources: &[LogSource],
+    printer: &mut LogPrinter<'_>,
+    last_seen: &HashMap<usize, String>,
+    started_at: i64,
+) -> Result<()> {
+    // New lines arrive as they're written, so they only need to be merged.
+    let mut shutdown = ctx.tx.as_ref().map(|tx| tx.subscribe());
+    let mut streams = select_all(sources.iter().enumerate().map(|(i, source)| {
+        source
+            .container
+            .logs(&logs_opts(true, Some(started_at)))
+            .map(move |chunk| (i, chunk))
+            .boxed_local()
+    }));
+    loop {
+        let next = match shutdown.as_mut() {
+            Some(receiver) => tokio::select! {
+                next = streams.next() => next,
+                _ = receiver.recv() => break,
+            },
+            None => streams.next().await,
+        };
+        let Some((i, chunk)) = next else {
+            break;
+        };
+        for line in parse_chunk(i, &chunk?) {
+            if last_seen
+                .get(&i)
+                .is_some_and(|last| line.timestamp <= *last)
+            {
+                continue;
+            }
+            printer.print(&line)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The containers of the environment's services, optionally limited to the
+/// services named in `filter`.
+async fn log_sources(
+    ctx: &CliContext,
+    env: &Environment,
+    filter: &[String],
+) -> Result<Vec<LogSource>> {
+    if let Some(name) = filter
+        .iter()
+        .find(|name| !env.services.iter().any(|svc| svc.name == **name))
+    {
+        bail!(CliError::Graceful {
+            title: "Service not found".to_string(),
+            message: format!(
+                "The environment '{}' has no service named '{}'.",
+                env.name, name
+            )
+        });
+    }
+
+    let containers = ctx
+        .docker()
+        .api()
+        .containers()
+        .list(&ContainerListOpts::for_environment(&env.name, true))
+        .await?;
+
+    // Containers are matched to services through their labels, which leaves
+    // out the environment container.
+    let mut services = vec![];
+    for service in env
+        .services
+        .iter()
+        .filter(|svc| filter.is_empty() || filter.contains(&svc.name))
+    {
+        let service_id = service.id.to_string();
+        let id = containers.iter().find_map(|container| {
+            container
+                .labels
+                .as_ref()?
+                .get(&LabelKey::ServiceId.to_string())
+                .filter(|id| **id == service_id)
+                .and(container.id.clone())
+        });
+        match id {
+            Some(id) => services.push((service.name.clone(), id)),
+            None => cliclack::log::warning(format!(
+                "The service '{}' has no container yet",
+                service.name
+            ))?,
+        }
+    }
+
+    let width = services
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default();
+    Ok(services
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, id))| {
+            let padded = format!("{:<width$}", name);
+            let prefix = match i % 5 {
+                0 => padded.cyan().to_string(),
+                1 => padded.magenta().to_string(),
+                2 => padded.yellow().to_string(),
+                3 => padded.green().to_string(),
+                _ => padded.blue().to_string(),
+            };
+            LogSource {
+                name,
+                prefix,
+                container: ctx.docker().api().containers().get(id),
+            }
+        })
+        .collect())
+}
+
+fn logs_opts(follow: bool, since: Option<i64>) -> LogsOpts {
+    let builder = LogsOpts::builder()
+        .stdout(true)
+        .stderr(true)
+        .timestamps(true)
+        .follow(follow)
+        .all();
+    match since {
+        Some(since) => builder.since(since).build(),
+        None => builder.build(),
+    }
+}
+
+/// Splits a chunk of container output into timestamped lines. Docker prefixes
+/// every line with its timestamp when asked to, in RFC 3339 format.
+fn parse_chunk(source: usize, chunk: &TtyChunk) -> Vec<LogLine> {
+    let bytes: &[u8] = match chunk {
+        TtyChunk::StdOut(bytes) | TtyChunk::StdErr(bytes) => bytes,
+        TtyChunk::StdIn(_) => return vec![],
+    };
+
+    let mut lines: Vec<LogLine> = vec![];
+    for line in String::from_utf8_lossy(bytes).lines() {
+        match line.split_once(' ') {
+            Some((timestamp, text)) if timestamp.ends_with('Z') => lines.push(LogLine {
+                timestamp: normalize_timestamp(timestamp),
+                source,
+                text: text.to_string(),
+            }),
+            // Lines without a timestamp are part of the previous one.
+            _ => match lines.last_mut() {
+                Some(last) => {
+                    last.text.push('\n');
+                    last.text.push_str(line);
+                }
+                None => lines.push(LogLine {
+                    timestamp: String::new(),
+                    source,
+                    text: line.to_string(),
+                }),
+            },
+        }
+    }
+    lines
+}
+
+/// Pads the fractional seconds of a timestamp to nanoseconds, as Docker trims
+/// trailing zeros, which would otherwise break sorting them as strings.
+fn normalize_timestamp(timestamp: &str) -> String {
+    let timestamp = timestamp.trim_end_matches('Z');
+    match timestamp.split_once('.') {
+        Some((seconds, fraction)) => format!("{}.{:0<9}", seconds, fraction),
+        None => format!("{}.000000000", timestamp),
+    }
+}
+
+impl LogPrinter<'_> {
+    fn print(&mut self, line: &LogLine) -> Result<()> {
+        if let Some(grep) = &self.grep {
+            if !grep.is_match(&line.text) {
+                return Ok(());
+            }
+        }
+
+        let source = &self.sources[line.source];
+        for text in line.text.lines() {
+            println!("{} {} {}", source.prefix, "|".bright_black(), text);
+            if let Some(file) = &mut self.file {
+                writeln!(file, "{} | {}", source.name, text)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a duration such as `30s`, `10m`, `2h` or `1d`. A plain number is
+/// taken as seconds.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let (value, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("Invalid duration '{}'", s))?;
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => {
+            return Err(format!(
+                "Invalid duration unit '{}', use s, m, h or d",
+                unit
+            ))
+        }
+    };
+    let seconds = value
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("Invalid duration '{}'", s))?;
+    Ok(Duration::from_secs(seconds))
+}
@@ -1,5 +1,18 @@
+use std::time::{Duration, Instant};
+
 use color_eyre::eyre::{bail, Result};
-use stackify_common::types::EnvironmentName;
+use stackify_common::{
+    bitcoin::rpc::BitcoinRpcClient,
+    stacks::{
+        api::{
+            clarity::PrincipalData, client::StacksNodeClient, codec::StacksMessageCodec,
+            transactions::StacksTransaction,
+        },
+        StacksAddress,
+    },
+    types::{Environment, EnvironmentKeychain, EnvironmentName},
+    ServiceType,
+};
 
 use crate::cli::context::CliContext;
 use crate::cli::theme::ThemedObject;
@@ -10,44 +23,48 @@ use self::epoch::exec_epoch;
 use self::service::exec_service;
 
 pub mod args;
+pub mod bitcoin;
 pub mod build;
 pub mod contract;
 pub mod down;
 pub mod epoch;
+pub mod exec;
+pub mod faucet;
+pub mod inspect;
 pub mod keychain;
 pub mod list;
+pub mod logs;
+pub mod remove;
 pub mod service;
+pub mod set;
 pub mod start;
+pub mod status;
 pub mod stop;
 
 pub async fn exec(ctx: &CliContext, args: EnvArgs) -> Result<()> {
     match args.commands {
         args::EnvSubCommands::List(inner_args) => list::exec(ctx, inner_args).await,
         args::EnvSubCommands::New(inner_args) => exec_create(ctx, inner_args).await,
-        args::EnvSubCommands::Remove(inner_args) => exec_delete(ctx, inner_args).await,
+        args::EnvSubCommands::Remove(inner_args) => remove::exec(ctx, inner_args).await,
         args::EnvSubCommands::Start(inner_args) => start::exec(ctx, inner_args).await,
         args::EnvSubCommands::Stop(inner_args) => stop::exec(ctx, inner_args).await,
-        args::EnvSubCommands::Inspect(inner_args) => exec_inspect(ctx, inner_args).await,
+        args::EnvSubCommands::Status(inner_args) => status::exec(ctx, inner_args).await,
+        args::EnvSubCommands::Logs(inner_args) => logs::exec(ctx, inner_args).await,
+        args::EnvSubCommands::Exec(inner_args) => exec::exec(ctx, inner_args).await,
+        args::EnvSubCommands::Shell(inner_args) => exec::shell(ctx, inner_args).await,
+        args::EnvSubCommands::Inspect(inner_args) => inspect::exec(ctx, inner_args).await,
         args::EnvSubCommands::Down(inner_args) => down::exec(ctx, inner_args).await,
         args::EnvSubCommands::Build(inner_args) => build::exec(ctx, inner_args).await,
         args::EnvSubCommands::Service(inner_args) => exec_service(ctx, inner_args).await,
         args::EnvSubCommands::Epoch(inner_args) => exec_epoch(ctx, inner_args),
-        args::EnvSubCommands::Set(inner_args) => exec_set(ctx, inner_args).await,
+        args::EnvSubCommands::Set(inner_args) => set::exec(ctx, inner_args).await,
         args::EnvSubCommands::Keychain(inner_args) => keychain::exec(ctx, inner_args).await,
         args::EnvSubCommands::Contract(inner_args) => contract::exec(ctx, inner_args).await,
+        args::EnvSubCommands::Faucet(inner_args) => faucet::exec(ctx, inner_args).await,
+        args::EnvSubCommands::Bitcoin(inner_args) => bitcoin::exec(ctx, inner_args).await,
     }
 }
 
-async fn exec_set(_ctx: &CliContext, args: args::SetArgs) -> Result<()> {
-    println!("Set environment: {}", args.env_name);
-    Ok(())
-}
-
-async fn exec_inspect(_ctx: &CliContext, args: args::InspectArgs) -> Result<()> {
-    println!("Inspect environment: {}", args.env_name);
-    Ok(())
-}
-
 async fn exec_create(ctx: &CliContext, args: args::NewArgs) -> Result<()> {
     let env_name = EnvironmentName::new(&args.env_name)?;
     let env = ctx
@@ -57,11 +74,6 @@ async fn exec_create(ctx: &CliContext, args: args::NewArgs) -> Result<()> {
     Ok(())
 }
 
-async fn exec_delete(_ctx: &CliContext, _args: args::RemoveArgs) -> Result<()> {
-    println!("Delete environment");
-    Ok(())
-}
-
 /// Prompts the user to select an environment from the list of available environments.
 /// This function is used when the user does not provide an environment name as an argument
 /// and is required to select an environment interactively.
@@ -89,3 +101,189 @@ pub fn prompt_environment_name(ctx: &CliContext) -> Result<EnvironmentName> {
 
     Ok(EnvironmentName::new(env_name)?)
 }
+
+/// The container port on which Stacks nodes serve their RPC API.
+const STACKS_NODE_RPC_PORT: u16 = 20443;
+/// The container port and credentials of bitcoind's RPC API, see
+/// `assets/bitcoin.conf.hbs`.
+const BITCOIN_RPC_PORT: u16 = 18443;
+const BITCOIN_RPC_USER: &str = "bitcoin";
+const BITCOIN_RPC_PASSWORD: &str = "bitcoin";
+/// How long to wait for transactions to be anchored.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(600);
+const WAIT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Creates a client for the RPC API of one of the environment's Stacks nodes,
+/// either the one named `node_name` or the first which has its RPC port
+/// published on the host. Fails gracefully if the node can't be reached.
+pub async fn stacks_node_client(
+    env: &Environment,
+    node_name: Option<&str>,
+) -> Result<StacksNodeClient> {
+    let nodes = env
+        .services
+        .iter()
+        .filter(|svc| {
+            svc.service_type.id == ServiceType::StacksMiner as i32
+                || svc.service_type.id == ServiceType::StacksFollower as i32
+        })
+        .filter(|svc| node_name.is_none_or(|name| svc.name == name))
+        .collect::<Vec<_>>();
+
+    if nodes.is_empty() {
+        bail!(CliError::Graceful {
+            title: "No Stacks node found".to_string(),
+            message: match node_name {
+                Some(name) => format!(
+                    "The environment '{}' has no Stacks node named '{}'.",
+                    env.name, name
+                ),
+                None => format!("The environment '{}' has no Stacks nodes.", env.name),
+            }
+        });
+    }
+
+    let Some((node, host_port)) = nodes.iter().find_map(|svc| {
+        svc.port_mappings
+            .iter()
+            .find(|map| map.container_port == STACKS_NODE_RPC_PORT)
+            .map(|map| (svc, map.host_port))
+    }) else {
+        bail!(CliError::Graceful {
+            title: "Stacks node RPC port is not published".to_string(),
+            message: format!(
+                "Publish the RPC port ({}) of a Stacks node using the `{}` command first.",
+                STACKS_NODE_RPC_PORT,
+                "stackify env service publish".bold()
+            )
+        });
+    };
+
+    let client = StacksNodeClient::new(&format!("http://localhost:{}", host_port))?;
+    if let Err(e) = client.get_info().await {
+        bail!(CliError::Graceful {
+            title: format!("Could not reach the Stacks node '{}'", node.name),
+            message: format!(
+                "Make sure the environment is running (`{}`): {}",
+                "stackify env start".bold(),
+                e
+            )
+        });
+    }
+
+    Ok(client)
+}
+
+/// Creates a client for the RPC API of one of the environment's Bitcoin nodes,
+/// either the one named `node_name` or the first which has its RPC port
+/// published on the host, preferring the miner. Fails gracefully if the node
+/// can't be reached.
+pub async fn bitcoin_rpc_client(
+    env: &Environment,
+    node_name: Option<&str>,
+) -> Result<BitcoinRpcClient> {
+    let mut nodes = env
+        .services
+        .iter()
+        .filter(|svc| {
+            svc.service_type.id == ServiceType::BitcoinMiner as i32
+                || svc.service_type.id == ServiceType::BitcoinFollower as i32
+        })
+        .filter(|svc| node_name.is_none_or(|name| svc.name == name))
+        .collect::<Vec<_>>();
+    nodes.sort_by_key(|svc| svc.service_type.id != ServiceType::BitcoinMiner as i32);
+
+    if nodes.is_empty() {
+        bail!(CliError::Graceful {
+            title: "No Bitcoin node found".to_string(),
+            message: match node_name {
+                Some(name) => format!(
+                    "The environment '{}' has no Bitcoin node named '{}'.",
+                    env.name, name
+                ),
+                None => format!("The environment '{}' has no Bitcoin nodes.", env.name),
+            }
+        });
+    }
+
+    let Some((node, host_port)) = nodes.iter().find_map(|svc| {
+        svc.port_mappings
+            .iter()
+            .find(|map| map.container_port == BITCOIN_RPC_PORT)
+            .map(|map| (svc, map.host_port))
+    }) else {
+        bail!(CliError::Graceful {
+            title: "Bitcoin node RPC port is not published".to_string(),
+            message: format!(
+                "Publish the RPC port ({}) of a Bitcoin node using the `{}` command first.",
+                BITCOIN_RPC_PORT,
+                "stackify env service publish".bold()
+            )
+        });
+    };
+
+    let client = BitcoinRpcClient::new(
+        &format!("http://localhost:{}", host_port),
+        BITCOIN_RPC_USER,
+        BITCOIN_RPC_PASSWORD,
+    )?;
+    if let Err(e) = client.get_blockchain_info().await {
+        bail!(CliError::Graceful {
+            title: format!("Could not reach the Bitcoin node '{}'", node.name),
+            message: format!(
+                "Make sure the environment is running (`{}`): {}",
+                "stackify env start".bold(),
+                e
+            )
+        });
+    }
+
+    Ok(client)
+}
+
+/// The next nonce to use for a keychain. This is the account's nonce on the
+/// node, unless transactions broadcast earlier are still pending, in which case
+/// the nonce recorded for the keychain is ahead of it.
+pub async fn next_nonce(
+    client: &StacksNodeClient,
+    keychain: &EnvironmentKeychain,
+) -> Result<u64> {
+    let address: StacksAddress = keychain.stx_address.parse()?;
+    let nonce = client.get_account(&address.into()).await?.nonce;
+    Ok(nonce.max(keychain.nonce))
+}
+
+/// Estimates the fee for a transaction of the same size as `tx`, from the
+/// node's fee rate.
+pub async fn estimate_fee(client: &StacksNodeClient, tx: &StacksTransaction) -> Result<u64> {
+    let size = tx.serialize_to_vec()?.len() as u64;
+    Ok(client.get_transfer_fee_rate().await? * size)
+}
+
+/// Waits until the account's nonce has reached `next_nonce`, i.e. until all of
+/// its transactions before then have been anchored.
+pub async fn wait_for_nonce(
+    client: &StacksNodeClient,
+    account: &PrincipalData,
+    next_nonce: u64,
+) -> Result<()> {
+    let spinner = cliclack::spinner();
+    spinner.start("Waiting for the transactions to be anchored...");
+    let started = Instant::now();
+    while client.get_account(account).await?.nonce < next_nonce {
+        if started.elapsed() > WAIT_TIMEOUT {
+            spinner.stop(format!("{} {}", "✖".red(), "Timed out"));
+            bail!(CliError::Graceful {
+                title: "Timed out waiting for the transactions".to_string(),
+                message: format!(
+                    "The transactions were not anchored within {} seconds. Is the environment mining blocks?",
+                    WAIT_TIMEOUT.as_secs()
+                )
+            });
+        }
+        tokio::time::sleep(WAIT_POLL_INTERVAL).await;
+    }
+    spinner.stop(format!("{} {}", "✔".green(), "Transactions anchored"));
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use cliclack::{intro, multi_progress, outro, outro_cancel};
+use color_eyre::{eyre::bail, Result};
+use stackify_common::types::Environment;
+
+use crate::{
+    cli::{context::CliContext, theme::ThemedObject},
+    db::cli_db::CliDatabase,
+    docker::{network_name, opts::ListContainers, ContainerState},
+    docker_api::opts::{ContainerListOpts, ContainerStopOpts},
+    errors::CliError,
+};
+
+use super::args::RemoveArgs;
+
+pub async fn exec(ctx: &CliContext, args: RemoveArgs) -> Result<()> {
+    intro("Remove Environment".bold())?;
+    let env = ctx.db.as_clidb().load_environment(&args.env_name)?;
+
+    if !args.yes {
+        let confirm = cliclack::confirm(format!(
+            "Are you sure you want to remove the environment {} along with its {} services, containers and data? This cannot be undone.",
+            env.name.bold(),
+            env.services.len()
+        ))
+        .interact()?;
+
+        if !confirm {
+            outro_cancel("Aborted by user")?;
+            return Ok(());
+        }
+    }
+
+    // The environment is only deleted from the database once its Docker
+    // resources are gone, so that a failed teardown can simply be retried.
+    let mut left_behind = remove_containers(ctx, &env).await?;
+    left_behind.extend(remove_network(ctx, &env).await?);
+
+    if !left_behind.is_empty() {
+        for item in left_behind.iter() {
+            cliclack::log::warning(item)?;
+        }
+        bail!(CliError::Graceful {
+            title: "Failed to remove all Docker resources".to_string(),
+            message: format!(
+                "The environment '{}' has not been removed. Remove the resources listed above manually (e.g. using `{}`) and run this command again.",
+                env.name,
+                "docker rm -f".bold()
+            )
+        });
+    }
+
+    ctx.db.delete_environment(env.name.as_ref())?;
+
+    outro(format!("The environment {} has been removed", env.name.bold()).green())?;
+
+    Ok(())
+}
+
+/// Stops and removes all of the environment's containers, returning a
+/// description of each one which couldn't be removed.
+async fn remove_containers(ctx: &CliContext, env: &Environment) -> Result<Vec<String>> {
+    let multi = multi_progress("Removing containers");
+
+    let containers = ctx
+        .docker()
+        .api()
+        .containers()
+        .list(&ContainerListOpts::for_environment(&env.name, true))
+        .await?;
+
+    if containers.is_empty() {
+        let spinner = multi.add(cliclack::spinner());
+        spinner.stop(format!(
+            "{} {}",
+            "✔".green(),
+            "This environment has no containers"
+        ));
+        multi.stop();
+        return Ok(vec![]);
+    }
+
+    let mut left_behind = vec![];
+    for summary in containers {
+        let spinner = multi.add(cliclack::spinner());
+        let container_name = summary
+            .names
+            .map(|names| names.join(", "))
+            .unwrap_or_default();
+        spinner.start(format!("Stopping container: {}", container_name));
+        let Some(container_id) = summary.id else {
+            spinner.stop(format!(
+                "{} Container {} has no ID",
+                "✖".red(),
+                container_name
+            ));
+            left_behind.push(format!("Container {}", container_name));
+            continue;
+        };
+
+        let container = ctx.docker().api().containers().get(container_id);
+
+        let running = summary
+            .state
+            .as_deref()
+            .map(ContainerState::parse)
+            .transpose()?
+            == Some(ContainerState::Running);
+        if running {
+            if let Err(e) = container
+                .stop(&ContainerStopOpts::builder().signal("SIGKILL").build())
+                .await
+            {
+                spinner.stop(format!(
+                    "{} Failed to stop container {}: {}",
+                    "✖".red(),
+                    container_name,
+                    e
+                ));
+                left_behind.push(format!("Container {}", container_name));
+                continue;
+            }
+        }
+
+        spinner.set_message(format!("Removing container: {}", container_name));
+        if let Err(e) = container.delete().await {
+            spinner.stop(format!(
+                "{} Failed to remove container {}: {}",
+                "✖".red(),
+                container_name,
+                e
+            ));
+            left_behind.push(format!("Container {}", container_name));
+            continue;
+        }
+
+        spinner.stop(format!(
+            "{} Container {} removed",
+            "✔".green(),
+            container_name.magenta()
+        ));
+    }
+
+    multi.stop();
+
+    Ok(left_behind)
+}
+
+/// Removes the environment's network, returning a description of it if it
+/// couldn't be removed.
+async fn remove_network(ctx: &CliContext, env: &Environment) -> Result<Option<String>> {
+    let multi = multi_progress("Removing network");
+
+    let network_name = network_name(&env.name);
+    let spinner = multi.add(cliclack::spinner());
+    spinner.start(format!("Removing network: {}", &network_name));
+
+    let mut left_behind = None;
+    if let Some((id, _)) = ctx.docker().find_network_for_environment(&env.name).await? {
+        match ctx.docker().api().networks().get(id).delete().await {
+            Ok(_) => spinner.stop(format!(
+                "{} Network {} removed",
+                "✔".green(),
+                &network_name.magenta()
+            )),
+            Err(e) => {
+                spinner.stop(format!(
+                    "{} Failed to remove network {}: {}",
+                    "✖".red(),
+                    network_name,
+                    e
+                ));
+                left_behind = Some(format!("Network {}", network_name));
+            }
+        }
+    } else {
+        spinner.stop(format!(
+            "{} Network {} does not exist",
+            "✔".green(),
+            &network_name.magenta()
+        ));
+    }
+
+    multi.stop();
+
+    Ok(left_behind)
+}
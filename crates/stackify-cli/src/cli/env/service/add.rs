@@ -195,7 +195,7 @@ pub async fn exec(ctx: &CliContext, args: ServiceAddArgs) -> Result<()> {
 
         let keychain = ctx
             .db
-            .get_environment_keychain_by_stx_address(&stx_address)?;
+            .get_environment_keychain_by_stx_address(env.id, &stx_address)?;
 
         if let Some(keychain) = keychain {
             Some(keychain.stx_address)
@@ -1,14 +1,20 @@
 use clap::Args;
-use color_eyre::Result;
+use cliclack::{intro, outro};
+use color_eyre::{eyre::bail, Result};
 use stackify_common::types::EnvironmentName;
 
-use crate::cli::context::CliContext;
+use crate::{
+    cli::{context::CliContext, env::prompt_environment_name, theme::ThemedObject},
+    db::cli_db::CliDatabase,
+    docker::service_container_name,
+    errors::CliError,
+};
 
 #[derive(Debug, Args)]
 pub struct ServicePublishArgs {
     /// The name of the service to publish.
     #[arg(
-        required = false, 
+        required = false,
         value_name = "SERVICE",
         short = 's',
         long = "service"
@@ -28,21 +34,218 @@ pub struct ServicePublishArgs {
 
     /// The port on the host which the service should be published (exposed) on.
     /// This must be a valid port number between 1 and 65535 and not already in use.
+    #[arg(required = false, value_name = "PORT", short = 'p', long = "port")]
+    pub port: Option<u16>,
+
+    /// The port of the service's container to publish, e.g. `20443` for a
+    /// Stacks node's RPC API. You will be prompted for it if the service has
+    /// more than one port which isn't published yet.
     #[arg(
         required = false,
         value_name = "PORT",
-        short = 'p',
-        long = "port"
+        short = 'c',
+        long = "container-port"
     )]
-    pub port: Option<u16>,
+    pub container_port: Option<u16>,
 }
 
 pub async fn exec(ctx: &CliContext, args: ServicePublishArgs) -> Result<()> {
-    let env_name = if let Some(name) = args.env_name {
-        EnvironmentName::new(&name)?;
-    } else {
-        todo!("prompt for environment");
+    intro("Publish Service Port".bold())?;
+
+    let env_name = match &args.env_name {
+        Some(name) => EnvironmentName::new(name)?,
+        None => match &args.svc_name {
+            Some(svc_name) => find_environment_for_service(ctx, svc_name)?,
+            None => prompt_environment_name(ctx)?,
+        },
+    };
+    let env = ctx.db.as_clidb().load_environment(env_name.as_ref())?;
+
+    let service = match &args.svc_name {
+        Some(name) => match env.services.iter().find(|svc| &svc.name == name) {
+            Some(service) => service,
+            None => bail!(CliError::Graceful {
+                title: "Service not found".to_string(),
+                message: format!(
+                    "The environment '{}' has no service named '{}'.",
+                    env.name, name
+                )
+            }),
+        },
+        None => {
+            if env.services.is_empty() {
+                bail!(CliError::Graceful {
+                    title: "No services found".to_string(),
+                    message: format!("The environment '{}' has no services.", env.name)
+                });
+            }
+            let items = env
+                .services
+                .iter()
+                .enumerate()
+                .map(|(i, svc)| (i, svc.name.as_str(), svc.service_type.name.as_str()))
+                .collect::<Vec<_>>();
+            let index = cliclack::select("Select a service")
+                .items(&items)
+                .interact()?;
+            &env.services[index]
+        }
+    };
+
+    // Only the ports of the service's type can be published, and each of them
+    // only once. Automatically published ports are replaced by the user's.
+    let ports = ctx
+        .db
+        .list_ports_for_service_type_id(service.service_type.id)?
+        .into_iter()
+        .filter(|port| {
+            !service
+                .port_mappings
+                .iter()
+                .any(|map| !map.is_auto_published && map.container_port as i32 == port.port)
+        })
+        .collect::<Vec<_>>();
+
+    let port = match args.container_port {
+        Some(container_port) => {
+            match ports.iter().find(|port| port.port == container_port as i32) {
+                Some(port) => port,
+                None => bail!(CliError::Graceful {
+                    title: "Port can't be published".to_string(),
+                    message: format!(
+                        "Port {} is not a port of the service '{}' or has already been published.",
+                        container_port, service.name
+                    )
+                }),
+            }
+        }
+        None => match ports.len() {
+            0 => bail!(CliError::Graceful {
+                title: "No ports to publish".to_string(),
+                message: format!(
+                    "The service '{}' has no ports which haven't been published yet.",
+                    service.name
+                )
+            }),
+            1 => &ports[0],
+            _ => {
+                let items = ports
+                    .iter()
+                    .enumerate()
+                    .map(|(i, port)| {
+                        (
+                            i,
+                            port.port.to_string(),
+                            port.remark.as_deref().unwrap_or(""),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let index = cliclack::select("Select a port to publish")
+                    .items(&items)
+                    .interact()?;
+                &ports[index]
+            }
+        },
     };
 
-    todo!()
-}
\ No newline at end of file
+    let publish_port: u16 = match args.port {
+        Some(publish_port) => publish_port,
+        None => cliclack::input("Host port:")
+            .default_input(&port.port.to_string())
+            .validate(|input: &String| match input.parse::<u16>() {
+                Ok(port) if port > 0 => Ok(()),
+                _ => Err("Invalid port. Please enter a number between 1 and 65535."),
+            })
+            .interact()?,
+    };
+    if publish_port == 0 {
+        bail!(CliError::Graceful {
+            title: "Invalid port".to_string(),
+            message: "The host port must be a number between 1 and 65535.".to_string()
+        });
+    }
+    if ctx
+        .db
+        .find_environment_service_port_by_publish_port(publish_port)?
+        .is_some_and(|port| port.environment_service_id != service.id || !port.is_auto_published)
+    {
+        bail!(CliError::Graceful {
+            title: "Port already in use".to_string(),
+            message: format!(
+                "The host port {} is already published by another service.",
+                publish_port
+            )
+        });
+    }
+
+    // A service with ports of its own doesn't get its default ports published.
+    if service
+        .port_mappings
+        .iter()
+        .any(|map| map.is_auto_published)
+    {
+        ctx.db.unpublish_default_service_ports(service.id)?;
+        cliclack::log::info("The service's automatically published ports have been removed.")?;
+    }
+    ctx.db.add_environment_service_port(
+        service.id,
+        port.port as u16,
+        publish_port,
+        port.network_protocol_id,
+        port.remark.as_deref(),
+    )?;
+
+    // Port bindings are set when a container is created, so an existing
+    // container has to be re-created to pick up the new port.
+    if ctx
+        .docker()
+        .find_container_by_name(&service_container_name(service))
+        .await?
+        .is_some()
+    {
+        cliclack::log::warning(format!(
+            "The service's container has already been created. Use `{}` followed by `{}` to re-create it with the new port.",
+            "stackify env down".bold(),
+            "stackify env start".bold()
+        ))?;
+    }
+
+    outro(
+        format!(
+            "Port {} of {} is published on host port {}",
+            port.port,
+            service.name.bold(),
+            publish_port.bold()
+        )
+        .green(),
+    )?;
+
+    Ok(())
+}
+
+/// Finds the name of the only environment with a service named `svc_name`.
+fn find_environment_for_service(ctx: &CliContext, svc_name: &str) -> Result<EnvironmentName> {
+    let environments = ctx
+        .db
+        .as_clidb()
+        .load_all_environments()?
+        .into_iter()
+        .filter(|env| env.services.iter().any(|svc| svc.name == svc_name))
+        .collect::<Vec<_>>();
+
+    match environments.as_slice() {
+        [env] => Ok(env.name.clone()),
+        [] => bail!(CliError::Graceful {
+            title: "Service not found".to_string(),
+            message: format!("No environment has a service named '{}'.", svc_name)
+        }),
+        _ => bail!(CliError::Graceful {
+            title: "Service name is ambiguous".to_string(),
+            message: format!(
+                "More than one environment has a service named '{}', use the `{}` argument to choose one.",
+                svc_name,
+                "--env".bold()
+            )
+        }),
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::BTreeSet;
+
+use clap::Args;
+use cliclack::{intro, outro};
+use color_eyre::{eyre::bail, Result};
+use stackify_common::{types::EnvironmentName, ServiceType};
+
+use crate::{
+    cli::{context::CliContext, theme::ThemedObject},
+    db::{cli_db::CliDatabase, opts::UpdateEnvironmentOpts},
+    docker::{
+        network_name,
+        opts::{CreateNetwork, ListContainers},
+        ContainerState, LabelKey,
+    },
+    docker_api::{
+        models::ContainerSummary,
+        opts::{ContainerConnectionOpts, ContainerListOpts, ContainerStopOpts, NetworkCreateOpts},
+    },
+    errors::CliError,
+    util::names::environment_container_name,
+};
+
+use super::prompt_environment_name;
+
+/// The maximum length of an environment's description.
+const MAX_DESCRIPTION_LENGTH: usize = 256;
+
+#[derive(Debug, Args)]
+pub struct SetArgs {
+    /// The name of the environment. You will be prompted for it if it's
+    /// omitted.
+    #[arg(
+        required = false,
+        value_name = "NAME",
+        short = 'e',
+        long = "environment",
+        visible_alias = "env"
+    )]
+    pub env_name: Option<String>,
+
+    /// The number of seconds between Bitcoin blocks. Bitcoin miners with their
+    /// own `bitcoin_block_frequency` parameter are not affected.
+    #[arg(required = false, short, long, value_name = "SECONDS", value_parser = clap::value_parser!(u32).range(1..))]
+    pub bitcoin_block_speed: Option<u32>,
+
+    /// Renames the environment. Its Docker containers are re-created and its
+    /// network replaced to match the new name, keeping the chains' state;
+    /// running containers are restarted for this.
+    #[arg(required = false, long = "rename", value_name = "NEW_NAME")]
+    pub new_name: Option<String>,
+
+    /// A free-form description of the environment. Pass an empty string to
+    /// remove it.
+    #[arg(required = false, short, long, value_name = "TEXT")]
+    pub description: Option<String>,
+
+    /// Whether the default ports of services without any ports of their own
+    /// are published on the host when their containers are created.
+    #[arg(required = false, short, long, value_name = "BOOL")]
+    pub publish_ports: Option<bool>,
+}
+
+pub async fn exec(ctx: &CliContext, args: SetArgs) -> Result<()> {
+    intro("Update Environment".bold())?;
+    let env_name = match &args.env_name {
+        Some(name) => EnvironmentName::new(name)?,
+        None => prompt_environment_name(ctx)?,
+    };
+    let env = ctx.db.as_clidb().load_environment(env_name.as_ref())?;
+
+    if args.bitcoin_block_speed.is_none()
+        && args.new_name.is_none()
+        && args.description.is_none()
+        && args.publish_ports.is_none()
+    {
+        bail!(CliError::Graceful {
+            title: "Nothing to change".to_string(),
+            message: format!(
+                "Use the `{}`, `{}`, `{}` or `{}` arguments to change the environment's settings.",
+                "--bitcoin-block-speed".bold(),
+                "--rename".bold(),
+                "--description".bold(),
+                "--publish-ports".bold()
+            )
+        });
+    }
+
+    let mut opts = UpdateEnvironmentOpts::default();
+    let mut changes = vec![];
+    // The services which need to be restarted to pick up the changes, should
+    // they be running.
+    let mut restarts = BTreeSet::new();
+
+    if let Some(speed) = args.bitcoin_block_speed {
+        if speed != env.bitcoin_block_speed {
+            opts.bitcoin_block_speed = Some(speed);
+            changes.push(format!(
+                "Bitcoin block speed: {} → {} seconds",
+                env.bitcoin_block_speed, speed
+            ));
+            restarts.extend(
+                env.services
+                    .iter()
+                    .filter(|svc| svc.service_type.id == ServiceType::BitcoinMiner as i32)
+                    .filter(|svc| svc.param_value("bitcoin_block_frequency").is_none())
+                    .map(|svc| svc.id),
+            );
+        }
+    }
+
+    if let Some(description) = &args.description {
+        let description = Some(description.trim().to_string()).filter(|d| !d.is_empty());
+        if description
+            .as_ref()
+            .is_some_and(|d| d.chars().count() > MAX_DESCRIPTION_LENGTH)
+        {
+            bail!(CliError::Graceful {
+                title: "Description is too long".to_string(),
+                message: format!(
+                    "The description can be at most {} characters long.",
+                    MAX_DESCRIPTION_LENGTH
+                )
+            });
+        }
+        if description != env.description {
+            changes.push(match &description {
+                Some(description) => format!("Description: {}", description),
+                None => "Description removed".to_string(),
+            });
+            opts.description = Some(description);
+        }
+    }
+
+    if let Some(publish_ports) = args.publish_ports {
+        if publish_ports != env.publish_ports {
+            opts.publish_ports = Some(publish_ports);
+            changes.push(format!(
+                "Publish default ports: {} → {}",
+                env.publish_ports, publish_ports
+            ));
+            // Only services without ports of their own are affected.
+            for service in env
+                .services
+                .iter()
+                .filter(|svc| svc.port_mappings.iter().all(|map| map.is_auto_published))
+            {
+                if !ctx
+                    .db
+                    .list_ports_for_service_type_id(service.service_type.id)?
+                    .is_empty()
+                {
+                    restarts.insert(service.id);
+                }
+            }
+        }
+    }
+
+    if let Some(new_name) = &args.new_name {
+        let new_name = EnvironmentName::new(new_name)?;
+        if new_name != env.name {
+            if ctx
+                .db
+                .find_environment_by_name(new_name.as_ref())?
+                .is_some()
+            {
+                bail!(CliError::Graceful {
+                    title: "Environment already exists".to_string(),
+                    message: format!("An environment named '{}' already exists.", new_name)
+                });
+            }
+            changes.push(format!("Name: {} → {}", env.name, new_name));
+            opts.name = Some(new_name.to_string());
+        }
+    }
+
+    if opts.is_empty() {
+        outro("The environment already has these settings, nothing was changed")?;
+        return Ok(());
+    }
+
+    let running_containers = ctx
+        .docker()
+        .api()
+        .containers()
+        .list(&ContainerListOpts::running_in_environment(&env.name))
+        .await?;
+    let running = service_ids(&running_containers);
+
+    if let Some(new_name) = &opts.name {
+        rename_docker_resources(ctx, &env.name, &EnvironmentName::new(new_name)?).await?;
+    }
+
+    ctx.db.update_environment(env.id, &opts)?;
+    for change in changes.iter() {
+        cliclack::log::success(change)?;
+    }
+
+    let needs_restart = env
+        .services
+        .iter()
+        .filter(|svc| restarts.contains(&svc.id) && running.contains(&svc.id))
+        .map(|svc| svc.name.as_str())
+        .collect::<Vec<_>>();
+    if !needs_restart.is_empty() {
+        cliclack::log::warning(format!(
+            "The following running services need to be re-created to pick up the changes: {}\nUse `{}` followed by `{}` to do so.",
+            needs_restart.join(", ").bold(),
+            "stackify env down".bold(),
+            "stackify env start".bold()
+        ))?;
+        if opts.bitcoin_block_speed.is_some() {
+            cliclack::log::info(format!(
+                "To change the block speed of a running Bitcoin miner right away, use `{}`.",
+                "stackify env bitcoin set-speed".bold()
+            ))?;
+        }
+    }
+
+    let name = opts.name.as_deref().unwrap_or(env.name.as_ref());
+    outro(format!("The environment {} has been updated", name.bold()).green())?;
+
+    Ok(())
+}
+
+/// Moves the environment's containers and network over to its new name. The
+/// containers are labelled with the environment's name, which Docker can't
+/// change, so each one is re-created from a snapshot of itself to keep the
+/// chains' state. The network is named after the environment and is replaced.
+/// Running containers are stopped for this and started again afterwards.
+async fn rename_docker_resources(
+    ctx: &CliContext,
+    old_name: &EnvironmentName,
+    new_name: &EnvironmentName,
+) -> Result<()> {
+    let containers = ctx
+        .docker()
+        .api()
+        .containers()
+        .list(&ContainerListOpts::for_environment(old_name, true))
+        .await?;
+    let old_network = ctx.docker().find_network_for_environment(old_name).await?;
+    if containers.is_empty() && old_network.is_none() {
+        return Ok(());
+    }
+
+    let spinner = cliclack::spinner();
+    spinner.start("Moving the environment's Docker resources...");
+
+    let new_network = ctx
+        .docker()
+        .api()
+        .networks()
+        .create(&NetworkCreateOpts::for_stackify_environment(new_name))
+        .await?;
+
+    for summary in containers {
+        let Some(id) = summary.id.clone() else {
+            continue;
+        };
+        let Some(name) = summary
+            .names
+            .as_ref()
+            .and_then(|names| names.first())
+            .map(|name| name.trim_start_matches('/').to_string())
+        else {
+            continue;
+        };
+        // Only the environment container is named after the environment.
+        let name = if name == environment_container_name(old_name) {
+            environment_container_name(new_name)
+        } else {
+            name
+        };
+        let mut labels = summary.labels.clone().unwrap_or_default();
+        labels.insert(LabelKey::EnvironmentName.to_string(), new_name.to_string());
+        let running = matches!(
+            summary.state.as_deref().map(ContainerState::parse),
+            Some(Ok(ContainerState::Running))
+        );
+        let connected = summary
+            .network_settings
+            .as_ref()
+            .and_then(|settings| settings.networks.as_ref())
+            .is_some_and(|networks| networks.contains_key(&network_name(old_name)));
+
+        spinner.set_message(format!("Moving container {}...", name));
+        if running {
+            ctx.docker()
+                .api()
+                .containers()
+                .get(id.clone())
+                .stop(&ContainerStopOpts::builder().build())
+                .await?;
+        }
+        let new_id = ctx
+            .docker()
+            .recreate_container(&id.into(), &name, labels)
+            .await?;
+        if connected {
+            new_network
+                .connect(&ContainerConnectionOpts::builder(&new_id).build())
+                .await?;
+        }
+        if running {
+            ctx.docker().api().containers().get(new_id).start().await?;
+        }
+    }
+
+    // The old containers are gone, so nothing is attached to the network.
+    if let Some((id, _)) = old_network {
+        ctx.docker().api().networks().get(id).delete().await?;
+    }
+
+    spinner.stop(format!(
+        "{} Docker resources moved to {}",
+        "✔".green(),
+        network_name(new_name)
+    ));
+
+    Ok(())
+}
+
+/// The ids of the services the given containers belong to, from their labels.
+fn service_ids(containers: &[ContainerSummary]) -> BTreeSet<i32> {
+    containers
+        .iter()
+        .filter_map(|container| {
+            container
+                .labels
+                .as_ref()?
+                .get(&LabelKey::ServiceId.to_string())?
+                .parse()
+                .ok()
+        })
+        .collect()
+}
@@ -1,7 +1,7 @@
 use crate::{
     cli::{log::clilog, Cli},
     docker::{api::DockerApi, ContainerState},
-    util::{names::service_container_name, stacks_cli::MakeKeychainResult},
+    util::names::service_container_name,
 };
 use cliclack::{intro, log::*, multi_progress, outro_note, MultiProgress};
 use color_eyre::{
@@ -27,6 +27,7 @@ use crate::{
         },
         Container,
     },
+    errors::CliError,
     util::names::environment_container_name,
 };
 
@@ -57,6 +58,18 @@ pub async fn exec(ctx: &CliContext, args: StartArgs) -> Result<()> {
         return Ok(());
     }
 
+    // The Stacks node won't start with an invalid epoch-map, so fail early.
+    if let Err(e) = env.ordered_epochs() {
+        bail!(CliError::Graceful {
+            title: "Invalid epoch-map".to_string(),
+            message: format!(
+                "{}. Use the `{}` command to fix it.",
+                e,
+                "stackify env epoch edit".bold()
+            )
+        });
+    }
+
     let multi = multi_progress("Checking environment status");
 
     // Assert that the environment is not already running.
@@ -78,6 +91,8 @@ pub async fn exec(ctx: &CliContext, args: StartArgs) -> Result<()> {
         return Ok(());
     }
 
+    let env = publish_default_ports(ctx, env).await?;
+
     assert_network(ctx, &multi, &env_name).await?;
     let env_container = assert_environment_container(ctx, &multi, &env_name).await?;
 
@@ -128,6 +143,47 @@ pub async fn exec(ctx: &CliContext, args: StartArgs) -> Result<()> {
     Ok(())
 }
 
+/// Publishes the default ports of services which don't have any ports of their
+/// own if the environment is set up to do so, and unpublishes them otherwise.
+/// Services which already have a container are skipped, as their ports can't
+/// be changed anymore.
+async fn publish_default_ports(ctx: &CliContext, env: Environment) -> Result<Environment> {
+    let mut changed = false;
+    for service in env.services.iter() {
+        let has_own_ports = service
+            .port_mappings
+            .iter()
+            .any(|map| !map.is_auto_published);
+        let has_default_ports = service
+            .port_mappings
+            .iter()
+            .any(|map| map.is_auto_published);
+        let publish = env.publish_ports && !has_own_ports;
+        if publish == has_default_ports {
+            continue;
+        }
+
+        if try_get_container(ctx.docker(), &service_container_name(service))
+            .await?
+            .is_none()
+        {
+            if publish {
+                ctx.db
+                    .publish_default_service_ports(service.id, service.service_type.id)?;
+            } else {
+                ctx.db.unpublish_default_service_ports(service.id)?;
+            }
+            changed = true;
+        }
+    }
+
+    if changed {
+        Ok(ctx.db.load_environment(&env.name)?)
+    } else {
+        Ok(env)
+    }
+}
+
 /// Assert that the Docker network for the environment exists, and if not create
 /// it.
 async fn assert_network(
@@ -331,42 +387,7 @@ async fn start_bitcoin_node(
     let container = match try_get_container(ctx.docker(), &container_name).await? {
         Some(container) => container,
         None => {
-            let container = create_bitcoin_container(ctx.docker(), &env.name, service).await?;
-
-            let bitcoin_peers = env
-                .services
-                .iter()
-                .filter(|service| {
-                    [ServiceType::BitcoinMiner, ServiceType::BitcoinFollower]
-                        .contains(&ServiceType::from_i32(service.service_type.id).unwrap())
-                })
-                .filter(|svc| &svc.name != &service.name)
-                .map(|service| service.name.clone())
-                .collect::<Vec<_>>();
-
-            clilog!("Handling files for service: {}", &service.name);
-            let handlebars = Handlebars::new();
-            let mut data = serde_json::Map::new();
-
-            for file in ctx.db.load_files_for_environment_service(service)? {
-                clilog!("Handling file: {}", &file.header.filename);
-                data.insert("peers".to_string(), to_json(&bitcoin_peers));
-                let mut content = file.contents.contents;
-
-                if file.header.file_type == FileType::HandlebarsTemplate {
-                    let rendered_content =
-                        handlebars.render_template(&String::from_utf8(content)?, &data)?;
-                    content = rendered_content.into_bytes();
-                }
-
-                let destination_path = &file.header.destination_dir.join(&file.header.filename);
-                clilog!(
-                    "Copying file: {} -> {:?}",
-                    &file.header.filename,
-                    destination_path
-                );
-                container.copy_file_into(destination_path, &content).await?;
-            }
+            let container = create_bitcoin_container(ctx, env, service).await?;
 
             // Attach the container to this environment's network
             if let Some((network_id, _)) =
@@ -394,19 +415,85 @@ async fn start_bitcoin_node(
 
 /// Create a new Bitcoin container for the environment.
 async fn create_bitcoin_container(
-    docker: &DockerApi,
-    env_name: &EnvironmentName,
+    ctx: &CliContext,
+    env: &Environment,
     service: &EnvironmentService,
 ) -> Result<Container> {
-    Ok(docker
+    let docker = ctx.docker();
+
+    let container = docker
         .api()
         .containers()
-        .create(
-            &docker
-                .opts_for()
-                .create_bitcoin_container(&env_name, &service)?,
-        )
-        .await?)
+        .create(&docker.opts_for().create_bitcoin_container(env, &service)?)
+        .await?;
+
+    clilog!("Handling files for service: {}", &service.name);
+    let handlebars = Handlebars::new();
+    let mut data = serde_json::Map::new();
+
+    let peers = bitcoin_peers(env, service)?;
+    clilog!("Bitcoin peers for {}: {:?}", &service.name, &peers);
+    data.insert("peers".to_string(), to_json(&peers));
+
+    for file in ctx.db.load_files_for_environment_service(service)? {
+        clilog!("Processing file: {}", &file.header.filename);
+        let mut content = file.contents.contents;
+
+        if file.header.file_type == FileType::HandlebarsTemplate {
+            let rendered_content =
+                handlebars.render_template(&String::from_utf8(content)?, &data)?;
+            clilog!("Rendered content: {}", &rendered_content);
+            content = rendered_content.into_bytes();
+        }
+
+        let destination_path = &file.header.destination_dir.join(&file.header.filename);
+        clilog!(
+            "Copying file: {} -> {:?}",
+            &file.header.filename,
+            destination_path
+        );
+        container.copy_file_into(destination_path, &content).await?;
+    }
+
+    Ok(container)
+}
+
+/// The Bitcoin nodes which a Bitcoin node should connect to. These are the
+/// services listed in its `bitcoin_peers` parameter, if set, or otherwise all
+/// other Bitcoin nodes in the environment.
+fn bitcoin_peers(env: &Environment, service: &EnvironmentService) -> Result<Vec<String>> {
+    let bitcoin_nodes = env
+        .services
+        .iter()
+        .filter(|svc| {
+            [ServiceType::BitcoinMiner, ServiceType::BitcoinFollower]
+                .contains(&ServiceType::from_i32(svc.service_type.id).unwrap())
+        })
+        .filter(|svc| svc.name != service.name)
+        .map(|svc| svc.name.clone())
+        .collect::<Vec<_>>();
+
+    let Some(peers) = service.param_value("bitcoin_peers") else {
+        return Ok(bitcoin_nodes);
+    };
+
+    let peers = peers
+        .split(',')
+        .map(str::trim)
+        .filter(|peer| !peer.is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    for peer in &peers {
+        if !bitcoin_nodes.contains(peer) {
+            bail!(
+                "Invalid environment configuration: '{}' is configured to peer with '{}', which is not another Bitcoin node in the environment",
+                service.name,
+                peer
+            );
+        }
+    }
+
+    Ok(peers)
 }
 
 /// Create a new Stacks Signer container for the environment.
@@ -441,7 +528,10 @@ async fn create_stacks_signer_container(
         .params
         .iter()
         .find(|param| param.param.key == "stacks_keychain")
-        .map(|param| ctx.db.get_environment_keychain_by_stx_address(&param.value))
+        .map(|param| {
+            ctx.db
+                .get_environment_keychain_by_stx_address(env.id, &param.value)
+        })
         .ok_or_else(|| eyre!("Error getting keychain for Stacks signer"))?
         .expect("Error retrieving keychain for stacks signer")
         .expect("Keychain not found for Stacks signer");
@@ -517,7 +607,7 @@ async fn create_stacks_node_container(
                 .expect("Seed param not found for Stacks miner");
             let keychain = ctx
                 .db
-                .get_environment_keychain_by_stx_address(&seed.value)
+                .get_environment_keychain_by_stx_address(env.id, &seed.value)
                 .map_err(|e| eyre!(e))
                 .expect("Error getting keychain for Stacks miner")
                 .expect("Keychain not found for Stacks miner");
@@ -576,6 +666,36 @@ async fn create_stacks_node_container(
         clilog!("No Stacks signers configured to use this node as a peer.");
     }
 
+    // Add the genesis balances of the environment's keychains. The keys match
+    // those of the Stacks CLI's keychain JSON, which the template expects.
+    let accounts = env
+        .keychains
+        .iter()
+        .map(|kc| {
+            serde_json::json!({
+                "address": kc.stx_address,
+                "amount": kc.amount,
+                "mnemonic": kc.mnemonic,
+                "privateKey": kc.private_key,
+                "btcAddress": kc.btc_address,
+            })
+        })
+        .collect::<Vec<_>>();
+    data.insert("accounts".to_string(), to_json(accounts));
+
+    // Add the environment's epoch-map.
+    let epochs = env
+        .ordered_epochs()?
+        .iter()
+        .map(|e| {
+            serde_json::json!({
+                "name": e.epoch.name,
+                "start_height": e.starts_at_block_height,
+            })
+        })
+        .collect::<Vec<_>>();
+    data.insert("epochs".to_string(), to_json(epochs));
+
     // Add event observers.
     let mut event_observers = Vec::new();
     // Add any signers configured to use this node as a peer to the event observers.
@@ -639,7 +759,7 @@ async fn create_stacks_node_container(
                 );
                 let keychain = ctx
                     .db
-                    .get_environment_keychain_by_stx_address(&param.value)?;
+                    .get_environment_keychain_by_stx_address(env.id, &param.value)?;
                 if let Some(keychain) = keychain {
                     let keychain: EnvironmentKeychain = keychain.into();
                     data.insert(param.param.key.clone(), to_json(keychain));
@@ -0,0 +1,351 @@
+use std::{collections::HashMap, time::Duration};
+
+use clap::Args;
+use color_eyre::Result;
+use console::Term;
+use prettytable::{row, Table};
+use stackify_common::{
+    stacks::{
+        api::{
+            client::StacksNodeClient,
+            v2::{PeerInfo, PoxInfo},
+        },
+        StacksPrivateKey,
+    },
+    types::{Environment, EnvironmentKeychain, EnvironmentService},
+    ServiceType,
+};
+
+use crate::{
+    cli::{
+        context::CliContext,
+        env::{bitcoin_rpc_client, stacks_node_client},
+        theme::ThemedObject,
+    },
+    db::cli_db::CliDatabase,
+    docker::{opts::ListContainers, ContainerState, LabelKey},
+    docker_api::{models::ContainerSummary, opts::ContainerListOpts},
+};
+
+#[derive(Debug, Args)]
+pub struct StatusArgs {
+    /// The name of the environment.
+    #[arg(required = true, value_name = "ENVIRONMENT")]
+    pub env_name: String,
+
+    /// Keep refreshing the status in place until interrupted with Ctrl+C.
+    #[arg(required = false, short, long)]
+    pub watch: bool,
+
+    /// The number of seconds between refreshes in watch mode.
+    #[arg(required = false, short, long, value_name = "SECONDS", default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: u64,
+}
+
+/// The chain state as reported by one of the environment's Stacks nodes.
+struct StacksChainState {
+    client: StacksNodeClient,
+    info: PeerInfo,
+    pox: Option<PoxInfo>,
+}
+
+pub async fn exec(ctx: &CliContext, args: StatusArgs) -> Result<()> {
+    let env = ctx.db.as_clidb().load_environment(&args.env_name)?;
+
+    if !args.watch {
+        cliclack::intro("Environment Status".bold())?;
+        for line in render_status(ctx, &env).await?.lines() {
+            println!("{} {}", "│".bright_black(), line);
+        }
+        cliclack::outro(format!(
+            "To follow the status as it changes, use the `{}` flag",
+            "--watch".bold()
+        ))?;
+        return Ok(());
+    }
+
+    // Ctrl+C is caught by the handler installed in `main`, which notifies us
+    // through the context's channel rather than terminating the process.
+    let mut shutdown = ctx.tx.as_ref().map(|tx| tx.subscribe());
+    let term = Term::stdout();
+    loop {
+        // Render before clearing the screen, so that the previous status stays
+        // visible while the nodes are queried. Errors (e.g. Docker being
+        // briefly unavailable) are shown in place of the status until the
+        // next refresh.
+        let status = match render_status(ctx, &env).await {
+            Ok(status) => status,
+            Err(e) => format!("{} {}\n", "Failed to query the status:".red(), e),
+        };
+        term.clear_screen()?;
+        println!("{} {}\n", "Environment Status:".bold(), env.name.cyan());
+        print!("{}", status);
+        println!(
+            "\n{}",
+            format!(
+                "Refreshing every {} seconds, press Ctrl+C to exit",
+                args.interval
+            )
+            .gray()
+        );
+
+        let sleep = tokio::time::sleep(Duration::from_secs(args.interval));
+        match shutdown.as_mut() {
+            Some(receiver) => tokio::select! {
+                _ = sleep => {}
+                _ = receiver.recv() => break,
+            },
+            None => sleep.await,
+        }
+    }
+
+    Ok(())
+}
+
+/// Queries Docker and the environment's nodes, returning the rendered tables.
+async fn render_status(ctx: &CliContext, env: &Environment) -> Result<String> {
+    let containers = service_containers(ctx, env).await?;
+
+    let mut bitcoin_height = None;
+    let mut stacks_state: Option<StacksChainState> = None;
+    let mut chain_column = HashMap::new();
+
+    for service in env.services.iter() {
+        match ServiceType::from(service.service_type.id) {
+            ServiceType::BitcoinMiner | ServiceType::BitcoinFollower => {
+                let height = match bitcoin_rpc_client(env, Some(&service.name)).await {
+                    Ok(client) => client.get_block_count().await.ok(),
+                    Err(_) => None,
+                };
+                if let Some(height) = height {
+                    chain_column.insert(service.id, format!("Height {}", height));
+                    bitcoin_height = bitcoin_height.max(Some(height));
+                }
+            }
+            ServiceType::StacksMiner | ServiceType::StacksFollower => {
+                let Ok(client) = stacks_node_client(env, Some(&service.name)).await else {
+                    continue;
+                };
+                let Ok(info) = client.get_info().await else {
+                    continue;
+                };
+                chain_column.insert(
+                    service.id,
+                    format!(
+                        "Tip {}, burn height {}",
+                        info.stacks_tip_height, info.burn_block_height
+                    ),
+                );
+                // Report the chain state of the node which is furthest ahead.
+                if stacks_state
+                    .as_ref()
+                    .is_none_or(|state| info.stacks_tip_height > state.info.stacks_tip_height)
+                {
+                    let pox = client.get_pox_info().await.ok();
+                    stacks_state = Some(StacksChainState { client, info, pox });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Signer registration is looked up in the stacker set of the current
+    // reward cycle.
+    if let Some(StacksChainState {
+        client,
+        pox: Some(pox),
+        ..
+    }) = &stacks_state
+    {
+        let stacker_set = client.get_stacker_set(pox.reward_cycle_id).await.ok();
+        for signer in env
+            .services
+            .iter()
+            .filter(|svc| svc.service_type.id == ServiceType::StacksSigner as i32)
+        {
+            let key = match signer_keychain(env, signer) {
+                Some(keychain) => {
+                    Some(StacksPrivateKey::from_hex(&keychain.private_key)?.public_key())
+                }
+                None => None,
+            };
+            let registration = match (&stacker_set, key) {
+                (Some(set), Some(key)) => match set.signer(&key) {
+                    Some(entry) => format!("Registered, weight {}", entry.weight)
+                        .green()
+                        .to_string(),
+                    None => "Not registered".yellow().to_string(),
+                },
+                _ => "Unknown".gray().to_string(),
+            };
+            chain_column.insert(
+                signer.id,
+                format!("{} (cycle {})", registration, pox.reward_cycle_id),
+            );
+        }
+    }
+
+    let mut services_table = Table::new();
+    services_table.set_format(*prettytable::format::consts::FORMAT_BOX_CHARS);
+    services_table.set_titles(row![
+        "Service".table_header(),
+        "Type".table_header(),
+        "Container".table_header(),
+        "Uptime".table_header(),
+        "Restarts".table_header(),
+        "Chain".table_header(),
+    ]);
+    for service in env.services.iter() {
+        let (state, uptime, restarts) = match containers.get(&service.id.to_string()) {
+            Some((summary, restarts)) => (
+                format_state(summary.state.as_deref()),
+                summary.status.clone().unwrap_or_default(),
+                restarts
+                    .map(|restarts| restarts.to_string())
+                    .unwrap_or_else(|| "unknown".gray().to_string()),
+            ),
+            None => (
+                "not created".gray().to_string(),
+                String::new(),
+                String::new(),
+            ),
+        };
+        services_table.add_row(row![
+            service.name.bold(),
+            service.service_type.name,
+            state,
+            uptime,
+            restarts,
+            chain_column
+                .remove(&service.id)
+                .unwrap_or_else(|| "-".gray().to_string()),
+        ]);
+    }
+
+    let mut chain_table = Table::new();
+    chain_table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+    chain_table.add_row(row![
+        "Bitcoin Tip Height".bold(),
+        format_optional(bitcoin_height)
+    ]);
+    let info = stacks_state.as_ref().map(|state| &state.info);
+    let pox = stacks_state.as_ref().and_then(|state| state.pox.as_ref());
+    chain_table.add_row(row![
+        "Stacks Tip Height".bold(),
+        format_optional(info.map(|info| info.stacks_tip_height))
+    ]);
+    chain_table.add_row(row![
+        "Burn Block Height".bold(),
+        format_optional(info.map(|info| info.burn_block_height))
+    ]);
+    chain_table.add_row(row![
+        "Current Epoch".bold(),
+        info.and_then(|info| current_epoch(env, info.burn_block_height))
+            .unwrap_or_else(|| "unknown".gray().to_string())
+    ]);
+    chain_table.add_row(row![
+        "PoX Cycle".bold(),
+        match pox {
+            Some(pox) => format!(
+                "{} ({}), next cycle in {} blocks",
+                pox.reward_cycle_id,
+                if pox.current_cycle.is_pox_active {
+                    "active"
+                } else {
+                    "inactive"
+                },
+                pox.next_reward_cycle_in
+            ),
+            None => "unknown".gray().to_string(),
+        }
+    ]);
+
+    let mut output = vec![];
+    services_table.print(&mut output)?;
+    output.push(b'\n');
+    chain_table.print(&mut output)?;
+
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+/// The environment's service containers, including stopped ones, keyed by
+/// the service id from their labels, along with their restart counts if they
+/// could be looked up.
+async fn service_containers(
+    ctx: &CliContext,
+    env: &Environment,
+) -> Result<HashMap<String, (ContainerSummary, Option<isize>)>> {
+    let summaries = ctx
+        .docker()
+        .api()
+        .containers()
+        .list(&ContainerListOpts::for_environment(&env.name, true))
+        .await?;
+
+    let mut containers = HashMap::new();
+    for summary in summaries {
+        let Some(service_id) = summary
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(&LabelKey::ServiceId.to_string()))
+            .cloned()
+        else {
+            continue;
+        };
+        // The restart count is only available from the container's details,
+        // and the container may have been removed since it was listed.
+        let restarts = match &summary.id {
+            Some(id) => ctx
+                .docker()
+                .api()
+                .containers()
+                .get(id.clone())
+                .inspect()
+                .await
+                .ok()
+                .map(|details| details.restart_count.unwrap_or_default()),
+            None => None,
+        };
+        containers.insert(service_id, (summary, restarts));
+    }
+
+    Ok(containers)
+}
+
+/// The keychain a signer signs with, if it has one.
+fn signer_keychain<'a>(
+    env: &'a Environment,
+    signer: &EnvironmentService,
+) -> Option<&'a EnvironmentKeychain> {
+    let address = signer.param_value("stacks_keychain")?;
+    env.keychains.iter().find(|kc| kc.stx_address == address)
+}
+
+/// The name of the epoch which is active at the given burn block height,
+/// according to the environment's epoch-map.
+fn current_epoch(env: &Environment, burn_height: u64) -> Option<String> {
+    env.ordered_epochs()
+        .ok()?
+        .into_iter()
+        .take_while(|epoch| epoch.starts_at_block_height as u64 <= burn_height)
+        .last()
+        .map(|epoch| epoch.epoch.name.clone())
+}
+
+fn format_state(state: Option<&str>) -> String {
+    match state.map(ContainerState::parse) {
+        Some(Ok(ContainerState::Running)) => "running".green().to_string(),
+        Some(Ok(ContainerState::Exited | ContainerState::Dead)) => {
+            state.unwrap_or_default().red().to_string()
+        }
+        Some(_) => state.unwrap_or_default().yellow().to_string(),
+        None => "unknown".gray().to_string(),
+    }
+}
+
+fn format_optional(value: Option<u64>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "unknown".gray().to_string(),
+    }
+}
@@ -12,9 +12,8 @@ use crate::{
     cli::{context::CliContext, theme::ThemedObject},
     includes::{
         BITCOIN_ENTRYPOINT, STACKIFY_BUILD_DOCKERFILE, STACKIFY_BUILD_ENTRYPOINT,
-        STACKIFY_BUILD_SETUP, STACKIFY_CARGO_CONFIG, STACKIFY_RUN_DOCKERFILE,
-        STACKS_CLI_DOCKERFILE, STACKS_NODE_CONF, STACKS_NODE_ENTRYPOINT, STACKS_SIGNER_CONF,
-        STACKS_SIGNER_ENTRYPOINT,
+        STACKIFY_BUILD_SETUP, STACKIFY_CARGO_CONFIG, STACKIFY_RUN_DOCKERFILE, STACKS_NODE_CONF,
+        STACKS_NODE_ENTRYPOINT, STACKS_SIGNER_CONF, STACKS_SIGNER_ENTRYPOINT,
     },
 };
 
@@ -64,13 +63,6 @@ pub fn copy_assets(ctx: &CliContext, force: bool) -> Result<()> {
         force,
         STACKIFY_RUN_DOCKERFILE,
     )?;
-    install_asset(
-        ctx,
-        &multi,
-        "Dockerfile.stacks-cli",
-        force,
-        STACKS_CLI_DOCKERFILE,
-    )?;
     install_asset(
         ctx,
         &multi,
@@ -137,6 +137,36 @@ pub fn load_default_configuration_params(ctx: &CliContext, force: bool) -> Resul
         force,
     )?;
 
+    assert_param(
+        ctx,
+        AssertParam {
+            name: "Bitcoin Mining Address",
+            service_types: vec![ServiceType::BitcoinMiner],
+            key: "bitcoin_mining_address",
+            description: "The address which the Bitcoin Miner will mine new blocks to.",
+            default_value: None,
+            allowed_values: None,
+            is_required: false,
+            value_type: ValueType::String,
+        },
+        force,
+    )?;
+
+    assert_param(
+        ctx,
+        AssertParam {
+            name: "Bitcoin Peers",
+            service_types: vec![ServiceType::BitcoinMiner, ServiceType::BitcoinFollower],
+            key: "bitcoin_peers",
+            description: "A comma-separated list of the Bitcoin services to connect to. Defaults to all other Bitcoin services in the environment.",
+            default_value: None,
+            allowed_values: None,
+            is_required: false,
+            value_type: ValueType::String,
+        },
+        force,
+    )?;
+
     assert_param(
         ctx,
         AssertParam {
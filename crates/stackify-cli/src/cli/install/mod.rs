@@ -178,41 +178,6 @@ runtime binaries, initialize the database and copy assets to the appropriate dir
             }
         }
 
-        clilog!("Building stacks cli image");
-        let stacks_cli_spinner = multi.add(spinner());
-        stacks_cli_spinner.start("Preparing Stacks CLI image...");
-        match build_image(
-            ctx,
-            &ImageBuildOpts::for_stacks_cli_image(&ctx.host_dirs, args.force),
-        )
-        .await?
-        {
-            BuildResult::Success(id) => {
-                stacks_cli_spinner.stop(format!(
-                    "{} {} {}",
-                    "✔".green(),
-                    "Stacks CLI image",
-                    id.dimmed()
-                ));
-            }
-            BuildResult::Failed(error, message) => {
-                stacks_cli_spinner.stop(format!(
-                    "{} {}",
-                    "✖".red(),
-                    "Failed to build stacks-cli image"
-                ));
-                bail!("Build image failed: {} - {}", error, message);
-            }
-            BuildResult::Cancelled => {
-                stacks_cli_spinner.stop(format!(
-                    "{} {}",
-                    "✖".red(),
-                    "Operation was cancelled by the user"
-                ));
-                bail!("Build image cancelled");
-            }
-        }
-
         multi.stop();
     }
 
@@ -113,11 +113,10 @@ impl CliDatabase for AppDb {
         name: &str,
     ) -> std::result::Result<types::Environment, LoadEnvironmentError> {
         let env_name = EnvironmentName::new(name)?;
-        let env = Self::find_environment_by_name(&mut *self.conn.borrow_mut(), name)?.ok_or(
-            LoadEnvironmentError::NotFound {
+        let env = <Self as InnerDb>::find_environment_by_name(&mut *self.conn.borrow_mut(), name)?
+            .ok_or(LoadEnvironmentError::NotFound {
                 env_name: env_name.to_string(),
-            },
-        )?;
+            })?;
 
         let service_types = Self::load_service_types(&mut *self.conn.borrow_mut())?;
         let epochs = self.load_all_epochs()?;
@@ -258,6 +257,7 @@ impl CliDatabase for AppDb {
                         protocol: NetworkProtocol::try_from(portmap.network_protocol_id)?,
                         host_port: portmap.publish_port as u16,
                         container_port: portmap.source_port as u16,
+                        is_auto_published: portmap.is_auto_published,
                     });
                 }
 
@@ -315,6 +315,7 @@ impl CliDatabase for AppDb {
                 mnemonic: esa.mnemonic.clone(),
                 private_key: esa.private_key.clone(),
                 public_key: esa.public_key.clone(),
+                nonce: esa.nonce as u64,
                 remark: esa.remark.clone(),
                 stx_address: esa.stx_address.clone(),
             })
@@ -323,6 +324,9 @@ impl CliDatabase for AppDb {
         let ret = types::Environment {
             id: env.id,
             name: env_name,
+            bitcoin_block_speed: env.bitcoin_block_speed as u32,
+            description: env.description.clone(),
+            publish_ports: env.publish_ports,
             services,
             epochs,
             keychains,
@@ -25,6 +25,8 @@ pub struct Environment {
     pub created_at: PrimitiveDateTime,
     pub updated_at: PrimitiveDateTime,
     pub bitcoin_block_speed: i32,
+    pub description: Option<String>,
+    pub publish_ports: bool,
 }
 
 #[derive(Queryable, Selectable, Identifiable, PartialEq, Eq, Debug, Clone, QueryableByName)]
@@ -176,6 +178,7 @@ pub struct EnvironmentServicePort {
     pub publish_port: i32,
     pub network_protocol_id: i32,
     pub remark: Option<String>,
+    pub is_auto_published: bool,
 }
 
 #[derive(Queryable, Selectable, Identifiable, PartialEq, Eq, Debug, Clone, QueryableByName)]
@@ -212,9 +215,8 @@ pub struct EnvironmentServiceParam {
 #[diesel(table_name = environment_container)]
 pub struct EnvironmentContainer {
     pub id: i32,
-    pub environment_id: i32,
+    pub environment_service_id: i32,
     pub container_id: String,
-    pub service_id: i32,
     pub service_version_id: i32,
     pub created_at: PrimitiveDateTime,
 }
@@ -247,5 +249,6 @@ pub struct EnvironmentKeychain {
     pub private_key: String,
     pub public_key: String,
     pub btc_address: String,
+    pub nonce: i64,
     pub remark: Option<String>,
 }
@@ -32,6 +32,7 @@ impl From<model::EnvironmentKeychain> for types::EnvironmentKeychain {
             amount: value.amount as u64,
             mnemonic: value.mnemonic,
             btc_address: value.btc_address,
+            nonce: value.nonce as u64,
             remark: value.remark,
         }
     }
@@ -22,6 +22,8 @@ table! {
         created_at -> Timestamp,
         updated_at -> Timestamp,
         bitcoin_block_speed -> Integer,
+        description -> Nullable<Text>,
+        publish_ports -> Bool,
     }
 }
 
@@ -151,6 +153,7 @@ table! {
         publish_port -> Integer,
         network_protocol_id -> Integer,
         remark -> Nullable<Text>,
+        is_auto_published -> Bool,
     }
 }
 
@@ -187,9 +190,8 @@ table! {
 table! {
     environment_container (id) {
         id -> Integer,
-        environment_id -> Integer,
+        environment_service_id -> Integer,
         container_id -> Text,
-        service_id -> Integer,
         service_version_id -> Integer,
         created_at -> Timestamp,
     }
@@ -216,6 +218,7 @@ table! {
         private_key -> Text,
         public_key -> Text,
         btc_address -> Text,
+        nonce -> BigInt,
         remark -> Nullable<Text>,
     }
 }
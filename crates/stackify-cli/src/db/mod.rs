@@ -4,6 +4,7 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 
 use ::diesel::connection::SimpleConnection;
+use ::diesel::dsl::now;
 use ::diesel::prelude::*;
 use ::diesel::upsert::excluded;
 use ::diesel::{delete, insert_into, update, OptionalExtension};
@@ -26,7 +27,7 @@ mod tests;
 
 use self::diesel::model::*;
 use self::diesel::schema::*;
-use self::opts::NewServiceVersionOpts;
+use self::opts::{NewServiceVersionOpts, UpdateEnvironmentOpts};
 
 pub const DB_MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
 
@@ -183,6 +184,39 @@ impl AppDb {
         Ok(())
     }
 
+    pub fn add_environment_service_port(
+        &self,
+        environment_service_id: i32,
+        source_port: u16,
+        publish_port: u16,
+        network_protocol_id: i32,
+        remark: Option<&str>,
+    ) -> Result<()> {
+        insert_into(environment_service_port::table)
+            .values((
+                environment_service_port::environment_service_id.eq(environment_service_id),
+                environment_service_port::source_port.eq(source_port as i32),
+                environment_service_port::publish_port.eq(publish_port as i32),
+                environment_service_port::network_protocol_id.eq(network_protocol_id),
+                environment_service_port::remark.eq(remark),
+            ))
+            .execute(&mut *self.conn.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Finds the service port, across all environments, which is published on
+    /// the given host port.
+    pub fn find_environment_service_port_by_publish_port(
+        &self,
+        publish_port: u16,
+    ) -> Result<Option<EnvironmentServicePort>> {
+        Ok(environment_service_port::table
+            .filter(environment_service_port::publish_port.eq(publish_port as i32))
+            .first::<EnvironmentServicePort>(&mut *self.conn.borrow_mut())
+            .optional()?)
+    }
+
     pub fn find_service_type_param_id_by_key(
         &self,
         service_type_id: i32,
@@ -202,16 +236,35 @@ impl AppDb {
             .map_err(|e| e.into())
     }
 
+    pub fn find_environment_by_name(&self, name: &str) -> Result<Option<Environment>> {
+        Ok(environment::table
+            .filter(environment::name.eq(name))
+            .first::<Environment>(&mut *self.conn.borrow_mut())
+            .optional()?)
+    }
+
     pub fn get_environment_keychain_by_stx_address(
         &self,
+        environment_id: i32,
         stx_address: &str,
     ) -> Result<Option<EnvironmentKeychain>> {
         Ok(environment_keychain::table
+            .filter(environment_keychain::environment_id.eq(environment_id))
             .filter(environment_keychain::stx_address.eq(stx_address))
             .first::<EnvironmentKeychain>(&mut *self.conn.borrow_mut())
             .optional()?)
     }
 
+    /// Finds keychains with the given address across all environments.
+    pub fn find_environment_keychains_by_stx_address(
+        &self,
+        stx_address: &str,
+    ) -> Result<Vec<EnvironmentKeychain>> {
+        Ok(environment_keychain::table
+            .filter(environment_keychain::stx_address.eq(stx_address))
+            .load::<EnvironmentKeychain>(&mut *self.conn.borrow_mut())?)
+    }
+
     pub fn update_environment_epochs(&self, epochs: HashMap<i32, i32>) -> Result<()> {
         let conn = &mut *self.conn.borrow_mut();
 
@@ -262,57 +315,243 @@ impl AppDb {
         })
     }
 
+    /// Updates the given settings of an environment and bumps its
+    /// `updated_at`, in a single transaction.
+    pub fn update_environment(
+        &self,
+        environment_id: i32,
+        opts: &UpdateEnvironmentOpts,
+    ) -> Result<Environment> {
+        let conn = &mut *self.conn.borrow_mut();
+
+        conn.transaction(|tx| {
+            if let Some(name) = &opts.name {
+                update(environment::table.find(environment_id))
+                    .set(environment::name.eq(name))
+                    .execute(tx)?;
+            }
+
+            if let Some(description) = &opts.description {
+                update(environment::table.find(environment_id))
+                    .set(environment::description.eq(description))
+                    .execute(tx)?;
+            }
+
+            if let Some(bitcoin_block_speed) = opts.bitcoin_block_speed {
+                update(environment::table.find(environment_id))
+                    .set(environment::bitcoin_block_speed.eq(bitcoin_block_speed as i32))
+                    .execute(tx)?;
+            }
+
+            if let Some(publish_ports) = opts.publish_ports {
+                update(environment::table.find(environment_id))
+                    .set(environment::publish_ports.eq(publish_ports))
+                    .execute(tx)?;
+            }
+
+            Ok(update(environment::table.find(environment_id))
+                .set(environment::updated_at.eq(now))
+                .get_result::<Environment>(tx)?)
+        })
+    }
+
+    /// Deletes an environment along with everything which belongs to it, in a
+    /// single transaction. Does nothing if the environment doesn't exist.
     pub fn delete_environment(&self, name: &str) -> Result<()> {
-        let environment_id: Option<i32> = environment::table
-            .select(environment::id)
-            .filter(environment::name.eq(name))
-            .first::<i32>(&mut *self.conn.borrow_mut())
-            .optional()?;
+        let conn = &mut *self.conn.borrow_mut();
+
+        conn.transaction(|tx| {
+            let Some(environment_id) = environment::table
+                .select(environment::id)
+                .filter(environment::name.eq(name))
+                .first::<i32>(tx)
+                .optional()?
+            else {
+                return Ok(());
+            };
 
-        if let Some(environment_id) = environment_id {
             let environment_service_ids = environment_service::table
                 .select(environment_service::id)
                 .filter(environment_service::environment_id.eq(environment_id))
-                .load::<i32>(&mut *self.conn.borrow_mut())?;
+                .load::<i32>(tx)?;
+
+            let environment_container_ids = environment_container::table
+                .select(environment_container::id)
+                .filter(
+                    environment_container::environment_service_id
+                        .eq_any(environment_service_ids.clone()),
+                )
+                .load::<i32>(tx)?;
 
             delete(
-                environment_service_action::table
-                    .filter(environment_service_action::id.eq_any(environment_service_ids)),
+                environment_container_action_log::table.filter(
+                    environment_container_action_log::environment_container_id
+                        .eq_any(environment_container_ids.clone()),
+                ),
             )
-            .execute(&mut *self.conn.borrow_mut())?;
+            .execute(tx)?;
+
+            delete(
+                environment_container::table
+                    .filter(environment_container::id.eq_any(environment_container_ids)),
+            )
+            .execute(tx)?;
+
+            delete(
+                environment_service_action::table.filter(
+                    environment_service_action::environment_service_id
+                        .eq_any(environment_service_ids.clone()),
+                ),
+            )
+            .execute(tx)?;
+
+            delete(
+                environment_service_param::table.filter(
+                    environment_service_param::environment_service_id
+                        .eq_any(environment_service_ids.clone()),
+                ),
+            )
+            .execute(tx)?;
+
+            delete(
+                environment_service_port::table.filter(
+                    environment_service_port::environment_service_id
+                        .eq_any(environment_service_ids),
+                ),
+            )
+            .execute(tx)?;
+
+            delete(
+                environment_service_file::table
+                    .filter(environment_service_file::environment_id.eq(environment_id)),
+            )
+            .execute(tx)?;
 
             delete(
                 environment_service::table
                     .filter(environment_service::environment_id.eq(environment_id)),
             )
-            .execute(&mut *self.conn.borrow_mut())?;
+            .execute(tx)?;
 
             delete(
                 environment_keychain::table
                     .filter(environment_keychain::environment_id.eq(environment_id)),
             )
-            .execute(&mut *self.conn.borrow_mut())?;
+            .execute(tx)?;
 
             delete(
                 environment_epoch::table
                     .filter(environment_epoch::environment_id.eq(environment_id)),
             )
+            .execute(tx)?;
+
+            delete(environment::table.filter(environment::id.eq(environment_id))).execute(tx)?;
+
+            Ok(())
+        })
+    }
+
+    pub fn delete_environment_keychain(
+        &self,
+        environment_id: i32,
+        stx_address: &str,
+    ) -> Result<()> {
+        delete(
+            environment_keychain::table
+                .filter(environment_keychain::environment_id.eq(environment_id))
+                .filter(environment_keychain::stx_address.eq(stx_address)),
+        )
+        .execute(&mut *self.conn.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Records the next nonce to use for a keychain, i.e. one past the nonce
+    /// of the last transaction it broadcast.
+    pub fn set_environment_keychain_nonce(
+        &self,
+        environment_id: i32,
+        stx_address: &str,
+        nonce: u64,
+    ) -> Result<()> {
+        update(environment_keychain::table)
+            .filter(environment_keychain::environment_id.eq(environment_id))
+            .filter(environment_keychain::stx_address.eq(stx_address))
+            .set(environment_keychain::nonce.eq(nonce as i64))
             .execute(&mut *self.conn.borrow_mut())?;
 
-            delete(
-                environment::table
-                    .filter(environment::id.eq(environment_id))
-                    .filter(environment::name.eq(name)),
-            )
+        Ok(())
+    }
+
+    /// Resets the nonces of all of the environment's keychains, for when its
+    /// chain state has been thrown away.
+    pub fn reset_environment_keychain_nonces(&self, environment_id: i32) -> Result<()> {
+        update(environment_keychain::table)
+            .filter(environment_keychain::environment_id.eq(environment_id))
+            .set(environment_keychain::nonce.eq(0))
             .execute(&mut *self.conn.borrow_mut())?;
-        }
 
         Ok(())
     }
 
-    pub fn delete_environment_keychain(&self, stx_address: &str) -> Result<()> {
+    /// Publishes a service's default ports, as defined by its service type, on
+    /// the host, marking them as automatically published. Each port is
+    /// published on the same port on the host, unless that is already taken by
+    /// another service, in which case the next free port is used instead.
+    pub fn publish_default_service_ports(
+        &self,
+        environment_service_id: i32,
+        service_type_id: i32,
+    ) -> Result<()> {
+        let conn = &mut *self.conn.borrow_mut();
+
+        conn.transaction(|tx| {
+            let default_ports = service_type_port::table
+                .filter(service_type_port::service_type_id.eq(service_type_id))
+                .order_by(service_type_port::port.asc())
+                .load::<ServiceTypePort>(tx)?;
+
+            let mut taken_ports = environment_service_port::table
+                .select(environment_service_port::publish_port)
+                .load::<i32>(tx)?;
+
+            for default_port in default_ports {
+                let mut publish_port = default_port.port;
+                while taken_ports.contains(&publish_port) {
+                    publish_port += 1;
+                }
+                if publish_port > u16::MAX as i32 {
+                    bail!(
+                        "No free port found to publish port {} on",
+                        default_port.port
+                    );
+                }
+
+                insert_into(environment_service_port::table)
+                    .values((
+                        environment_service_port::environment_service_id.eq(environment_service_id),
+                        environment_service_port::source_port.eq(default_port.port),
+                        environment_service_port::publish_port.eq(publish_port),
+                        environment_service_port::network_protocol_id
+                            .eq(default_port.network_protocol_id),
+                        environment_service_port::remark.eq(&default_port.remark),
+                        environment_service_port::is_auto_published.eq(true),
+                    ))
+                    .execute(tx)?;
+                taken_ports.push(publish_port);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Removes a service's automatically published ports, leaving those which
+    /// were published by the user in place.
+    pub fn unpublish_default_service_ports(&self, environment_service_id: i32) -> Result<()> {
         delete(
-            environment_keychain::table.filter(environment_keychain::stx_address.eq(stx_address)),
+            environment_service_port::table
+                .filter(environment_service_port::environment_service_id.eq(environment_service_id))
+                .filter(environment_service_port::is_auto_published.eq(true)),
         )
         .execute(&mut *self.conn.borrow_mut())?;
 
@@ -6,3 +6,23 @@ pub struct NewServiceVersionOpts {
     pub minimum_epoch_id: Option<i32>,
     pub maximum_epoch_id: Option<i32>,
 }
+
+/// The settings to change on an environment. Settings which are `None` are
+/// left as they are.
+#[derive(Debug, Default)]
+pub struct UpdateEnvironmentOpts {
+    pub name: Option<String>,
+    /// The new description, where `Some(None)` removes it.
+    pub description: Option<Option<String>>,
+    pub bitcoin_block_speed: Option<u32>,
+    pub publish_ports: Option<bool>,
+}
+
+impl UpdateEnvironmentOpts {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.description.is_none()
+            && self.bitcoin_block_speed.is_none()
+            && self.publish_ports.is_none()
+    }
+}
@@ -1,10 +1,21 @@
 use color_eyre::{eyre::eyre, Result};
-use diesel::{Connection, SqliteConnection};
-use stackify_common::types::EnvironmentName;
+use diesel::{insert_into, Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use stackify_common::{
+    types::{EnvironmentName, NetworkProtocol, PortMap},
+    ServiceType, ValueType,
+};
 
 use crate::util::FilterByServiceType;
 
-use super::{apply_db_migrations, cli_db::CliDatabase, AppDb};
+use super::{
+    apply_db_migrations,
+    cli_db::CliDatabase,
+    diesel::schema::{
+        environment_container, environment_container_action_log, environment_service_port,
+    },
+    opts::UpdateEnvironmentOpts,
+    AppDb, InsertServiceFile, InsertServiceParam,
+};
 
 #[test]
 pub fn test_load_empty_environment() -> Result<()> {
@@ -91,6 +102,302 @@ pub fn test_load_all_service_types() -> Result<()> {
     Ok(())
 }
 
+#[test]
+pub fn test_environment_keychain_nonce() -> Result<()> {
+    let db = get_db()?;
+    let env = db.create_environment("foo", 30)?;
+    db.add_environment_keychain(
+        env.id,
+        "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM",
+        "mvZtbibDAAA3WLpY7zXXFqRa3T4XSknBX7",
+        "public",
+        "private",
+        "",
+        100,
+        "",
+    )?;
+
+    let keychain = |db: &AppDb| -> Result<_> {
+        db.load_environment("foo")?
+            .keychains
+            .pop()
+            .ok_or(eyre!("Keychain not found"))
+    };
+    assert_eq!(keychain(&db)?.nonce, 0);
+
+    db.set_environment_keychain_nonce(env.id, "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM", 3)?;
+    assert_eq!(keychain(&db)?.nonce, 3);
+
+    db.reset_environment_keychain_nonces(env.id)?;
+    assert_eq!(keychain(&db)?.nonce, 0);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_delete_environment() -> Result<()> {
+    let db = get_db()?;
+
+    let service_type = ServiceType::BitcoinMiner;
+    let service_type_id = service_type as i32;
+    db.insert_service_param(&InsertServiceParam {
+        service_type: &service_type,
+        name: "Test Param",
+        key: "test_param",
+        description: "",
+        default_value: None,
+        is_required: false,
+        value_type: &ValueType::String,
+        allowed_values: None,
+    })?;
+    db.insert_service_file(InsertServiceFile {
+        service_type_id,
+        file_type_id: 1,
+        filename: "test.conf".to_string(),
+        destination_dir: "/tmp".to_string(),
+        description: "".to_string(),
+        default_contents: vec![],
+    })?;
+    let param_id = db.find_service_type_param_id_by_key(service_type_id, "test_param")?;
+    let file_id = db
+        .list_service_files_for_service_type(service_type_id)?
+        .iter()
+        .find(|file| file.filename == "test.conf")
+        .ok_or(eyre!("Service file not found"))?
+        .id;
+    let version_id = db
+        .list_service_versions()?
+        .filter_by_service_type(service_type_id)
+        .first()
+        .ok_or(eyre!("Service version not found"))?
+        .id;
+
+    // Give both environments one of everything, so that we can check that
+    // only the deleted one's rows are removed.
+    for (i, name) in ["foo", "bar"].iter().enumerate() {
+        let env = db.create_environment(name, 30)?;
+        let service =
+            db.add_environment_service(env.id, version_id, &format!("{}-miner", name), None)?;
+        db.add_environment_service_param(service.id, param_id, "value")?;
+        db.add_environment_service_file(env.id, service.id, file_id, b"contents")?;
+        db.add_environment_service_action(service.id, 1, Some(100), None)?;
+        db.add_environment_keychain(
+            env.id,
+            &format!("stx-{}", name),
+            &format!("btc-{}", name),
+            "public",
+            "private",
+            "",
+            100,
+            "",
+        )?;
+        insert_into(environment_service_port::table)
+            .values((
+                environment_service_port::environment_service_id.eq(service.id),
+                environment_service_port::source_port.eq(18443),
+                environment_service_port::publish_port.eq(18443 + i as i32),
+                environment_service_port::network_protocol_id.eq(1),
+            ))
+            .execute(&mut *db.conn.borrow_mut())?;
+        let container_id: i32 = insert_into(environment_container::table)
+            .values((
+                environment_container::environment_service_id.eq(service.id),
+                environment_container::container_id.eq(format!("container-{}", name)),
+                environment_container::service_version_id.eq(version_id),
+            ))
+            .returning(environment_container::id)
+            .get_result(&mut *db.conn.borrow_mut())?;
+        insert_into(environment_container_action_log::table)
+            .values((
+                environment_container_action_log::environment_container_id.eq(container_id),
+                environment_container_action_log::service_action_type_id.eq(1),
+                environment_container_action_log::at_block_height.eq(100),
+            ))
+            .execute(&mut *db.conn.borrow_mut())?;
+    }
+
+    let foo_id = db.get_environment_by_name("foo")?.id;
+    db.delete_environment("foo")?;
+
+    assert!(db.get_environment_by_name("foo").is_err());
+    assert!(db.list_environment_epochs(foo_id)?.is_empty());
+    assert!(db
+        .list_environment_services_for_environment_id(foo_id)?
+        .is_empty());
+    let port_count: i64 = environment_service_port::table
+        .count()
+        .get_result(&mut *db.conn.borrow_mut())?;
+    assert_eq!(port_count, 1);
+    let container_ids: Vec<String> = environment_container::table
+        .select(environment_container::container_id)
+        .load(&mut *db.conn.borrow_mut())?;
+    assert_eq!(container_ids, vec!["container-bar".to_string()]);
+    let action_log_count: i64 = environment_container_action_log::table
+        .count()
+        .get_result(&mut *db.conn.borrow_mut())?;
+    assert_eq!(action_log_count, 1);
+
+    let bar = db.as_clidb().load_environment("bar")?;
+    assert_eq!(bar.services.len(), 1);
+    assert_eq!(bar.services[0].param_value("test_param"), Some("value"));
+    assert_eq!(bar.keychains.len(), 1);
+    assert_eq!(
+        db.list_environment_service_files(bar.services[0].id)?.len(),
+        1
+    );
+
+    // Deleting an environment which doesn't exist is a no-op.
+    db.delete_environment("foo")?;
+
+    Ok(())
+}
+
+#[test]
+pub fn test_add_environment_service_port() -> Result<()> {
+    let db = get_db()?;
+
+    let service_type_id = ServiceType::StacksMiner as i32;
+    let version_id = db
+        .list_service_versions()?
+        .filter_by_service_type(service_type_id)
+        .first()
+        .ok_or(eyre!("Service version not found"))?
+        .id;
+
+    let env = db.create_environment("foo", 30)?;
+    let service = db.add_environment_service(env.id, version_id, "miner", None)?;
+    db.add_environment_service_port(service.id, 20443, 30443, 1, Some("RPC"))?;
+
+    let env = db.load_environment("foo")?;
+    assert_eq!(
+        env.services[0].port_mappings,
+        vec![PortMap {
+            host_port: 30443,
+            container_port: 20443,
+            protocol: NetworkProtocol::Tcp,
+            is_auto_published: false,
+        }]
+    );
+
+    let published = db
+        .find_environment_service_port_by_publish_port(30443)?
+        .ok_or(eyre!("Published port not found"))?;
+    assert_eq!(published.environment_service_id, service.id);
+    assert!(db
+        .find_environment_service_port_by_publish_port(20443)?
+        .is_none());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_update_environment() -> Result<()> {
+    let db = get_db()?;
+    let env = db.create_environment("foo", 30)?;
+    assert_eq!(env.description, None);
+    assert!(!env.publish_ports);
+
+    let updated = db.update_environment(
+        env.id,
+        &UpdateEnvironmentOpts {
+            name: Some("bar".to_string()),
+            description: Some(Some("My environment".to_string())),
+            bitcoin_block_speed: Some(10),
+            publish_ports: Some(true),
+        },
+    )?;
+    assert_eq!(updated.name, "bar");
+    assert_eq!(updated.description.as_deref(), Some("My environment"));
+    assert_eq!(updated.bitcoin_block_speed, 10);
+    assert!(updated.publish_ports);
+    assert!(db.find_environment_by_name("foo")?.is_none());
+    assert_eq!(
+        db.find_environment_by_name("bar")?.map(|env| env.id),
+        Some(env.id)
+    );
+
+    // Settings which aren't given are left as they are.
+    let updated = db.update_environment(
+        env.id,
+        &UpdateEnvironmentOpts {
+            description: Some(None),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(updated.name, "bar");
+    assert_eq!(updated.description, None);
+    assert_eq!(updated.bitcoin_block_speed, 10);
+    assert!(updated.publish_ports);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_publish_default_service_ports() -> Result<()> {
+    let db = get_db()?;
+
+    let service_type_id = ServiceType::BitcoinMiner as i32;
+    let version_id = db
+        .list_service_versions()?
+        .filter_by_service_type(service_type_id)
+        .first()
+        .ok_or(eyre!("Service version not found"))?
+        .id;
+
+    let env = db.create_environment("foo", 30)?;
+    let first = db.add_environment_service(env.id, version_id, "miner-1", None)?;
+    let second = db.add_environment_service(env.id, version_id, "miner-2", None)?;
+    db.publish_default_service_ports(first.id, service_type_id)?;
+    db.publish_default_service_ports(second.id, service_type_id)?;
+
+    let env = db.load_environment("foo")?;
+    let ports = |name: &str| -> Result<Vec<(u16, u16)>> {
+        let service = env
+            .services
+            .iter()
+            .find(|svc| svc.name == name)
+            .ok_or(eyre!("Service not found"))?;
+        let mut ports = service
+            .port_mappings
+            .iter()
+            .map(|map| (map.container_port, map.host_port))
+            .collect::<Vec<_>>();
+        ports.sort_unstable();
+        Ok(ports)
+    };
+
+    // The second miner's ports are moved out of the way of the first's.
+    assert_eq!(ports("miner-1")?, vec![(18443, 18443), (18444, 18444)]);
+    assert_eq!(ports("miner-2")?, vec![(18443, 18445), (18444, 18446)]);
+    assert!(env
+        .services
+        .iter()
+        .flat_map(|svc| svc.port_mappings.iter())
+        .all(|map| map.is_auto_published));
+
+    // Unpublishing leaves the ports published by the user in place.
+    db.unpublish_default_service_ports(first.id)?;
+    db.add_environment_service_port(first.id, 18443, 28443, 1, None)?;
+    db.unpublish_default_service_ports(first.id)?;
+    let env = db.load_environment("foo")?;
+    let first = env
+        .services
+        .iter()
+        .find(|svc| svc.name == "miner-1")
+        .ok_or(eyre!("Service not found"))?;
+    assert_eq!(
+        first.port_mappings,
+        vec![PortMap {
+            host_port: 28443,
+            container_port: 18443,
+            protocol: NetworkProtocol::Tcp,
+            is_auto_published: false,
+        }]
+    );
+
+    Ok(())
+}
+
 pub fn get_db() -> Result<AppDb> {
     let mut db_conn = SqliteConnection::establish(":memory:").unwrap();
     apply_db_migrations(&mut db_conn)?;
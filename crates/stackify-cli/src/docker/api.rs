@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use color_eyre::{eyre::eyre, owo_colors::OwoColorize, Result};
 
 use stackify_common::{
-    types::{EnvironmentName, EnvironmentService},
+    types::{Environment, EnvironmentName, EnvironmentService, NetworkProtocol},
     ServiceType,
 };
 
@@ -13,9 +13,10 @@ use crate::{
     cli::{log::clilog, StackifyHostDirs},
     docker::LabelKey,
     docker_api::{
-        models::{ContainerSummary, Network},
+        models::{ContainerSummary, HostConfig, Network},
         opts::{
-            ContainerCreateOpts, ContainerFilter, ContainerListOpts, NetworkFilter, NetworkListOpts,
+            ContainerCommitOpts, ContainerCreateOpts, ContainerCreateOptsBuilder, ContainerFilter,
+            ContainerListOpts, NetworkFilter, NetworkListOpts, PublishPort,
         },
         Id,
     },
@@ -24,6 +25,10 @@ use crate::{
 
 use super::{network_name, ContainerUser, StackifyContainerDirs};
 
+/// The address the Bitcoin miner mines to unless the service's
+/// `bitcoin_mining_address` parameter is set.
+pub const DEFAULT_BITCOIN_MINING_ADDRESS: &str = "bcrt1qng0yt0xgn40lykjj5q6xmgxfrqmdruy36kxgul";
+
 #[derive(Clone)]
 pub struct DockerApi {
     docker: ::stackify_docker_api::Docker,
@@ -176,6 +181,95 @@ impl DockerApi {
 
         Ok(Some((network_id.into(), network.clone())))
     }
+
+    /// Re-creates a stopped container under the given name and with the given
+    /// labels, which Docker can't change on an existing container. The
+    /// container is committed to an image first and the new container created
+    /// from it, so that its filesystem (and with it the chain state) is kept.
+    /// The new container has the same settings and port bindings, but isn't
+    /// attached to any networks or started.
+    ///
+    /// Returns the id of the new container.
+    pub async fn recreate_container(
+        &self,
+        id: &Id,
+        name: &str,
+        labels: HashMap<String, String>,
+    ) -> Result<Id> {
+        let container = self.docker.containers().get(id.clone());
+        let details = container.inspect().await?;
+        let config = details
+            .config
+            .ok_or(eyre!("Container {} has no config.", id))?;
+        let host_config: HostConfig = serde_json::from_value(
+            details
+                .host_config
+                .ok_or(eyre!("Container {} has no host config.", id))?,
+        )?;
+
+        let image = container
+            .commit(&ContainerCommitOpts::builder().build(), None)
+            .await?;
+        // The old container has to go first, as it holds the name and ports.
+        container.delete().await?;
+
+        let mut opts = ContainerCreateOpts::builder()
+            .name(name)
+            .image(&image)
+            .labels(labels)
+            .volumes(host_config.binds.unwrap_or_default());
+        if let Some(hostname) = config.hostname {
+            opts = opts.hostname(hostname);
+        }
+        if let Some(user) = config.user {
+            opts = opts.user(user);
+        }
+        if let Some(env) = config.env {
+            opts = opts.env(env);
+        }
+        if let Some(entrypoint) = config.entrypoint {
+            opts = opts.entrypoint(entrypoint);
+        }
+        if let Some(cmd) = config.cmd {
+            opts = opts.command(cmd);
+        }
+        for (port, bindings) in host_config.port_bindings.unwrap_or_default() {
+            let Some((port, protocol)) = port.split_once('/') else {
+                continue;
+            };
+            let Ok(port) = port.parse::<u32>() else {
+                continue;
+            };
+            let publish_port = match protocol {
+                "udp" => PublishPort::udp(port),
+                "sctp" => PublishPort::sctp(port),
+                _ => PublishPort::tcp(port),
+            };
+            for host_port in bindings
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|binding| binding.host_port.as_deref()?.parse::<u32>().ok())
+            {
+                opts = opts.expose(publish_port.clone(), host_port);
+            }
+        }
+
+        let new_container = self
+            .docker
+            .containers()
+            .create(&opts.build())
+            .await
+            .map_err(|e| {
+                eyre!(
+                    "Failed to re-create container '{}' from image {}: {}",
+                    name,
+                    image,
+                    e
+                )
+            })?;
+
+        Ok(new_container.id().clone())
+    }
 }
 
 pub struct DockerOptsHelper<'a>(&'a DockerApi);
@@ -185,25 +279,12 @@ impl<'a> DockerOptsHelper<'a> {
         Self(api)
     }
 
-    pub fn generate_stacks_keychain(&self) -> ContainerCreateOpts {
-        let labels = default_labels(None, None);
-
-        ContainerCreateOpts::builder()
-            .name("stx-stacks-cli")
-            .command(["npx", "@stacks/cli", "make_keychain", "-t"])
-            .attach_stdout(true)
-            .auto_remove(true)
-            .image("stackify-stacks-cli:latest")
-            .labels(labels)
-            .build()
-    }
-
     pub fn create_bitcoin_container(
         &self,
-        env_name: &EnvironmentName,
+        env: &Environment,
         service: &EnvironmentService,
     ) -> Result<ContainerCreateOpts> {
-        let labels = default_labels(Some(env_name), Some(service));
+        let labels = default_labels(Some(&env.name), Some(service));
 
         let bin_mount = format!(
             "{}:/opt/stackify/bin:rw",
@@ -221,6 +302,32 @@ impl<'a> DockerOptsHelper<'a> {
 
         let is_miner = ServiceType::from_i32(service.service_type.id)? == ServiceType::BitcoinMiner;
 
+        // The miner's own block frequency takes precedence over the
+        // environment's block speed.
+        let block_speed = match service.param_value("bitcoin_block_frequency") {
+            Some(value) => value.parse::<u32>().map_err(|e| {
+                eyre!(
+                    "Invalid Bitcoin block frequency '{}' for service '{}': {}",
+                    value,
+                    service.name,
+                    e
+                )
+            })?,
+            None => env.bitcoin_block_speed,
+        };
+        let mining_address = service
+            .param_value("bitcoin_mining_address")
+            .unwrap_or(DEFAULT_BITCOIN_MINING_ADDRESS);
+        // Every keychain gets a coinbase during the bootstrap, so that Stacks
+        // miners have BTC to submit block commits with.
+        let mut funding_addresses = env
+            .keychains
+            .iter()
+            .map(|kc| kc.btc_address.as_str())
+            .collect::<Vec<_>>();
+        funding_addresses.sort_unstable();
+        funding_addresses.dedup();
+
         let opts = ContainerCreateOpts::builder()
             .name(service_container_name(service))
             .hostname(&service.name)
@@ -231,11 +338,13 @@ impl<'a> DockerOptsHelper<'a> {
             .env(vec![
                 format!("BITCOIN_VERSION={}", service.version.version),
                 format!("BITCOIN_MINER={is_miner}"),
+                format!("BITCOIN_BLOCK_SPEED={block_speed}"),
+                format!("BITCOIN_MINING_ADDRESS={mining_address}"),
+                format!("BITCOIN_FUNDING_ADDRESSES={}", funding_addresses.join(" ")),
             ])
-            .entrypoint(["/bin/sh", "/entrypoint.sh"])
-            .build();
+            .entrypoint(["/bin/sh", "/entrypoint.sh"]);
 
-        Ok(opts)
+        Ok(publish_ports(opts, service).build())
     }
 
     pub fn create_stacks_node_container(
@@ -283,10 +392,9 @@ impl<'a> DockerOptsHelper<'a> {
                 "/bin/sh",
                 "-c",
                 "/entrypoint.sh 2>&1 | tee /var/log/stackify/stacks-node.log",
-            ])
-            .build();
+            ]);
 
-        Ok(opts)
+        Ok(publish_ports(opts, service).build())
     }
 
     pub fn create_stacks_signer_container(
@@ -330,11 +438,27 @@ impl<'a> DockerOptsHelper<'a> {
                 "/bin/sh",
                 "-c",
                 "/entrypoint.sh 2>&1 | tee /var/log/stackify/stacks-signer.log",
-            ])
-            .build();
+            ]);
+
+        Ok(publish_ports(opts, service).build())
+    }
+}
 
-        Ok(opts)
+/// Publishes the service's ports on the host.
+fn publish_ports(
+    mut opts: ContainerCreateOptsBuilder,
+    service: &EnvironmentService,
+) -> ContainerCreateOptsBuilder {
+    for map in service.port_mappings.iter() {
+        let publish_port = match map.protocol {
+            NetworkProtocol::Tcp => PublishPort::tcp(map.container_port.into()),
+            NetworkProtocol::Udp => PublishPort::udp(map.container_port.into()),
+            NetworkProtocol::Sctp => PublishPort::sctp(map.container_port.into()),
+        };
+        opts = opts.expose::<u32>(publish_port, map.host_port.into());
     }
+
+    opts
 }
 
 fn default_labels(
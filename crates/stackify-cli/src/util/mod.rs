@@ -3,7 +3,6 @@ use crate::db::diesel::model::{Epoch, ServiceType, ServiceUpgradePath, ServiceVe
 pub mod git;
 pub mod names;
 pub mod print;
-pub mod stacks_cli;
 
 pub trait FindById<T> {
     fn find_by_id(&self, id: i32) -> Option<&T>;
@@ -1,2 +1,6 @@
 pub mod opcodes;
+pub mod rpc;
 pub mod script;
+
+#[cfg(test)]
+mod tests;
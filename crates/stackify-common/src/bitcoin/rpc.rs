@@ -0,0 +1,177 @@
+//! A minimal async client for bitcoind's JSON-RPC interface, covering the
+//! calls Stackify needs to inspect and drive a regtest network.
+
+use reqwest::{Client, Url};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::stacks::api::errors::Error;
+
+const SATS_PER_BTC: f64 = 100_000_000.0;
+
+#[derive(Debug, Clone)]
+pub struct BitcoinRpcClient {
+    url: Url,
+    username: String,
+    password: String,
+    http: Client,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcRequest<'a> {
+    jsonrpc: &'a str,
+    id: &'a str,
+    method: &'a str,
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: Value,
+    error: Option<RpcErrorObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcErrorObject {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockchainInfo {
+    pub chain: String,
+    pub blocks: u64,
+    pub headers: u64,
+    #[serde(rename = "bestblockhash")]
+    pub best_block_hash: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ScanTxOutSetResult {
+    success: bool,
+    total_amount: f64,
+}
+
+impl BitcoinRpcClient {
+    /// Creates a client for the node at `url`, e.g. `http://localhost:18443`.
+    pub fn new(url: &str, username: &str, password: &str) -> Result<Self, Error> {
+        let url =
+            Url::parse(url).map_err(|e| Error::Other(format!("Invalid node URL '{url}': {e}")))?;
+        Ok(BitcoinRpcClient {
+            url,
+            username: username.to_string(),
+            password: password.to_string(),
+            http: Client::new(),
+        })
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Calls an RPC method, decoding its result. Errors reported by bitcoind
+    /// are returned as [`Error::RpcError`].
+    pub async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, Error> {
+        let request = RpcRequest {
+            jsonrpc: "1.0",
+            id: "stackify",
+            method,
+            params,
+        };
+        let response = self
+            .http
+            .post(self.url.clone())
+            .basic_auth(&self.username, Some(&self.password))
+            .json(&request)
+            .send()
+            .await?;
+
+        // bitcoind reports RPC errors with a non-success status code, but
+        // still includes the error in the body.
+        let status = response.status();
+        let body = response.text().await?;
+        let response = match serde_json::from_str::<RpcResponse>(&body) {
+            Ok(RpcResponse {
+                error: Some(error), ..
+            }) => {
+                return Err(Error::RpcError(format!(
+                    "{} failed: {} ({})",
+                    method, error.message, error.code
+                )))
+            }
+            _ if !status.is_success() => return Err(Error::HttpStatus(status.as_u16(), body)),
+            Ok(response) => response,
+            Err(e) => {
+                return Err(Error::RpcError(format!(
+                    "Invalid response to {method}: {e}"
+                )))
+            }
+        };
+        // Methods without a result return `null`, which decodes into `()`.
+        serde_json::from_value(response.result)
+            .map_err(|e| Error::RpcError(format!("Invalid result of {method}: {e}")))
+    }
+
+    pub async fn get_blockchain_info(&self) -> Result<BlockchainInfo, Error> {
+        self.call("getblockchaininfo", Value::Array(vec![])).await
+    }
+
+    pub async fn get_block_count(&self) -> Result<u64, Error> {
+        self.call("getblockcount", Value::Array(vec![])).await
+    }
+
+    pub async fn get_block_hash(&self, height: u64) -> Result<String, Error> {
+        self.call("getblockhash", serde_json::json!([height])).await
+    }
+
+    /// Mines `blocks` blocks to `address` right away, returning their hashes.
+    pub async fn generate_to_address(
+        &self,
+        blocks: u64,
+        address: &str,
+    ) -> Result<Vec<String>, Error> {
+        self.call("generatetoaddress", serde_json::json!([blocks, address]))
+            .await
+    }
+
+    /// Marks a block, and with it all of its descendants, as invalid. The node
+    /// falls back to the best chain without them.
+    pub async fn invalidate_block(&self, hash: &str) -> Result<(), Error> {
+        self.call("invalidateblock", serde_json::json!([hash]))
+            .await
+    }
+
+    /// A new address from the node's wallet.
+    pub async fn get_new_address(&self) -> Result<String, Error> {
+        self.call("getnewaddress", Value::Array(vec![])).await
+    }
+
+    /// The balance of an address in satoshis, from a scan of the UTXO set.
+    /// This works for any address, without it having to be imported into the
+    /// node's wallet.
+    pub async fn get_address_balance(&self, address: &str) -> Result<u64, Error> {
+        let result: ScanTxOutSetResult = self
+            .call(
+                "scantxoutset",
+                serde_json::json!(["start", [format!("addr({address})")]]),
+            )
+            .await?;
+        if !result.success {
+            return Err(Error::RpcError(format!(
+                "Failed to scan the UTXO set for {address}"
+            )));
+        }
+        Ok(btc_to_sats(result.total_amount))
+    }
+}
+
+/// Converts an amount in BTC, as reported by bitcoind, to satoshis.
+pub fn btc_to_sats(btc: f64) -> u64 {
+    (btc * SATS_PER_BTC).round() as u64
+}
+
+/// Formats an amount in satoshis as BTC, e.g. `50.00000000`.
+pub fn format_sats(sats: u64) -> String {
+    format!("{}.{:08}", sats / 100_000_000, sats % 100_000_000)
+}
@@ -0,0 +1,195 @@
+//! Crockford base32 ("c32") and c32check encoding, as used by Stacks
+//! addresses.
+//!
+//! This code is largely borrowed from https://github.com/stacks-network/stacks-core.
+
+use sha2::{Digest, Sha256};
+
+use super::errors::Error;
+
+const C32_CHARACTERS: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Maps an ASCII byte to its c32 digit. Lowercase letters are accepted, as are
+/// the commonly-confused `O`, `L` and `I` (read as `0`, `1` and `1`).
+fn c32_digit(c: u8) -> Option<u8> {
+    let c = match c.to_ascii_uppercase() {
+        b'O' => b'0',
+        b'L' | b'I' => b'1',
+        c => c,
+    };
+    C32_CHARACTERS
+        .iter()
+        .position(|&x| x == c)
+        .map(|pos| pos as u8)
+}
+
+/// Encodes bytes as c32. Leading zero bytes are preserved as leading `0`s.
+pub fn c32_encode(input_bytes: &[u8]) -> String {
+    let mut result = vec![];
+    let mut carry = 0;
+    let mut carry_bits = 0;
+
+    for current_value in input_bytes.iter().rev() {
+        let low_bits_to_take = 5 - carry_bits;
+        let low_bits = current_value & ((1 << low_bits_to_take) - 1);
+        let c32_value = (low_bits << carry_bits) + carry;
+        result.push(C32_CHARACTERS[c32_value as usize]);
+        carry_bits = (8 + carry_bits) - 5;
+        carry = current_value >> (8 - carry_bits);
+
+        if carry_bits >= 5 {
+            let c32_value = carry & ((1 << 5) - 1);
+            result.push(C32_CHARACTERS[c32_value as usize]);
+            carry_bits -= 5;
+            carry >>= 5;
+        }
+    }
+
+    if carry_bits > 0 {
+        result.push(C32_CHARACTERS[carry as usize]);
+    }
+
+    // remove leading zeros from c32 encoding
+    while let Some(v) = result.pop() {
+        if v != C32_CHARACTERS[0] {
+            result.push(v);
+            break;
+        }
+    }
+
+    // add leading zeros from input.
+    for current_value in input_bytes.iter() {
+        if *current_value == 0 {
+            result.push(C32_CHARACTERS[0]);
+        } else {
+            break;
+        }
+    }
+
+    result.reverse();
+    // every character comes from C32_CHARACTERS, so this is valid ASCII
+    String::from_utf8(result).expect("c32 output is always ASCII")
+}
+
+/// Decodes a c32 string into bytes.
+pub fn c32_decode(input_str: &str) -> Result<Vec<u8>, Error> {
+    if !input_str.is_ascii() {
+        return Err(Error::InvalidCrockford32);
+    }
+
+    let c32_digits = input_str
+        .as_bytes()
+        .iter()
+        .rev()
+        .map(|c| c32_digit(*c).ok_or(Error::InvalidCrockford32))
+        .collect::<Result<Vec<u8>, Error>>()?;
+
+    let mut result = Vec::with_capacity(input_str.len());
+    let mut carry: u16 = 0;
+    let mut carry_bits = 0;
+
+    for current_5bit in &c32_digits {
+        carry += (*current_5bit as u16) << carry_bits;
+        carry_bits += 5;
+
+        if carry_bits >= 8 {
+            result.push((carry & ((1 << 8) - 1)) as u8);
+            carry_bits -= 8;
+            carry >>= 8;
+        }
+    }
+
+    if carry_bits > 0 {
+        result.push(carry as u8);
+    }
+
+    // remove leading zeros from Vec<u8> encoding
+    while result.last() == Some(&0) {
+        result.pop();
+    }
+
+    // add leading zeros from input.
+    for current_value in c32_digits.iter().rev() {
+        if *current_value == 0 {
+            result.push(0);
+        } else {
+            break;
+        }
+    }
+
+    result.reverse();
+    Ok(result)
+}
+
+/// The first four bytes of sha256(sha256(version || data)).
+fn c32_checksum(version: u8, data: &[u8]) -> [u8; 4] {
+    let mut buf = Vec::with_capacity(1 + data.len());
+    buf.push(version);
+    buf.extend_from_slice(data);
+    let hash = Sha256::digest(Sha256::digest(&buf));
+    let mut checksum = [0u8; 4];
+    checksum.copy_from_slice(&hash[..4]);
+    checksum
+}
+
+/// Encodes a version byte and data with a trailing checksum.
+pub fn c32check_encode(version: u8, data: &[u8]) -> Result<String, Error> {
+    if version >= 32 {
+        return Err(Error::InvalidVersion(version));
+    }
+
+    let mut buf = data.to_vec();
+    buf.extend_from_slice(&c32_checksum(version, data));
+
+    Ok(format!(
+        "{}{}",
+        C32_CHARACTERS[version as usize] as char,
+        c32_encode(&buf)
+    ))
+}
+
+/// Decodes a c32check string into its version byte and data, verifying the
+/// checksum.
+pub fn c32check_decode(check_data: &str) -> Result<(u8, Vec<u8>), Error> {
+    if !check_data.is_ascii() {
+        return Err(Error::InvalidCrockford32);
+    }
+    if check_data.len() < 2 {
+        return Err(Error::TooShort(check_data.len()));
+    }
+
+    let (version, data) = check_data.split_at(1);
+    let version = c32_digit(version.as_bytes()[0]).ok_or(Error::InvalidCrockford32)?;
+    let data_sum = c32_decode(data)?;
+    if data_sum.len() < 4 {
+        return Err(Error::TooShort(data_sum.len()));
+    }
+
+    let (data, expected) = data_sum.split_at(data_sum.len() - 4);
+    let computed = c32_checksum(version, data);
+    if computed != expected {
+        return Err(Error::BadChecksum(
+            u32::from_be_bytes(computed),
+            u32::from_be_bytes(expected.try_into().expect("checksum is four bytes")),
+        ));
+    }
+
+    Ok((version, data.to_vec()))
+}
+
+/// Encodes a Stacks address: `S` followed by the c32check-encoded version and
+/// hash.
+pub fn c32_address(version: u8, data: &[u8]) -> Result<String, Error> {
+    Ok(format!("S{}", c32check_encode(version, data)?))
+}
+
+/// Decodes a Stacks address into its version byte and hash bytes.
+pub fn c32_address_decode(c32_address_str: &str) -> Result<(u8, Vec<u8>), Error> {
+    if c32_address_str.len() <= 5 {
+        return Err(Error::TooShort(c32_address_str.len()));
+    }
+    match c32_address_str.strip_prefix('S') {
+        Some(rest) => c32check_decode(rest),
+        None => Err(Error::InvalidCrockford32),
+    }
+}
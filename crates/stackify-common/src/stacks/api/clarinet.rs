@@ -0,0 +1,392 @@
+//! Clarinet projects: the `Clarinet.toml` manifest, the accounts of
+//! `settings/Devnet.toml` and deployment plans, resolved into the list of
+//! contracts to deploy and the order to deploy them in.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use bip39::Mnemonic;
+use serde::Deserialize;
+
+use super::{
+    clarity::ClarityVersion,
+    errors::Error,
+    keychain::{derive_private_key, STACKS_DERIVATION_PATH},
+};
+use crate::stacks::{StacksAddress, StacksPrivateKey};
+
+/// The plan Clarinet generates for devnet deployments, relative to the
+/// project root.
+pub const DEFAULT_DEVNET_PLAN: &str = "deployments/default.devnet-plan.yaml";
+/// The account which deploys the project's contracts by default.
+pub const DEFAULT_DEPLOYER: &str = "deployer";
+
+/// A Clarinet project, loaded from its `Clarinet.toml`.
+#[derive(Debug, Clone)]
+pub struct ClarinetProject {
+    /// The directory containing `Clarinet.toml`, which all paths in the
+    /// project are relative to.
+    pub root: PathBuf,
+    pub manifest: ClarinetManifest,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClarinetManifest {
+    pub project: ClarinetProjectSettings,
+    #[serde(default)]
+    pub contracts: BTreeMap<String, ClarinetContract>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClarinetProjectSettings {
+    pub name: String,
+    /// Contracts deployed on mainnet which the project depends on.
+    #[serde(default)]
+    pub requirements: Vec<ClarinetRequirement>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClarinetRequirement {
+    pub contract_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClarinetContract {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub clarity_version: Option<u8>,
+}
+
+/// An account of `settings/Devnet.toml`, e.g. `[accounts.deployer]`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DevnetAccount {
+    pub mnemonic: String,
+    #[serde(default)]
+    pub balance: u64,
+    #[serde(default)]
+    pub derivation: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DevnetSettings {
+    #[serde(default)]
+    accounts: BTreeMap<String, DevnetAccount>,
+}
+
+/// A contract to deploy, with its source already read (and, for requirements,
+/// with the mainnet principals remapped).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedContract {
+    pub name: String,
+    /// The address expected to deploy the contract.
+    pub sender: StacksAddress,
+    pub path: PathBuf,
+    pub source: String,
+    pub clarity_version: Option<ClarityVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeploymentPlan {
+    plan: DeploymentPlanBatches,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeploymentPlanBatches {
+    batches: Vec<DeploymentBatch>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeploymentBatch {
+    /// Each transaction is a single-entry map from its kind, e.g.
+    /// `contract-publish`, to its details.
+    transactions: Vec<BTreeMap<String, serde_yaml::Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ContractPublishSpec {
+    contract_name: String,
+    expected_sender: String,
+    path: PathBuf,
+    #[serde(default)]
+    clarity_version: Option<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RequirementPublishSpec {
+    contract_id: String,
+    remap_sender: String,
+    #[serde(default)]
+    remap_principals: BTreeMap<String, String>,
+    path: PathBuf,
+    #[serde(default)]
+    clarity_version: Option<u8>,
+}
+
+impl ClarinetProject {
+    /// Loads the project whose manifest is at `manifest_path`.
+    pub fn load(manifest_path: &Path) -> Result<Self, Error> {
+        let manifest = std::fs::read_to_string(manifest_path)?;
+        let manifest = toml::from_str(&manifest).map_err(|e| {
+            Error::ClarinetError(format!("Invalid '{}': {e}", manifest_path.display()))
+        })?;
+        let root = manifest_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        Ok(ClarinetProject { root, manifest })
+    }
+
+    /// The accounts of `settings/Devnet.toml`, keyed by their name.
+    pub fn devnet_accounts(&self) -> Result<BTreeMap<String, DevnetAccount>, Error> {
+        let path = self.root.join("settings").join("Devnet.toml");
+        let settings = std::fs::read_to_string(&path)?;
+        let settings: DevnetSettings = toml::from_str(&settings)
+            .map_err(|e| Error::ClarinetError(format!("Invalid '{}': {e}", path.display())))?;
+        Ok(settings.accounts)
+    }
+
+    /// The path of the default devnet deployment plan, if the project has one.
+    pub fn default_devnet_plan(&self) -> Option<PathBuf> {
+        Some(self.root.join(DEFAULT_DEVNET_PLAN)).filter(|path| path.is_file())
+    }
+
+    /// The project's contracts, deployed by the devnet `deployer` account and
+    /// ordered so that every contract comes after the contracts it references.
+    pub fn contracts_in_dependency_order(&self) -> Result<Vec<PlannedContract>, Error> {
+        let accounts = self.devnet_accounts()?;
+        let deployer = accounts
+            .get(DEFAULT_DEPLOYER)
+            .ok_or_else(|| {
+                Error::ClarinetError(format!(
+                    "settings/Devnet.toml has no '{DEFAULT_DEPLOYER}' account"
+                ))
+            })?
+            .address()?;
+
+        let mut contracts = BTreeMap::new();
+        for (name, contract) in self.manifest.contracts.iter() {
+            let path = self.root.join(&contract.path);
+            contracts.insert(
+                name.clone(),
+                PlannedContract {
+                    name: name.clone(),
+                    sender: deployer.clone(),
+                    source: std::fs::read_to_string(&path)?,
+                    path,
+                    clarity_version: contract.clarity_version.map(clarity_version).transpose()?,
+                },
+            );
+        }
+
+        let dependencies = contracts
+            .iter()
+            .map(|(name, contract)| {
+                let references = contract_references(&contract.source)
+                    .into_iter()
+                    .filter(|reference| reference != name && contracts.contains_key(reference))
+                    .collect();
+                (name.clone(), references)
+            })
+            .collect();
+
+        Ok(dependency_order(&dependencies)?
+            .into_iter()
+            .filter_map(|name| contracts.remove(&name))
+            .collect())
+    }
+
+    /// The contracts published by a deployment plan, in the plan's order.
+    /// Requirements are published under their remapped sender, with their
+    /// mainnet principals remapped in the source. Any other kind of
+    /// transaction is returned by name so that it can be reported.
+    pub fn load_plan(&self, path: &Path) -> Result<(Vec<PlannedContract>, Vec<String>), Error> {
+        let plan = std::fs::read_to_string(path)?;
+        let plan: DeploymentPlan = serde_yaml::from_str(&plan)
+            .map_err(|e| Error::ClarinetError(format!("Invalid '{}': {e}", path.display())))?;
+        let invalid = |e: serde_yaml::Error| {
+            Error::ClarinetError(format!("Invalid transaction in '{}': {e}", path.display()))
+        };
+
+        let mut contracts = vec![];
+        let mut unsupported = vec![];
+        for transaction in plan.plan.batches.into_iter().flat_map(|b| b.transactions) {
+            for (kind, spec) in transaction {
+                match kind.as_str() {
+                    "contract-publish" => {
+                        let spec: ContractPublishSpec =
+                            serde_yaml::from_value(spec).map_err(invalid)?;
+                        let path = self.root.join(&spec.path);
+                        contracts.push(PlannedContract {
+                            name: spec.contract_name,
+                            sender: spec.expected_sender.parse()?,
+                            source: std::fs::read_to_string(&path)?,
+                            path,
+                            clarity_version: spec
+                                .clarity_version
+                                .map(clarity_version)
+                                .transpose()?,
+                        });
+                    }
+                    "requirement-publish" => {
+                        let spec: RequirementPublishSpec =
+                            serde_yaml::from_value(spec).map_err(invalid)?;
+                        let name = spec
+                            .contract_id
+                            .split_once('.')
+                            .map(|(_, name)| name.to_string())
+                            .ok_or_else(|| {
+                                Error::ClarinetError(format!(
+                                    "Invalid requirement '{}'",
+                                    spec.contract_id
+                                ))
+                            })?;
+                        let path = self.root.join(&spec.path);
+                        let source = spec
+                            .remap_principals
+                            .iter()
+                            .fold(std::fs::read_to_string(&path)?, |source, (from, to)| {
+                                source.replace(from, to)
+                            });
+                        contracts.push(PlannedContract {
+                            name,
+                            sender: spec.remap_sender.parse()?,
+                            source,
+                            path,
+                            clarity_version: spec
+                                .clarity_version
+                                .map(clarity_version)
+                                .transpose()?,
+                        });
+                    }
+                    _ => unsupported.push(kind),
+                }
+            }
+        }
+
+        Ok((contracts, unsupported))
+    }
+}
+
+impl DevnetAccount {
+    pub fn private_key(&self) -> Result<StacksPrivateKey, Error> {
+        let mnemonic = Mnemonic::parse_normalized(&self.mnemonic)
+            .map_err(|e| Error::InvalidKey(format!("Invalid mnemonic: {e}")))?;
+        derive_private_key(
+            &mnemonic.to_seed(""),
+            self.derivation.as_deref().unwrap_or(STACKS_DERIVATION_PATH),
+        )
+    }
+
+    /// The account's (testnet) Stacks address.
+    pub fn address(&self) -> Result<StacksAddress, Error> {
+        Ok(StacksAddress::p2pkh(
+            false,
+            &self.private_key()?.public_key(),
+        ))
+    }
+}
+
+fn clarity_version(version: u8) -> Result<ClarityVersion, Error> {
+    match version {
+        1 => Ok(ClarityVersion::Clarity1),
+        2 => Ok(ClarityVersion::Clarity2),
+        3 => Ok(ClarityVersion::Clarity3),
+        v => Err(Error::ClarinetError(format!(
+            "Unsupported Clarity version: {v}"
+        ))),
+    }
+}
+
+/// The names of the contracts referenced by `source`, either relative to the
+/// deployer (`.token`, `.token.sip-010-trait`) or fully qualified
+/// (`'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.token`). Comments and string
+/// literals are ignored.
+pub fn contract_references(source: &str) -> BTreeSet<String> {
+    let mut references = BTreeSet::new();
+    let mut token = String::new();
+    let mut chars = source.chars();
+
+    let mut flush = |token: &mut String| {
+        let trimmed = token.trim_start_matches('\'');
+        let name = match trimmed.split('.').collect::<Vec<_>>().as_slice() {
+            ["", name, ..] => Some(*name),
+            [issuer, name, ..] if is_standard_principal(issuer) => Some(*name),
+            _ => None,
+        };
+        if let Some(name) = name.filter(|name| !name.is_empty()) {
+            references.insert(name.to_string());
+        }
+        token.clear();
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                flush(&mut token);
+                chars.by_ref().find(|c| *c == '\n');
+            }
+            '"' => {
+                flush(&mut token);
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            c if c.is_whitespace() || "()[]{},:".contains(c) => flush(&mut token),
+            c => token.push(c),
+        }
+    }
+    flush(&mut token);
+
+    references
+}
+
+fn is_standard_principal(s: &str) -> bool {
+    s.starts_with('S')
+        && (28..=41).contains(&s.len())
+        && s.chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// Orders contracts so that each comes after its dependencies, preferring
+/// alphabetical order where the dependencies allow it.
+pub fn dependency_order(
+    dependencies: &BTreeMap<String, BTreeSet<String>>,
+) -> Result<Vec<String>, Error> {
+    let mut ordered: Vec<String> = vec![];
+    let mut remaining = dependencies.keys().collect::<BTreeSet<_>>();
+
+    while !remaining.is_empty() {
+        let next = remaining
+            .iter()
+            .find(|name| {
+                dependencies[name.as_str()]
+                    .iter()
+                    .all(|dep| ordered.contains(dep) || !dependencies.contains_key(dep))
+            })
+            .copied()
+            .ok_or_else(|| {
+                Error::ClarinetError(format!(
+                    "Circular dependency between the contracts {}",
+                    remaining
+                        .iter()
+                        .map(|name| format!("'{name}'"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            })?;
+        remaining.remove(next);
+        ordered.push(next.clone());
+    }
+
+    Ok(ordered)
+}
@@ -1,7 +1,31 @@
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
 
 use serde::{Deserialize, Serialize};
 
+use crate::stacks::StacksAddress;
+
+use super::{errors::Error, hash::Hash160};
+
+mod codec;
+mod display;
+mod parser;
+
+pub use codec::value_hex;
+pub use parser::{parse_value, parse_value_typed};
+
+/// Maximum length of a Clarity name (and of a contract name on the wire).
+pub const MAX_STRING_LEN: u8 = 128;
+/// Maximum length of the name of a newly-deployed contract.
+pub const CONTRACT_MAX_NAME_LENGTH: usize = 40;
+/// Maximum size (in bytes) of a single Clarity value.
+pub const MAX_VALUE_SIZE: u32 = 1024 * 1024;
+/// Maximum nesting depth of a Clarity type.
+pub const MAX_TYPE_DEPTH: u8 = 32;
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub enum ClarityVersion {
     Clarity1,
@@ -173,3 +197,617 @@ pub enum Value {
     //  compound values like `Optional`, `Tuple`, `Response`, or `Sequence(List)`)
     //  must be handled in the value sanitization routine!
 }
+
+/// Returns whether or not `name` is a valid Clarity name, i.e. the name of a
+/// function, variable, map, asset or tuple key.
+pub fn is_valid_clarity_name(name: &str) -> bool {
+    if name.is_empty() || name.len() > MAX_STRING_LEN as usize {
+        return false;
+    }
+    if matches!(name, "-" | "+" | "=" | "/" | "*" | "<" | ">" | "<=" | ">=") {
+        return true;
+    }
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "-_!?+<>=/*".contains(c))
+}
+
+/// Returns whether or not `name` is a valid contract name.
+pub fn is_valid_contract_name(name: &str) -> bool {
+    if name == "__transient" {
+        return true;
+    }
+    if name.is_empty() || name.len() > MAX_STRING_LEN as usize {
+        return false;
+    }
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+impl From<StacksAddress> for StandardPrincipalData {
+    fn from(addr: StacksAddress) -> Self {
+        StandardPrincipalData(addr.version, addr.bytes.0)
+    }
+}
+
+impl From<StandardPrincipalData> for StacksAddress {
+    fn from(principal: StandardPrincipalData) -> Self {
+        StacksAddress::new(principal.0, Hash160(principal.1))
+    }
+}
+
+impl From<StacksAddress> for PrincipalData {
+    fn from(addr: StacksAddress) -> Self {
+        PrincipalData::Standard(addr.into())
+    }
+}
+
+impl From<StandardPrincipalData> for PrincipalData {
+    fn from(principal: StandardPrincipalData) -> Self {
+        PrincipalData::Standard(principal)
+    }
+}
+
+impl From<QualifiedContractIdentifier> for PrincipalData {
+    fn from(contract: QualifiedContractIdentifier) -> Self {
+        PrincipalData::Contract(contract)
+    }
+}
+
+impl fmt::Display for StandardPrincipalData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", StacksAddress::new(self.0, Hash160(self.1)))
+    }
+}
+
+impl FromStr for StandardPrincipalData {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(StacksAddress::from_str(s)?.into())
+    }
+}
+
+impl QualifiedContractIdentifier {
+    pub fn new(issuer: StandardPrincipalData, name: &str) -> Result<Self, Error> {
+        if !is_valid_contract_name(name) {
+            return Err(Error::Other(format!("Invalid contract name: '{name}'")));
+        }
+        Ok(QualifiedContractIdentifier {
+            issuer,
+            name: name.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for QualifiedContractIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.issuer, self.name)
+    }
+}
+
+/// Parses a fully-qualified contract identifier, i.e. `ST...address.contract-name`.
+impl FromStr for QualifiedContractIdentifier {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (issuer, name) = s.split_once('.').ok_or_else(|| {
+            Error::Other(format!(
+                "Expected a contract identifier of the form 'address.contract-name', got '{s}'"
+            ))
+        })?;
+        QualifiedContractIdentifier::new(issuer.parse()?, name)
+    }
+}
+
+impl fmt::Display for PrincipalData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrincipalData::Standard(principal) => write!(f, "{principal}"),
+            PrincipalData::Contract(contract) => write!(f, "{contract}"),
+        }
+    }
+}
+
+/// Parses either a standard principal (`ST...`) or a contract principal
+/// (`ST....contract-name`).
+impl FromStr for PrincipalData {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('.') {
+            Ok(PrincipalData::Contract(s.parse()?))
+        } else {
+            Ok(PrincipalData::Standard(s.parse()?))
+        }
+    }
+}
+
+impl BufferLength {
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl StringUTF8Length {
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl TryFrom<u32> for BufferLength {
+    type Error = Error;
+
+    fn try_from(len: u32) -> Result<Self, Self::Error> {
+        if len > MAX_VALUE_SIZE {
+            return Err(Error::ClarityTypeError(format!(
+                "buffer length {len} exceeds the maximum value size"
+            )));
+        }
+        Ok(BufferLength(len))
+    }
+}
+
+impl TryFrom<u32> for StringUTF8Length {
+    type Error = Error;
+
+    /// `len` is the number of characters, each of which may take up to four bytes.
+    fn try_from(len: u32) -> Result<Self, Self::Error> {
+        if len > MAX_VALUE_SIZE / 4 {
+            return Err(Error::ClarityTypeError(format!(
+                "string length {len} exceeds the maximum value size"
+            )));
+        }
+        Ok(StringUTF8Length(len))
+    }
+}
+
+impl ListTypeData {
+    pub fn new_list(entry_type: TypeSignature, max_len: u32) -> ListTypeData {
+        ListTypeData {
+            max_len,
+            entry_type: Box::new(entry_type),
+        }
+    }
+
+    pub fn get_max_len(&self) -> u32 {
+        self.max_len
+    }
+
+    pub fn get_list_item_type(&self) -> &TypeSignature {
+        &self.entry_type
+    }
+}
+
+impl TryFrom<Vec<(String, TypeSignature)>> for TupleTypeSignature {
+    type Error = Error;
+
+    fn try_from(fields: Vec<(String, TypeSignature)>) -> Result<Self, Self::Error> {
+        if fields.is_empty() {
+            return Err(Error::ClarityTypeError(
+                "tuples must have at least one field".into(),
+            ));
+        }
+        let mut type_map = HashMap::with_capacity(fields.len());
+        for (name, field_type) in fields {
+            if !is_valid_clarity_name(&name) {
+                return Err(Error::ClarityTypeError(format!(
+                    "invalid tuple key: '{name}'"
+                )));
+            }
+            if type_map.insert(name.clone(), field_type).is_some() {
+                return Err(Error::ClarityTypeError(format!(
+                    "duplicate tuple key: '{name}'"
+                )));
+            }
+        }
+        Ok(TupleTypeSignature { type_map })
+    }
+}
+
+impl TupleTypeSignature {
+    pub fn get_type_map(&self) -> &HashMap<String, TypeSignature> {
+        &self.type_map
+    }
+
+    pub fn field_type(&self, field: &str) -> Option<&TypeSignature> {
+        self.type_map.get(field)
+    }
+}
+
+impl TypeSignature {
+    /// Returns the (most specific) type of the given value.
+    pub fn type_of(value: &Value) -> TypeSignature {
+        match value {
+            Value::Int(_) => TypeSignature::IntType,
+            Value::UInt(_) => TypeSignature::UIntType,
+            Value::Bool(_) => TypeSignature::BoolType,
+            Value::Principal(_) => TypeSignature::PrincipalType,
+            Value::Sequence(SequenceData::Buffer(buff)) => TypeSignature::SequenceType(
+                SequenceSubtype::BufferType(BufferLength(buff.data.len() as u32)),
+            ),
+            Value::Sequence(SequenceData::List(list)) => {
+                TypeSignature::SequenceType(SequenceSubtype::ListType(list.type_signature.clone()))
+            }
+            Value::Sequence(SequenceData::String(CharType::ASCII(ascii))) => {
+                TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+                    BufferLength(ascii.data.len() as u32),
+                )))
+            }
+            Value::Sequence(SequenceData::String(CharType::UTF8(utf8))) => {
+                TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(
+                    StringUTF8Length(utf8.data.len() as u32),
+                )))
+            }
+            Value::Tuple(tuple) => TypeSignature::TupleType(tuple.type_signature.clone()),
+            Value::Optional(OptionalData { data: None }) => {
+                TypeSignature::OptionalType(Box::new(TypeSignature::NoType))
+            }
+            Value::Optional(OptionalData { data: Some(inner) }) => {
+                TypeSignature::OptionalType(Box::new(TypeSignature::type_of(inner)))
+            }
+            Value::Response(ResponseData { committed, data }) => {
+                let inner = TypeSignature::type_of(data);
+                if *committed {
+                    TypeSignature::ResponseType(Box::new((inner, TypeSignature::NoType)))
+                } else {
+                    TypeSignature::ResponseType(Box::new((TypeSignature::NoType, inner)))
+                }
+            }
+            Value::CallableContract(CallableData {
+                contract_identifier,
+                trait_identifier,
+            }) => match trait_identifier {
+                Some(trait_identifier) => {
+                    TypeSignature::CallableType(CallableSubtype::Trait(trait_identifier.clone()))
+                }
+                None => TypeSignature::CallableType(CallableSubtype::Principal(
+                    contract_identifier.clone(),
+                )),
+            },
+        }
+    }
+
+    /// Returns the smallest type which admits values of both `a` and `b`.
+    pub fn least_supertype(a: &TypeSignature, b: &TypeSignature) -> Result<TypeSignature, Error> {
+        use SequenceSubtype::*;
+        use StringSubtype::*;
+        use TypeSignature::*;
+
+        match (a, b) {
+            (NoType, x) | (x, NoType) => Ok(x.clone()),
+            (OptionalType(a), OptionalType(b)) => {
+                Ok(OptionalType(Box::new(Self::least_supertype(a, b)?)))
+            }
+            (ResponseType(a), ResponseType(b)) => Ok(ResponseType(Box::new((
+                Self::least_supertype(&a.0, &b.0)?,
+                Self::least_supertype(&a.1, &b.1)?,
+            )))),
+            (SequenceType(ListType(a)), SequenceType(ListType(b))) => {
+                let entry_type = Self::least_supertype(&a.entry_type, &b.entry_type)?;
+                Ok(SequenceType(ListType(ListTypeData::new_list(
+                    entry_type,
+                    a.max_len.max(b.max_len),
+                ))))
+            }
+            (SequenceType(BufferType(a)), SequenceType(BufferType(b))) => {
+                Ok(SequenceType(BufferType(BufferLength(a.0.max(b.0)))))
+            }
+            (SequenceType(StringType(ASCII(a))), SequenceType(StringType(ASCII(b)))) => {
+                Ok(SequenceType(StringType(ASCII(BufferLength(a.0.max(b.0))))))
+            }
+            (SequenceType(StringType(UTF8(a))), SequenceType(StringType(UTF8(b)))) => Ok(
+                SequenceType(StringType(UTF8(StringUTF8Length(a.0.max(b.0))))),
+            ),
+            (TupleType(a), TupleType(b)) => {
+                if a.type_map.len() != b.type_map.len() {
+                    return Err(type_mismatch(a, b));
+                }
+                let mut type_map = HashMap::with_capacity(a.type_map.len());
+                for (name, a_type) in a.type_map.iter() {
+                    let b_type = b.type_map.get(name).ok_or_else(|| type_mismatch(a, b))?;
+                    type_map.insert(name.clone(), Self::least_supertype(a_type, b_type)?);
+                }
+                Ok(TupleType(TupleTypeSignature { type_map }))
+            }
+            (PrincipalType, CallableType(CallableSubtype::Principal(_)))
+            | (CallableType(CallableSubtype::Principal(_)), PrincipalType) => Ok(PrincipalType),
+            (
+                CallableType(CallableSubtype::Principal(a)),
+                CallableType(CallableSubtype::Principal(b)),
+            ) if a != b => Ok(ListUnionType(HashSet::from([
+                CallableSubtype::Principal(a.clone()),
+                CallableSubtype::Principal(b.clone()),
+            ]))),
+            (x, y) if x == y => Ok(x.clone()),
+            (x, y) => Err(type_mismatch(x, y)),
+        }
+    }
+}
+
+/// Returns the contract identified by `value`, if it is a contract principal
+/// or callable contract.
+fn contract_of(value: &Value) -> Option<&QualifiedContractIdentifier> {
+    match value {
+        Value::Principal(PrincipalData::Contract(contract)) => Some(contract),
+        Value::CallableContract(CallableData {
+            contract_identifier,
+            ..
+        }) => Some(contract_identifier),
+        _ => None,
+    }
+}
+
+/// Prefixes a type error with the location at which it occurred, e.g.
+/// `tuple field 'a': expected uint, got 1`.
+fn with_context(e: Error, context: &str) -> Error {
+    match e {
+        Error::ClarityTypeError(msg) => Error::ClarityTypeError(format!("{context}: {msg}")),
+        e => e,
+    }
+}
+
+fn type_mismatch(a: &impl std::fmt::Debug, b: &impl std::fmt::Debug) -> Error {
+    Error::ClarityTypeError(format!("type mismatch: {a:?} vs. {b:?}"))
+}
+
+impl TupleData {
+    /// Builds a tuple from a list of (key, value) pairs, failing on duplicate
+    /// or invalid keys.
+    pub fn from_data(data: Vec<(String, Value)>) -> Result<TupleData, Error> {
+        let mut type_map = HashMap::with_capacity(data.len());
+        let mut data_map = BTreeMap::new();
+        for (name, value) in data {
+            if !is_valid_clarity_name(&name) {
+                return Err(Error::ClarityTypeError(format!(
+                    "invalid tuple key: '{name}'"
+                )));
+            }
+            if data_map.contains_key(&name) {
+                return Err(Error::ClarityTypeError(format!(
+                    "duplicate tuple key: '{name}'"
+                )));
+            }
+            type_map.insert(name.clone(), TypeSignature::type_of(&value));
+            data_map.insert(name, value);
+        }
+        Ok(TupleData {
+            type_signature: TupleTypeSignature { type_map },
+            data_map,
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.data_map.get(name)
+    }
+}
+
+impl Value {
+    pub fn none() -> Value {
+        Value::Optional(OptionalData { data: None })
+    }
+
+    pub fn some(data: Value) -> Value {
+        Value::Optional(OptionalData {
+            data: Some(Box::new(data)),
+        })
+    }
+
+    pub fn okay(data: Value) -> Value {
+        Value::Response(ResponseData {
+            committed: true,
+            data: Box::new(data),
+        })
+    }
+
+    pub fn error(data: Value) -> Value {
+        Value::Response(ResponseData {
+            committed: false,
+            data: Box::new(data),
+        })
+    }
+
+    pub fn buff_from(data: Vec<u8>) -> Result<Value, Error> {
+        if data.len() > MAX_VALUE_SIZE as usize {
+            return Err(Error::ClarityTypeError(format!(
+                "buffer of {} bytes exceeds the maximum value size",
+                data.len()
+            )));
+        }
+        Ok(Value::Sequence(SequenceData::Buffer(BuffData { data })))
+    }
+
+    /// Builds a `string-ascii` value, failing if any byte is not printable ASCII.
+    pub fn string_ascii_from_bytes(data: Vec<u8>) -> Result<Value, Error> {
+        if data.len() > MAX_VALUE_SIZE as usize {
+            return Err(Error::ClarityTypeError(format!(
+                "string of {} bytes exceeds the maximum value size",
+                data.len()
+            )));
+        }
+        if let Some(c) = data.iter().find(|c| {
+            !(c.is_ascii_alphanumeric() || c.is_ascii_punctuation() || c.is_ascii_whitespace())
+        }) {
+            return Err(Error::ClarityTypeError(format!(
+                "invalid character in string-ascii: {c:#04x}"
+            )));
+        }
+        Ok(Value::Sequence(SequenceData::String(CharType::ASCII(
+            ASCIIData { data },
+        ))))
+    }
+
+    /// Builds a `string-utf8` value from UTF-8 encoded bytes.
+    pub fn string_utf8_from_bytes(data: Vec<u8>) -> Result<Value, Error> {
+        if data.len() > MAX_VALUE_SIZE as usize {
+            return Err(Error::ClarityTypeError(format!(
+                "string of {} bytes exceeds the maximum value size",
+                data.len()
+            )));
+        }
+        let s = std::str::from_utf8(&data)
+            .map_err(|e| Error::ClarityTypeError(format!("invalid UTF-8 string: {e}")))?;
+        let data = s
+            .chars()
+            .map(|c| c.to_string().into_bytes())
+            .collect::<Vec<_>>();
+        Ok(Value::Sequence(SequenceData::String(CharType::UTF8(
+            UTF8Data { data },
+        ))))
+    }
+
+    /// Builds a list value, failing if the items don't share a common type.
+    pub fn list_from(items: Vec<Value>) -> Result<Value, Error> {
+        let mut entry_type = TypeSignature::NoType;
+        for item in items.iter() {
+            entry_type =
+                TypeSignature::least_supertype(&entry_type, &TypeSignature::type_of(item))?;
+        }
+        let max_len = items.len() as u32;
+        Ok(Value::Sequence(SequenceData::List(ListData {
+            data: items,
+            type_signature: ListTypeData::new_list(entry_type, max_len),
+        })))
+    }
+
+    pub fn tuple_from_data(data: Vec<(String, Value)>) -> Result<Value, Error> {
+        Ok(Value::Tuple(TupleData::from_data(data)?))
+    }
+    /// Checks this value against `expected`, converting contract principals
+    /// into callable contracts where the type calls for a trait or contract
+    /// reference (as happens when a function argument is a `<trait>`).
+    pub fn conform_to_type(self, expected: &TypeSignature) -> Result<Value, Error> {
+        use SequenceSubtype::*;
+        use StringSubtype::*;
+        use TypeSignature::*;
+
+        let mismatch =
+            |value: &Value| Error::ClarityTypeError(format!("expected {expected}, got {value}"));
+
+        match (expected, self) {
+            (IntType, value @ Value::Int(_))
+            | (UIntType, value @ Value::UInt(_))
+            | (BoolType, value @ Value::Bool(_))
+            | (PrincipalType, value @ Value::Principal(_)) => Ok(value),
+            (PrincipalType, Value::CallableContract(callable)) => Ok(Value::Principal(
+                PrincipalData::Contract(callable.contract_identifier),
+            )),
+            (CallableType(CallableSubtype::Principal(expected_contract)), value) => {
+                match contract_of(&value) {
+                    Some(contract) if contract == expected_contract => {
+                        Ok(Value::CallableContract(CallableData {
+                            contract_identifier: contract.clone(),
+                            trait_identifier: None,
+                        }))
+                    }
+                    _ => Err(mismatch(&value)),
+                }
+            }
+            (
+                CallableType(CallableSubtype::Trait(trait_id)) | TraitReferenceType(trait_id),
+                value,
+            ) => {
+                // Whether or not the contract actually implements the trait can
+                // only be checked by the node.
+                match contract_of(&value) {
+                    Some(contract) => Ok(Value::CallableContract(CallableData {
+                        contract_identifier: contract.clone(),
+                        trait_identifier: Some(trait_id.clone()),
+                    })),
+                    None => Err(mismatch(&value)),
+                }
+            }
+            (ListUnionType(types), value) => match contract_of(&value) {
+                Some(contract)
+                    if types.iter().any(|t| match t {
+                        CallableSubtype::Principal(c) => c == contract,
+                        CallableSubtype::Trait(_) => true,
+                    }) =>
+                {
+                    Ok(Value::CallableContract(CallableData {
+                        contract_identifier: contract.clone(),
+                        trait_identifier: None,
+                    }))
+                }
+                _ => Err(mismatch(&value)),
+            },
+            (SequenceType(BufferType(len)), Value::Sequence(SequenceData::Buffer(buff)))
+                if buff.data.len() as u32 <= len.get() =>
+            {
+                Ok(Value::Sequence(SequenceData::Buffer(buff)))
+            }
+            (
+                SequenceType(StringType(ASCII(len))),
+                Value::Sequence(SequenceData::String(CharType::ASCII(ascii))),
+            ) if ascii.data.len() as u32 <= len.get() => Ok(Value::Sequence(SequenceData::String(
+                CharType::ASCII(ascii),
+            ))),
+            (
+                SequenceType(StringType(UTF8(len))),
+                Value::Sequence(SequenceData::String(CharType::UTF8(utf8))),
+            ) if utf8.data.len() as u32 <= len.get() => {
+                Ok(Value::Sequence(SequenceData::String(CharType::UTF8(utf8))))
+            }
+            (SequenceType(ListType(list_type)), Value::Sequence(SequenceData::List(list)))
+                if list.data.len() as u32 <= list_type.get_max_len() =>
+            {
+                let data = list
+                    .data
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        item.conform_to_type(list_type.get_list_item_type())
+                            .map_err(|e| with_context(e, &format!("list item {i}")))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Value::Sequence(SequenceData::List(ListData {
+                    data,
+                    type_signature: list_type.clone(),
+                })))
+            }
+            (TupleType(tuple_type), Value::Tuple(tuple)) => {
+                if let Some(name) = tuple
+                    .data_map
+                    .keys()
+                    .find(|name| tuple_type.field_type(name).is_none())
+                {
+                    return Err(Error::ClarityTypeError(format!(
+                        "expected {expected}, got unexpected tuple field '{name}'"
+                    )));
+                }
+                let mut data_map = BTreeMap::new();
+                let mut fields = tuple_type.get_type_map().iter().collect::<Vec<_>>();
+                fields.sort_by(|a, b| a.0.cmp(b.0));
+                let mut tuple = tuple;
+                for (name, field_type) in fields {
+                    let value = tuple.data_map.remove(name).ok_or_else(|| {
+                        Error::ClarityTypeError(format!(
+                            "expected {expected}, but tuple field '{name}' is missing"
+                        ))
+                    })?;
+                    let value = value
+                        .conform_to_type(field_type)
+                        .map_err(|e| with_context(e, &format!("tuple field '{name}'")))?;
+                    data_map.insert(name.clone(), value);
+                }
+                Ok(Value::Tuple(TupleData {
+                    type_signature: tuple_type.clone(),
+                    data_map,
+                }))
+            }
+            (OptionalType(_), Value::Optional(OptionalData { data: None })) => Ok(Value::none()),
+            (OptionalType(inner), Value::Optional(OptionalData { data: Some(value) })) => {
+                Ok(Value::some(value.conform_to_type(inner)?))
+            }
+            (ResponseType(inner), Value::Response(ResponseData { committed, data })) => {
+                if committed {
+                    Ok(Value::okay(data.conform_to_type(&inner.0)?))
+                } else {
+                    Ok(Value::error(data.conform_to_type(&inner.1)?))
+                }
+            }
+            (_, value) => Err(mismatch(&value)),
+        }
+    }
+}
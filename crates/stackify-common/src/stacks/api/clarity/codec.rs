@@ -0,0 +1,362 @@
+//! Consensus serialization of Clarity values. Every value is prefixed by a
+//! single type byte, followed by its type-specific encoding.
+
+use std::io::{Read, Write};
+
+use crate::stacks::api::{
+    codec::{
+        read_bytes, read_clarity_name, read_contract_name, read_next, write_clarity_name,
+        write_contract_name, write_next, StacksMessageCodec,
+    },
+    errors::Error,
+};
+
+use super::{
+    CallableData, CharType, ClarityVersion, OptionalData, PrincipalData,
+    QualifiedContractIdentifier, ResponseData, SequenceData, StandardPrincipalData, TupleData,
+    TypeSignature, Value, MAX_TYPE_DEPTH, MAX_VALUE_SIZE,
+};
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TypePrefix {
+    Int = 0,
+    UInt = 1,
+    Buffer = 2,
+    BoolTrue = 3,
+    BoolFalse = 4,
+    PrincipalStandard = 5,
+    PrincipalContract = 6,
+    ResponseOk = 7,
+    ResponseErr = 8,
+    OptionalNone = 9,
+    OptionalSome = 10,
+    List = 11,
+    Tuple = 12,
+    StringASCII = 13,
+    StringUTF8 = 14,
+}
+
+impl TryFrom<u8> for TypePrefix {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<TypePrefix, Self::Error> {
+        use TypePrefix::*;
+        let prefix = match value {
+            0 => Int,
+            1 => UInt,
+            2 => Buffer,
+            3 => BoolTrue,
+            4 => BoolFalse,
+            5 => PrincipalStandard,
+            6 => PrincipalContract,
+            7 => ResponseOk,
+            8 => ResponseErr,
+            9 => OptionalNone,
+            10 => OptionalSome,
+            11 => List,
+            12 => Tuple,
+            13 => StringASCII,
+            14 => StringUTF8,
+            _ => {
+                return Err(Error::DeserializeError(format!(
+                    "Bad Clarity type prefix: {value:#04x}"
+                )))
+            }
+        };
+        Ok(prefix)
+    }
+}
+
+impl StacksMessageCodec for StandardPrincipalData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.0)?;
+        write_next(fd, &self.1)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let version: u8 = read_next(fd)?;
+        if version >= 32 {
+            return Err(Error::InvalidVersion(version));
+        }
+        Ok(StandardPrincipalData(version, read_next(fd)?))
+    }
+}
+
+impl StacksMessageCodec for QualifiedContractIdentifier {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.issuer)?;
+        write_contract_name(fd, &self.name)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let issuer = read_next(fd)?;
+        let name = read_contract_name(fd)?;
+        Ok(QualifiedContractIdentifier { issuer, name })
+    }
+}
+
+/// Principals are serialized exactly as their corresponding Clarity value.
+impl StacksMessageCodec for PrincipalData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        match self {
+            PrincipalData::Standard(p) => {
+                write_next(fd, &(TypePrefix::PrincipalStandard as u8))?;
+                write_next(fd, p)
+            }
+            PrincipalData::Contract(c) => {
+                write_next(fd, &(TypePrefix::PrincipalContract as u8))?;
+                write_next(fd, c)
+            }
+        }
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let prefix = TypePrefix::try_from(read_next::<u8, _>(fd)?)?;
+        match prefix {
+            TypePrefix::PrincipalStandard => Ok(PrincipalData::Standard(read_next(fd)?)),
+            TypePrefix::PrincipalContract => Ok(PrincipalData::Contract(read_next(fd)?)),
+            _ => Err(Error::DeserializeError(format!(
+                "Expected a principal, got type prefix {prefix:?}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for ClarityVersion {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        let version: u8 = match self {
+            ClarityVersion::Clarity1 => 1,
+            ClarityVersion::Clarity2 => 2,
+            ClarityVersion::Clarity3 => 3,
+        };
+        write_next(fd, &version)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        match read_next::<u8, _>(fd)? {
+            1 => Ok(ClarityVersion::Clarity1),
+            2 => Ok(ClarityVersion::Clarity2),
+            3 => Ok(ClarityVersion::Clarity3),
+            v => Err(Error::DeserializeError(format!(
+                "Unrecognized Clarity version: {v}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for Value {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        match self {
+            Value::Int(value) => {
+                write_next(fd, &(TypePrefix::Int as u8))?;
+                fd.write_all(&value.to_be_bytes())?;
+            }
+            Value::UInt(value) => {
+                write_next(fd, &(TypePrefix::UInt as u8))?;
+                fd.write_all(&value.to_be_bytes())?;
+            }
+            Value::Bool(true) => write_next(fd, &(TypePrefix::BoolTrue as u8))?,
+            Value::Bool(false) => write_next(fd, &(TypePrefix::BoolFalse as u8))?,
+            Value::Principal(principal) => write_next(fd, principal)?,
+            // Callable contracts are serialized as their contract principal.
+            Value::CallableContract(CallableData {
+                contract_identifier,
+                ..
+            }) => {
+                write_next(fd, &(TypePrefix::PrincipalContract as u8))?;
+                write_next(fd, contract_identifier)?;
+            }
+            Value::Response(ResponseData { committed, data }) => {
+                let prefix = if *committed {
+                    TypePrefix::ResponseOk
+                } else {
+                    TypePrefix::ResponseErr
+                };
+                write_next(fd, &(prefix as u8))?;
+                write_next(fd, data.as_ref())?;
+            }
+            Value::Optional(OptionalData { data: None }) => {
+                write_next(fd, &(TypePrefix::OptionalNone as u8))?;
+            }
+            Value::Optional(OptionalData { data: Some(value) }) => {
+                write_next(fd, &(TypePrefix::OptionalSome as u8))?;
+                write_next(fd, value.as_ref())?;
+            }
+            Value::Sequence(SequenceData::Buffer(buff)) => {
+                write_next(fd, &(TypePrefix::Buffer as u8))?;
+                write_length_prefixed(fd, &buff.data)?;
+            }
+            Value::Sequence(SequenceData::String(CharType::ASCII(ascii))) => {
+                write_next(fd, &(TypePrefix::StringASCII as u8))?;
+                write_length_prefixed(fd, &ascii.data)?;
+            }
+            Value::Sequence(SequenceData::String(CharType::UTF8(utf8))) => {
+                write_next(fd, &(TypePrefix::StringUTF8 as u8))?;
+                write_length_prefixed(fd, &utf8.data.concat())?;
+            }
+            Value::Sequence(SequenceData::List(list)) => {
+                write_next(fd, &(TypePrefix::List as u8))?;
+                write_next(fd, &(list.data.len() as u32))?;
+                for item in list.data.iter() {
+                    write_next(fd, item)?;
+                }
+            }
+            Value::Tuple(TupleData { data_map, .. }) => {
+                write_next(fd, &(TypePrefix::Tuple as u8))?;
+                write_next(fd, &(data_map.len() as u32))?;
+                // `data_map` is a BTreeMap, so keys are written in sorted order.
+                for (name, value) in data_map.iter() {
+                    write_clarity_name(fd, name)?;
+                    write_next(fd, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        deserialize_value(fd, 0)
+    }
+}
+
+/// The hex encoding of consensus-serialized values, as used by the node's RPC
+/// interface (read-only call arguments and results, `/v2/data_var`,
+/// `/v2/map_entry`, ...). A leading `0x` is accepted when decoding.
+impl Value {
+    pub fn serialize_to_hex(&self) -> Result<String, Error> {
+        Ok(hex::encode(self.serialize_to_vec()?))
+    }
+
+    /// Decodes a value without any knowledge of its type. Contract principals
+    /// are always decoded as `Value::Principal`.
+    pub fn try_deserialize_hex_untyped(hex_str: &str) -> Result<Value, Error> {
+        Value::deserialize_from_slice(&decode_hex(hex_str)?)
+    }
+
+    /// Decodes a value and checks it against `expected`, see
+    /// [`Value::conform_to_type`].
+    pub fn try_deserialize_hex(hex_str: &str, expected: &TypeSignature) -> Result<Value, Error> {
+        Value::try_deserialize_bytes(&decode_hex(hex_str)?, expected)
+    }
+
+    pub fn try_deserialize_bytes(bytes: &[u8], expected: &TypeSignature) -> Result<Value, Error> {
+        Value::deserialize_from_slice(bytes)?.conform_to_type(expected)
+    }
+}
+
+fn decode_hex(hex_str: &str) -> Result<Vec<u8>, Error> {
+    hex::decode(hex_str.strip_prefix("0x").unwrap_or(hex_str))
+        .map_err(|e| Error::DeserializeError(format!("Invalid hex: {e}")))
+}
+
+/// Serde (de)serialization of a [`Value`] as a `0x`-prefixed hex string, for
+/// use with `#[serde(with = "value_hex")]` on RPC request/response types.
+pub mod value_hex {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::Value;
+
+    pub fn serialize<S>(value: &Value, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let hex_str = value
+            .serialize_to_hex()
+            .map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&format!("0x{hex_str}"))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Value::try_deserialize_hex_untyped(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+fn write_length_prefixed<W: Write>(fd: &mut W, data: &[u8]) -> Result<(), Error> {
+    if data.len() > MAX_VALUE_SIZE as usize {
+        return Err(Error::SerializeError(format!(
+            "Value of {} bytes exceeds the maximum value size",
+            data.len()
+        )));
+    }
+    write_next(fd, &(data.len() as u32))?;
+    fd.write_all(data)?;
+    Ok(())
+}
+
+fn read_length_prefixed<R: Read>(fd: &mut R) -> Result<Vec<u8>, Error> {
+    let len: u32 = read_next(fd)?;
+    if len > MAX_VALUE_SIZE {
+        return Err(Error::DeserializeError(format!(
+            "Value of {len} bytes exceeds the maximum value size"
+        )));
+    }
+    read_bytes(fd, len)
+}
+
+fn deserialize_value<R: Read>(fd: &mut R, depth: u8) -> Result<Value, Error> {
+    if depth >= MAX_TYPE_DEPTH {
+        return Err(Error::DeserializeError(
+            "Clarity value exceeds the maximum type depth".into(),
+        ));
+    }
+
+    let prefix = TypePrefix::try_from(read_next::<u8, _>(fd)?)?;
+    let value = match prefix {
+        TypePrefix::Int => {
+            let mut buf = [0u8; 16];
+            fd.read_exact(&mut buf)?;
+            Value::Int(i128::from_be_bytes(buf))
+        }
+        TypePrefix::UInt => {
+            let mut buf = [0u8; 16];
+            fd.read_exact(&mut buf)?;
+            Value::UInt(u128::from_be_bytes(buf))
+        }
+        TypePrefix::BoolTrue => Value::Bool(true),
+        TypePrefix::BoolFalse => Value::Bool(false),
+        TypePrefix::PrincipalStandard => Value::Principal(PrincipalData::Standard(read_next(fd)?)),
+        TypePrefix::PrincipalContract => Value::Principal(PrincipalData::Contract(read_next(fd)?)),
+        TypePrefix::ResponseOk => Value::okay(deserialize_value(fd, depth + 1)?),
+        TypePrefix::ResponseErr => Value::error(deserialize_value(fd, depth + 1)?),
+        TypePrefix::OptionalNone => Value::none(),
+        TypePrefix::OptionalSome => Value::some(deserialize_value(fd, depth + 1)?),
+        TypePrefix::Buffer => Value::buff_from(read_length_prefixed(fd)?)?,
+        TypePrefix::StringASCII => Value::string_ascii_from_bytes(read_length_prefixed(fd)?)?,
+        TypePrefix::StringUTF8 => Value::string_utf8_from_bytes(read_length_prefixed(fd)?)?,
+        TypePrefix::List => {
+            let len: u32 = read_next(fd)?;
+            if len > MAX_VALUE_SIZE {
+                return Err(Error::DeserializeError(format!(
+                    "List of {len} items exceeds the maximum value size"
+                )));
+            }
+            let mut items = Vec::with_capacity(len.min(1024) as usize);
+            for _ in 0..len {
+                items.push(deserialize_value(fd, depth + 1)?);
+            }
+            Value::list_from(items)?
+        }
+        TypePrefix::Tuple => {
+            let len: u32 = read_next(fd)?;
+            if len > MAX_VALUE_SIZE {
+                return Err(Error::DeserializeError(format!(
+                    "Tuple of {len} items exceeds the maximum value size"
+                )));
+            }
+            let mut items = Vec::with_capacity(len.min(1024) as usize);
+            for _ in 0..len {
+                let name = read_clarity_name(fd)?;
+                let value = deserialize_value(fd, depth + 1)?;
+                items.push((name, value));
+            }
+            Value::tuple_from_data(items)?
+        }
+    };
+
+    Ok(value)
+}
@@ -0,0 +1,155 @@
+//! Human-readable Clarity literals, e.g. `(ok u1)` or `(tuple (a 1) (b "x"))`.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use super::{
+    ASCIIData, CallableData, CallableSubtype, CharType, ListData, OptionalData, ResponseData,
+    SequenceData, SequenceSubtype, StringSubtype, TraitIdentifier, TupleData, TypeSignature,
+    UTF8Data, Value,
+};
+
+/// Writes a single ASCII byte, escaping the characters which the Clarity
+/// lexer treats specially.
+fn write_escaped_ascii(f: &mut Formatter<'_>, c: u8) -> fmt::Result {
+    match c {
+        b'"' => f.write_str("\\\""),
+        b'\\' => f.write_str("\\\\"),
+        b'\n' => f.write_str("\\n"),
+        b'\t' => f.write_str("\\t"),
+        b'\r' => f.write_str("\\r"),
+        _ => f.write_char(c as char),
+    }
+}
+
+impl Display for ASCIIData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        for c in self.data.iter() {
+            write_escaped_ascii(f, *c)?;
+        }
+        f.write_char('"')
+    }
+}
+
+impl Display for UTF8Data {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("u\"")?;
+        for c in self.data.iter() {
+            match std::str::from_utf8(c).ok().and_then(|s| s.chars().next()) {
+                Some(c) if c.is_ascii() => write_escaped_ascii(f, c as u8)?,
+                Some(c) => write!(f, "\\u{{{:x}}}", c as u32)?,
+                None => return Err(fmt::Error),
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+impl Display for ListData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("(list")?;
+        for item in self.data.iter() {
+            write!(f, " {item}")?;
+        }
+        f.write_char(')')
+    }
+}
+
+impl Display for TupleData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("(tuple")?;
+        for (name, value) in self.data_map.iter() {
+            write!(f, " ({name} {value})")?;
+        }
+        f.write_char(')')
+    }
+}
+
+impl Display for TraitIdentifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.contract_identifier, self.name)
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{value}"),
+            Value::UInt(value) => write!(f, "u{value}"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Principal(principal) => write!(f, "'{principal}"),
+            Value::CallableContract(CallableData {
+                contract_identifier,
+                ..
+            }) => write!(f, "'{contract_identifier}"),
+            Value::Sequence(SequenceData::Buffer(buff)) => {
+                write!(f, "0x{}", hex::encode(&buff.data))
+            }
+            Value::Sequence(SequenceData::String(CharType::ASCII(ascii))) => write!(f, "{ascii}"),
+            Value::Sequence(SequenceData::String(CharType::UTF8(utf8))) => write!(f, "{utf8}"),
+            Value::Sequence(SequenceData::List(list)) => write!(f, "{list}"),
+            Value::Tuple(tuple) => write!(f, "{tuple}"),
+            Value::Optional(OptionalData { data: None }) => f.write_str("none"),
+            Value::Optional(OptionalData { data: Some(value) }) => write!(f, "(some {value})"),
+            Value::Response(ResponseData { committed, data }) => {
+                if *committed {
+                    write!(f, "(ok {data})")
+                } else {
+                    write!(f, "(err {data})")
+                }
+            }
+        }
+    }
+}
+
+impl Display for TypeSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use SequenceSubtype::*;
+        use StringSubtype::*;
+        use TypeSignature::*;
+
+        match self {
+            NoType => f.write_str("UnknownType"),
+            IntType => f.write_str("int"),
+            UIntType => f.write_str("uint"),
+            BoolType => f.write_str("bool"),
+            PrincipalType => f.write_str("principal"),
+            SequenceType(BufferType(len)) => write!(f, "(buff {})", len.get()),
+            SequenceType(StringType(ASCII(len))) => write!(f, "(string-ascii {})", len.get()),
+            SequenceType(StringType(UTF8(len))) => write!(f, "(string-utf8 {})", len.get()),
+            SequenceType(ListType(list)) => write!(
+                f,
+                "(list {} {})",
+                list.get_max_len(),
+                list.get_list_item_type()
+            ),
+            TupleType(tuple) => {
+                // Sort the fields so that the output is stable.
+                let mut fields = tuple.get_type_map().iter().collect::<Vec<_>>();
+                fields.sort_by(|a, b| a.0.cmp(b.0));
+                f.write_str("(tuple")?;
+                for (name, field_type) in fields {
+                    write!(f, " ({name} {field_type})")?;
+                }
+                f.write_char(')')
+            }
+            OptionalType(inner) => write!(f, "(optional {inner})"),
+            ResponseType(inner) => write!(f, "(response {} {})", inner.0, inner.1),
+            CallableType(CallableSubtype::Principal(contract)) => write!(f, "'{contract}"),
+            CallableType(CallableSubtype::Trait(trait_id)) | TraitReferenceType(trait_id) => {
+                write!(f, "<{trait_id}>")
+            }
+            ListUnionType(types) => {
+                let mut types = types
+                    .iter()
+                    .map(|t| match t {
+                        CallableSubtype::Principal(contract) => format!("'{contract}"),
+                        CallableSubtype::Trait(trait_id) => format!("<{trait_id}>"),
+                    })
+                    .collect::<Vec<_>>();
+                types.sort();
+                write!(f, "UnionType({})", types.join(", "))
+            }
+        }
+    }
+}
@@ -0,0 +1,490 @@
+//! Parsing of Clarity literals, e.g. `u100`, `'ST1...`, `(tuple (a 1) (b "x"))`,
+//! `(list u1 u2)`, `0xdeadbeef`, `(some none)` or `(ok true)`, into [`Value`]s.
+//!
+//! Parsing happens in two steps: the input is first parsed into an expression
+//! tree which remembers where each expression started, and that tree is then
+//! converted into a `Value`. When the expected type is known, it is pushed down
+//! the tree during conversion so that type errors point at the offending
+//! sub-expression rather than at the whole input.
+
+use std::str::FromStr;
+
+use crate::stacks::api::errors::Error;
+
+use super::{
+    is_valid_clarity_name, PrincipalData, SequenceSubtype, TypeSignature, Value, MAX_TYPE_DEPTH,
+};
+
+/// Parses a Clarity literal into a [`Value`].
+pub fn parse_value(input: &str) -> Result<Value, Error> {
+    let expr = Parser::new(input).parse()?;
+    to_value(input, &expr)
+}
+
+/// Parses a Clarity literal into a [`Value`], checking it against `expected`.
+/// Contract principals are converted into callable contracts where `expected`
+/// calls for a trait reference.
+pub fn parse_value_typed(input: &str, expected: &TypeSignature) -> Result<Value, Error> {
+    let expr = Parser::new(input).parse()?;
+    to_typed_value(input, &expr, expected)
+}
+
+impl FromStr for Value {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_value(s)
+    }
+}
+
+#[derive(Debug)]
+struct Expr {
+    /// Byte offset of the expression in the input.
+    pos: usize,
+    kind: ExprKind,
+}
+
+#[derive(Debug)]
+enum ExprKind {
+    Int(i128),
+    UInt(u128),
+    Bool(bool),
+    Buffer(Vec<u8>),
+    Ascii(Vec<u8>),
+    Utf8(String),
+    Principal(PrincipalData),
+    None,
+    Some(Box<Expr>),
+    Ok(Box<Expr>),
+    Err(Box<Expr>),
+    List(Vec<Expr>),
+    /// Tuple fields, along with the position of each field's name.
+    Tuple(Vec<(String, usize, Expr)>),
+}
+
+/// Builds a parse error, reporting the (1-based) column of `pos` in `input`.
+fn parse_error(input: &str, pos: usize, message: impl Into<String>) -> Error {
+    let column = input[..pos.min(input.len())].chars().count() + 1;
+    Error::ClarityParseError(column, message.into())
+}
+
+/// Whether `c` may appear in a Clarity name (function names, tuple keys, ...).
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-_!?+<>=/*".contains(c)
+}
+
+/// Whether `c` ends an atom such as `u1` or `'ST...`.
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || "(){},\"".contains(c)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Parser { input, pos: 0 }
+    }
+
+    fn parse(mut self) -> Result<Expr, Error> {
+        self.skip_whitespace();
+        if self.peek().is_none() {
+            return Err(self.error(self.pos, "expected a value, got an empty string"));
+        }
+        let expr = self.parse_expr(0)?;
+        self.skip_whitespace();
+        if self.peek().is_some() {
+            return Err(self.error(self.pos, "unexpected input after value"));
+        }
+        Ok(expr)
+    }
+
+    fn error(&self, pos: usize, message: impl Into<String>) -> Error {
+        parse_error(self.input, pos, message)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.bump();
+                Ok(())
+            }
+            Some(c) => Err(self.error(self.pos, format!("expected '{expected}', got '{c}'"))),
+            None => Err(self.error(self.pos, format!("expected '{expected}', got end of input"))),
+        }
+    }
+
+    /// Consumes characters while `pred` holds, returning them.
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&pred) {
+            self.bump();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn parse_name(&mut self) -> Result<(String, usize), Error> {
+        self.skip_whitespace();
+        let pos = self.pos;
+        let name = self.take_while(is_name_char);
+        if !is_valid_clarity_name(name) {
+            return Err(self.error(pos, format!("expected a name, got '{name}'")));
+        }
+        Ok((name.to_string(), pos))
+    }
+
+    fn parse_expr(&mut self, depth: u8) -> Result<Expr, Error> {
+        self.skip_whitespace();
+        let pos = self.pos;
+        if depth >= MAX_TYPE_DEPTH {
+            return Err(self.error(pos, "value exceeds the maximum nesting depth"));
+        }
+
+        let kind = match self.peek() {
+            Some('(') => {
+                self.bump();
+                self.parse_form(pos, depth)?
+            }
+            Some('{') => {
+                self.bump();
+                self.parse_tuple_shorthand(depth)?
+            }
+            Some('"') => ExprKind::Ascii(self.parse_ascii_string()?),
+            Some('u') if self.rest().starts_with("u\"") => {
+                self.bump();
+                ExprKind::Utf8(self.parse_utf8_string()?)
+            }
+            Some(c) if !is_delimiter(c) => self.parse_atom()?,
+            Some(c) => return Err(self.error(pos, format!("unexpected '{c}'"))),
+            None => return Err(self.error(pos, "expected a value, got end of input")),
+        };
+
+        Ok(Expr { pos, kind })
+    }
+
+    /// Parses the remainder of a parenthesized form, after the opening `(`.
+    fn parse_form(&mut self, pos: usize, depth: u8) -> Result<ExprKind, Error> {
+        let (keyword, keyword_pos) = self.parse_name()?;
+        let kind = match keyword.as_str() {
+            "some" => ExprKind::Some(Box::new(self.parse_expr(depth + 1)?)),
+            "ok" => ExprKind::Ok(Box::new(self.parse_expr(depth + 1)?)),
+            "err" => ExprKind::Err(Box::new(self.parse_expr(depth + 1)?)),
+            "list" => {
+                let mut items = vec![];
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(')') => break,
+                        None => return Err(self.error(pos, "unclosed '('")),
+                        _ => items.push(self.parse_expr(depth + 1)?),
+                    }
+                }
+                ExprKind::List(items)
+            }
+            "tuple" => {
+                let mut fields = vec![];
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(')') => break,
+                        None => return Err(self.error(pos, "unclosed '('")),
+                        _ => {
+                            self.expect('(')?;
+                            let (name, name_pos) = self.parse_name()?;
+                            let value = self.parse_expr(depth + 1)?;
+                            self.expect(')')?;
+                            self.push_field(&mut fields, name, name_pos, value)?;
+                        }
+                    }
+                }
+                if fields.is_empty() {
+                    return Err(self.error(pos, "tuples must have at least one field"));
+                }
+                ExprKind::Tuple(fields)
+            }
+            _ => {
+                return Err(self.error(
+                    keyword_pos,
+                    format!(
+                        "unknown form '{keyword}', expected one of 'some', 'ok', 'err', 'list' or 'tuple'"
+                    ),
+                ))
+            }
+        };
+        self.expect(')')?;
+        Ok(kind)
+    }
+
+    /// Parses the remainder of a `{ a: 1, b: u2 }` tuple, after the opening `{`.
+    fn parse_tuple_shorthand(&mut self, depth: u8) -> Result<ExprKind, Error> {
+        let mut fields = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some('}') {
+                self.bump();
+                break;
+            }
+            let (name, name_pos) = self.parse_name()?;
+            self.expect(':')?;
+            let value = self.parse_expr(depth + 1)?;
+            self.push_field(&mut fields, name, name_pos, value)?;
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some('}') => {}
+                _ => self.expect('}')?,
+            }
+        }
+        if fields.is_empty() {
+            return Err(self.error(self.pos - 1, "tuples must have at least one field"));
+        }
+        Ok(ExprKind::Tuple(fields))
+    }
+
+    fn push_field(
+        &self,
+        fields: &mut Vec<(String, usize, Expr)>,
+        name: String,
+        name_pos: usize,
+        value: Expr,
+    ) -> Result<(), Error> {
+        if fields.iter().any(|(existing, _, _)| *existing == name) {
+            return Err(self.error(name_pos, format!("duplicate tuple field '{name}'")));
+        }
+        fields.push((name, name_pos, value));
+        Ok(())
+    }
+
+    /// Parses an escape sequence common to both string types, after the `\`.
+    fn parse_escape(&mut self, escape_pos: usize) -> Result<char, Error> {
+        match self.bump() {
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('r') => Ok('\r'),
+            Some(c) => Err(self.error(escape_pos, format!("unknown escape sequence '\\{c}'"))),
+            None => Err(self.error(escape_pos, "unterminated string")),
+        }
+    }
+
+    fn parse_ascii_string(&mut self) -> Result<Vec<u8>, Error> {
+        let start = self.pos;
+        self.bump();
+        let mut data = vec![];
+        loop {
+            let pos = self.pos;
+            match self.bump() {
+                Some('"') => return Ok(data),
+                Some('\\') => data.push(self.parse_escape(pos)? as u8),
+                Some(c) if c.is_ascii() && !c.is_ascii_control() || c == '\n' || c == '\t' => {
+                    data.push(c as u8)
+                }
+                Some(c) => {
+                    return Err(self.error(
+                        pos,
+                        format!("'{c}' is not allowed in a string-ascii, use u\"...\" instead"),
+                    ))
+                }
+                None => return Err(self.error(start, "unterminated string")),
+            }
+        }
+    }
+
+    fn parse_utf8_string(&mut self) -> Result<String, Error> {
+        let start = self.pos;
+        self.bump();
+        let mut data = String::new();
+        loop {
+            let pos = self.pos;
+            match self.bump() {
+                Some('"') => return Ok(data),
+                Some('\\') if self.rest().starts_with("u{") => {
+                    self.pos += 2;
+                    let digits = self.take_while(|c| c.is_ascii_hexdigit());
+                    let c = u32::from_str_radix(digits, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| self.error(pos, "invalid unicode escape"))?;
+                    self.expect('}')?;
+                    data.push(c);
+                }
+                Some('\\') => data.push(self.parse_escape(pos)?),
+                Some(c) => data.push(c),
+                None => return Err(self.error(start, "unterminated string")),
+            }
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<ExprKind, Error> {
+        let pos = self.pos;
+        let token = self.take_while(|c| !is_delimiter(c));
+
+        let kind = match token {
+            "true" => ExprKind::Bool(true),
+            "false" => ExprKind::Bool(false),
+            "none" => ExprKind::None,
+            _ if token.starts_with("0x") => ExprKind::Buffer(
+                hex::decode(&token[2..])
+                    .map_err(|e| self.error(pos, format!("invalid buffer literal: {e}")))?,
+            ),
+            _ if token.starts_with('u') && token[1..].starts_with(|c: char| c.is_ascii_digit()) => {
+                ExprKind::UInt(
+                    token[1..]
+                        .parse()
+                        .map_err(|e| self.error(pos, format!("invalid uint literal: {e}")))?,
+                )
+            }
+            _ if token.starts_with(|c: char| c.is_ascii_digit() || c == '-') => ExprKind::Int(
+                token
+                    .parse()
+                    .map_err(|e| self.error(pos, format!("invalid int literal: {e}")))?,
+            ),
+            _ if token.starts_with("'.") || token.starts_with('.') => {
+                return Err(self.error(
+                    pos,
+                    "contract principals must be fully qualified, e.g. 'ST...address.contract-name",
+                ))
+            }
+            _ if token.starts_with('\'') => ExprKind::Principal(
+                token[1..]
+                    .parse()
+                    .map_err(|e| self.error(pos, format!("invalid principal: {e}")))?,
+            ),
+            _ => return Err(self.error(pos, format!("unexpected '{token}'"))),
+        };
+        Ok(kind)
+    }
+}
+
+/// Converts an expression into a value, without any knowledge of its type.
+fn to_value(input: &str, expr: &Expr) -> Result<Value, Error> {
+    let value = match &expr.kind {
+        ExprKind::Int(i) => Ok(Value::Int(*i)),
+        ExprKind::UInt(u) => Ok(Value::UInt(*u)),
+        ExprKind::Bool(b) => Ok(Value::Bool(*b)),
+        ExprKind::Buffer(data) => Value::buff_from(data.clone()),
+        ExprKind::Ascii(data) => Value::string_ascii_from_bytes(data.clone()),
+        ExprKind::Utf8(data) => Value::string_utf8_from_bytes(data.clone().into_bytes()),
+        ExprKind::Principal(principal) => Ok(Value::Principal(principal.clone())),
+        ExprKind::None => Ok(Value::none()),
+        ExprKind::Some(inner) => Ok(Value::some(to_value(input, inner)?)),
+        ExprKind::Ok(inner) => Ok(Value::okay(to_value(input, inner)?)),
+        ExprKind::Err(inner) => Ok(Value::error(to_value(input, inner)?)),
+        ExprKind::List(items) => Value::list_from(
+            items
+                .iter()
+                .map(|item| to_value(input, item))
+                .collect::<Result<_, _>>()?,
+        ),
+        ExprKind::Tuple(fields) => Value::tuple_from_data(
+            fields
+                .iter()
+                .map(|(name, _, value)| Ok((name.clone(), to_value(input, value)?)))
+                .collect::<Result<_, Error>>()?,
+        ),
+    };
+    value.map_err(|e| located(input, expr.pos, e))
+}
+
+/// Converts an expression into a value of the `expected` type.
+fn to_typed_value(input: &str, expr: &Expr, expected: &TypeSignature) -> Result<Value, Error> {
+    match (&expr.kind, expected) {
+        (ExprKind::Some(inner), TypeSignature::OptionalType(inner_type)) => {
+            Ok(Value::some(to_typed_value(input, inner, inner_type)?))
+        }
+        (ExprKind::Ok(inner), TypeSignature::ResponseType(types)) => {
+            Ok(Value::okay(to_typed_value(input, inner, &types.0)?))
+        }
+        (ExprKind::Err(inner), TypeSignature::ResponseType(types)) => {
+            Ok(Value::error(to_typed_value(input, inner, &types.1)?))
+        }
+        (
+            ExprKind::List(items),
+            TypeSignature::SequenceType(SequenceSubtype::ListType(list_type)),
+        ) => {
+            if items.len() as u32 > list_type.get_max_len() {
+                return Err(parse_error(
+                    input,
+                    expr.pos,
+                    format!("expected {expected}, got a list of {} items", items.len()),
+                ));
+            }
+            let items = items
+                .iter()
+                .map(|item| to_typed_value(input, item, list_type.get_list_item_type()))
+                .collect::<Result<Vec<_>, _>>()?;
+            // Re-check the assembled list so that it carries the expected type.
+            Value::list_from(items)
+                .and_then(|list| list.conform_to_type(expected))
+                .map_err(|e| located(input, expr.pos, e))
+        }
+        (ExprKind::Tuple(fields), TypeSignature::TupleType(tuple_type)) => {
+            let mut data = Vec::with_capacity(fields.len());
+            for (name, name_pos, value) in fields {
+                let field_type = tuple_type.field_type(name).ok_or_else(|| {
+                    parse_error(
+                        input,
+                        *name_pos,
+                        format!("unexpected tuple field '{name}', expected {expected}"),
+                    )
+                })?;
+                data.push((name.clone(), to_typed_value(input, value, field_type)?));
+            }
+            let mut missing = tuple_type
+                .get_type_map()
+                .keys()
+                .filter(|name| !fields.iter().any(|(field, _, _)| field == *name))
+                .collect::<Vec<_>>();
+            missing.sort();
+            if let Some(name) = missing.first() {
+                return Err(parse_error(
+                    input,
+                    expr.pos,
+                    format!("missing tuple field '{name}', expected {expected}"),
+                ));
+            }
+            Value::tuple_from_data(data)
+                .and_then(|tuple| tuple.conform_to_type(expected))
+                .map_err(|e| located(input, expr.pos, e))
+        }
+        _ => to_value(input, expr)?
+            .conform_to_type(expected)
+            .map_err(|e| located(input, expr.pos, e)),
+    }
+}
+
+/// Attaches a position to a value-construction or type error.
+fn located(input: &str, pos: usize, e: Error) -> Error {
+    match e {
+        Error::ClarityTypeError(msg) => parse_error(input, pos, msg),
+        Error::ClarityParseError(..) => e,
+        e => parse_error(input, pos, e.to_string()),
+    }
+}
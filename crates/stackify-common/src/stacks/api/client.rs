@@ -0,0 +1,73 @@
+//! An async client for a Stacks node's RPC interface. The endpoints
+//! themselves are implemented per API version, see the `v2` and `v3`
+//! modules.
+
+use reqwest::{Client, RequestBuilder, Response, Url};
+use serde::de::DeserializeOwned;
+
+use super::errors::Error;
+
+#[derive(Debug, Clone)]
+pub struct StacksNodeClient {
+    base_url: Url,
+    http: Client,
+}
+
+impl StacksNodeClient {
+    /// Creates a client for the node at `base_url`, e.g. `http://localhost:20443`.
+    pub fn new(base_url: &str) -> Result<Self, Error> {
+        let base_url = Url::parse(base_url)
+            .map_err(|e| Error::Other(format!("Invalid node URL '{base_url}': {e}")))?;
+        Ok(Self::from_url(base_url))
+    }
+
+    pub fn from_url(base_url: Url) -> Self {
+        StacksNodeClient {
+            base_url,
+            http: Client::new(),
+        }
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    pub(crate) fn url(&self, path: &str) -> Result<Url, Error> {
+        self.base_url
+            .join(path)
+            .map_err(|e| Error::Other(format!("Invalid request path '{path}': {e}")))
+    }
+
+    pub(crate) fn get(&self, path: &str) -> Result<RequestBuilder, Error> {
+        Ok(self.http.get(self.url(path)?))
+    }
+
+    pub(crate) fn post(&self, path: &str) -> Result<RequestBuilder, Error> {
+        Ok(self.http.post(self.url(path)?))
+    }
+
+    /// Sends the request, failing on non-success status codes.
+    async fn send(&self, request: RequestBuilder) -> Result<Response, Error> {
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::HttpStatus(status.as_u16(), body));
+        }
+        Ok(response)
+    }
+
+    /// Sends the request and decodes the JSON response body.
+    pub(crate) async fn send_json<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, Error> {
+        Ok(self.send(request).await?.json().await?)
+    }
+
+    /// Sends the request and returns the raw response body, e.g. for
+    /// consensus-serialized blocks.
+    pub(crate) async fn send_bytes(&self, request: RequestBuilder) -> Result<Vec<u8>, Error> {
+        Ok(self.send(request).await?.bytes().await?.to_vec())
+    }
+}
@@ -0,0 +1,280 @@
+//! Consensus (wire) serialization for Stacks types.
+//!
+//! This mirrors the `StacksMessageCodec` trait from
+//! https://github.com/stacks-network/stacks-core so that the objects built
+//! here are byte-for-byte compatible with what a Stacks node expects.
+
+use std::io::{Read, Write};
+
+use crate::stacks::{
+    BlockHeaderHash, ConsensusHash, MessageSignature, Sha512Trunc256Sum, StacksAddress,
+    StacksBlockId, TrieHash, Txid, VRFProof,
+};
+
+use super::{
+    clarity::{is_valid_clarity_name, is_valid_contract_name},
+    errors::Error,
+    hash::Hash160,
+};
+
+/// Maximum size of a single consensus-serialized message, as defined by stacks-core.
+pub const MAX_MESSAGE_LEN: u32 = 1 + 16 * 1024 * 1024;
+
+/// Objects which can be written to and read from the Stacks consensus wire format.
+pub trait StacksMessageCodec {
+    /// Serialize this object to the given writer.
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error>;
+
+    /// Deserialize an object of this type from the given reader.
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error>
+    where
+        Self: Sized;
+
+    /// Serialize this object into a new byte vector.
+    fn serialize_to_vec(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        self.consensus_serialize(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Deserialize an object from a byte slice, requiring that the entire
+    /// slice is consumed.
+    fn deserialize_from_slice(bytes: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let mut cursor = bytes;
+        let ret = Self::consensus_deserialize(&mut cursor)?;
+        if !cursor.is_empty() {
+            return Err(Error::DeserializeError(format!(
+                "{} trailing byte(s) after deserialized object",
+                cursor.len()
+            )));
+        }
+        Ok(ret)
+    }
+}
+
+/// Write the next item to the given writer.
+pub fn write_next<T: StacksMessageCodec, W: Write>(fd: &mut W, item: &T) -> Result<(), Error> {
+    item.consensus_serialize(fd)
+}
+
+/// Read the next item from the given reader.
+pub fn read_next<T: StacksMessageCodec, R: Read>(fd: &mut R) -> Result<T, Error> {
+    T::consensus_deserialize(fd)
+}
+
+/// Read a length-prefixed vector, failing if it claims to hold more than
+/// `max_items` items.
+pub fn read_next_at_most<T: StacksMessageCodec, R: Read>(
+    fd: &mut R,
+    max_items: u32,
+) -> Result<Vec<T>, Error> {
+    let len: u32 = read_next(fd)?;
+    if len > max_items {
+        return Err(Error::DeserializeError(format!(
+            "Vector length {len} exceeds the maximum of {max_items} items"
+        )));
+    }
+
+    // Don't trust the length prefix when allocating.
+    let mut ret = Vec::with_capacity(len.min(1024) as usize);
+    for _ in 0..len {
+        ret.push(read_next(fd)?);
+    }
+    Ok(ret)
+}
+
+/// Read exactly `len` bytes from the given reader.
+pub(crate) fn read_bytes<R: Read>(fd: &mut R, len: u32) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![];
+    let read = fd.take(len as u64).read_to_end(&mut buf)?;
+    if read != len as usize {
+        return Err(Error::DeserializeError(format!(
+            "Expected {len} bytes but only {read} were available"
+        )));
+    }
+    Ok(buf)
+}
+
+/// Write a Clarity name (function names, asset names, tuple keys, ...) as a
+/// single-byte length prefix followed by its bytes.
+pub(crate) fn write_clarity_name<W: Write>(fd: &mut W, name: &str) -> Result<(), Error> {
+    if !is_valid_clarity_name(name) {
+        return Err(Error::SerializeError(format!(
+            "Invalid Clarity name: '{name}'"
+        )));
+    }
+    write_next(fd, &(name.len() as u8))?;
+    fd.write_all(name.as_bytes())?;
+    Ok(())
+}
+
+/// Read a single-byte length-prefixed Clarity name.
+pub(crate) fn read_clarity_name<R: Read>(fd: &mut R) -> Result<String, Error> {
+    let len: u8 = read_next(fd)?;
+    let name = String::from_utf8(read_bytes(fd, len as u32)?)
+        .map_err(|_| Error::DeserializeError("Clarity name is not valid UTF-8".into()))?;
+    if !is_valid_clarity_name(&name) {
+        return Err(Error::DeserializeError(format!(
+            "Invalid Clarity name: '{name}'"
+        )));
+    }
+    Ok(name)
+}
+
+/// Write a contract name as a single-byte length prefix followed by its bytes.
+pub(crate) fn write_contract_name<W: Write>(fd: &mut W, name: &str) -> Result<(), Error> {
+    if !is_valid_contract_name(name) {
+        return Err(Error::SerializeError(format!(
+            "Invalid contract name: '{name}'"
+        )));
+    }
+    write_next(fd, &(name.len() as u8))?;
+    fd.write_all(name.as_bytes())?;
+    Ok(())
+}
+
+/// Read a single-byte length-prefixed contract name.
+pub(crate) fn read_contract_name<R: Read>(fd: &mut R) -> Result<String, Error> {
+    let len: u8 = read_next(fd)?;
+    let name = String::from_utf8(read_bytes(fd, len as u32)?)
+        .map_err(|_| Error::DeserializeError("Contract name is not valid UTF-8".into()))?;
+    if !is_valid_contract_name(&name) {
+        return Err(Error::DeserializeError(format!(
+            "Invalid contract name: '{name}'"
+        )));
+    }
+    Ok(name)
+}
+
+/// Whether or not the given byte is allowed in a `StacksString` (i.e. contract
+/// source code): printable ASCII plus tabs and newlines.
+fn is_valid_stacks_string_byte(b: u8) -> bool {
+    (0x20..=0x7e).contains(&b) || b == b'\t' || b == b'\n' || b == b'\r'
+}
+
+/// Write a `StacksString` (used for contract source code) as a four-byte
+/// length prefix followed by its bytes.
+pub(crate) fn write_stacks_string<W: Write>(fd: &mut W, s: &str) -> Result<(), Error> {
+    if !s.bytes().all(is_valid_stacks_string_byte) {
+        return Err(Error::SerializeError(
+            "String contains characters which are not printable ASCII".into(),
+        ));
+    }
+    let len =
+        u32::try_from(s.len()).map_err(|_| Error::SerializeError("String is too long".into()))?;
+    write_next(fd, &len)?;
+    fd.write_all(s.as_bytes())?;
+    Ok(())
+}
+
+/// Read a four-byte length-prefixed `StacksString`.
+pub(crate) fn read_stacks_string<R: Read>(fd: &mut R) -> Result<String, Error> {
+    let len: u32 = read_next(fd)?;
+    if len > MAX_MESSAGE_LEN {
+        return Err(Error::DeserializeError(format!(
+            "String length {len} exceeds the maximum message length"
+        )));
+    }
+    let bytes = read_bytes(fd, len)?;
+    if !bytes.iter().copied().all(is_valid_stacks_string_byte) {
+        return Err(Error::DeserializeError(
+            "String contains characters which are not printable ASCII".into(),
+        ));
+    }
+    // All bytes are ASCII, so this can't fail.
+    String::from_utf8(bytes).map_err(|e| Error::DeserializeError(e.to_string()))
+}
+
+macro_rules! impl_stacks_message_codec_for_int {
+    ($typ:ty) => {
+        impl StacksMessageCodec for $typ {
+            fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+                fd.write_all(&self.to_be_bytes())?;
+                Ok(())
+            }
+
+            fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+                let mut buf = [0u8; std::mem::size_of::<$typ>()];
+                fd.read_exact(&mut buf)?;
+                Ok(<$typ>::from_be_bytes(buf))
+            }
+        }
+    };
+}
+
+impl_stacks_message_codec_for_int!(u8);
+impl_stacks_message_codec_for_int!(u16);
+impl_stacks_message_codec_for_int!(u32);
+impl_stacks_message_codec_for_int!(u64);
+
+impl<const N: usize> StacksMessageCodec for [u8; N] {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        fd.write_all(self)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let mut buf = [0u8; N];
+        fd.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+impl<T: StacksMessageCodec> StacksMessageCodec for Vec<T> {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        let len = u32::try_from(self.len())
+            .map_err(|_| Error::SerializeError("Vector has too many items".into()))?;
+        write_next(fd, &len)?;
+        for item in self {
+            write_next(fd, item)?;
+        }
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        read_next_at_most(fd, MAX_MESSAGE_LEN)
+    }
+}
+
+macro_rules! impl_byte_array_message_codec {
+    ($name:ident) => {
+        impl StacksMessageCodec for $name {
+            fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+                write_next(fd, &self.0)
+            }
+
+            fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+                Ok($name(read_next(fd)?))
+            }
+        }
+    };
+}
+
+impl_byte_array_message_codec!(Hash160);
+impl_byte_array_message_codec!(ConsensusHash);
+impl_byte_array_message_codec!(StacksBlockId);
+impl_byte_array_message_codec!(Sha512Trunc256Sum);
+impl_byte_array_message_codec!(BlockHeaderHash);
+impl_byte_array_message_codec!(TrieHash);
+impl_byte_array_message_codec!(MessageSignature);
+impl_byte_array_message_codec!(Txid);
+impl_byte_array_message_codec!(VRFProof);
+
+impl StacksMessageCodec for StacksAddress {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.version)?;
+        write_next(fd, &self.bytes)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let version: u8 = read_next(fd)?;
+        if version >= 32 {
+            return Err(Error::InvalidVersion(version));
+        }
+        let bytes: Hash160 = read_next(fd)?;
+        Ok(StacksAddress { version, bytes })
+    }
+}
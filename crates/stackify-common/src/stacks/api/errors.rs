@@ -22,6 +22,42 @@ pub enum Error {
     /// Checked data was less than 4 bytes
     #[error("Data too short: {0}")]
     TooShort(usize),
+    /// An I/O error occurred while reading or writing consensus-serialized data
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The object could not be consensus-serialized
+    #[error("Serialization error: {0}")]
+    SerializeError(String),
+    /// The bytes could not be consensus-deserialized into the expected object
+    #[error("Deserialization error: {0}")]
+    DeserializeError(String),
+    /// A Clarity value did not conform to the expected type
+    #[error("Clarity type error: {0}")]
+    ClarityTypeError(String),
+    /// A Clarity literal could not be parsed (column, message)
+    #[error("Parse error at column {0}: {1}")]
+    ClarityParseError(usize, String),
+    /// A private or public key could not be parsed
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+    /// A transaction could not be signed
+    #[error("Signing error: {0}")]
+    SigningError(String),
+    /// A transaction's signatures could not be verified
+    #[error("Verification error: {0}")]
+    VerifyingError(String),
+    /// The HTTP request to the node failed
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The node responded with a non-success status code (status, body)
+    #[error("The node responded with HTTP {0}: {1}")]
+    HttpStatus(u16, String),
+    /// The node processed the request but reported a failure
+    #[error("RPC error: {0}")]
+    RpcError(String),
+    /// A Clarinet project's manifest, settings or deployment plan is invalid
+    #[error("Clarinet project error: {0}")]
+    ClarinetError(String),
     /// Any other error
     #[error("Error: {0}")]
     Other(String),
@@ -0,0 +1,226 @@
+//! Stacks keychains derived from BIP39 mnemonics, producing the same keys and
+//! addresses as `@stacks/cli make_keychain`.
+
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac};
+use libsecp256k1::{PublicKey, SecretKey};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+
+use super::{errors::Error, hash::Hash160};
+use crate::stacks::{StacksAddress, StacksPrivateKey};
+
+/// The BIP32 derivation path of the first Stacks account, as used by the
+/// Stacks CLI and wallets.
+pub const STACKS_DERIVATION_PATH: &str = "m/44'/5757'/0'/0/0";
+
+const BIP32_HARDENED: u32 = 0x80000000;
+
+const BTC_ADDRESS_VERSION_MAINNET: u8 = 0x00;
+const BTC_ADDRESS_VERSION_TESTNET: u8 = 0x6f;
+const WIF_VERSION_MAINNET: u8 = 0x80;
+const WIF_VERSION_TESTNET: u8 = 0xef;
+
+/// A generated keychain, in the shape of the Stacks CLI's `make_keychain`
+/// JSON output.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MakeKeychainResult {
+    pub mnemonic: String,
+    #[serde(rename = "keyInfo")]
+    pub key_info: KeyInfo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KeyInfo {
+    /// Hex-encoded, with a trailing `01` if the public key is compressed.
+    #[serde(rename = "privateKey")]
+    pub private_key: String,
+    #[serde(rename = "publicKey")]
+    pub public_key: String,
+    pub address: String,
+    /// The P2PKH Bitcoin address of the same key.
+    #[serde(rename = "btcAddress")]
+    pub btc_address: String,
+    pub wif: String,
+    pub index: u32,
+}
+
+impl MakeKeychainResult {
+    /// Generates a keychain from a new random 24-word mnemonic.
+    pub fn generate(mainnet: bool) -> Result<Self, Error> {
+        let mut entropy = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut entropy);
+        let mnemonic = Mnemonic::from_entropy(&entropy)
+            .map_err(|e| Error::InvalidKey(format!("Failed to generate mnemonic: {e}")))?;
+        Self::from_mnemonic(&mnemonic.to_string(), mainnet)
+    }
+
+    /// Derives the first Stacks account of the given mnemonic.
+    pub fn from_mnemonic(mnemonic: &str, mainnet: bool) -> Result<Self, Error> {
+        let mnemonic = Mnemonic::parse_normalized(mnemonic)
+            .map_err(|e| Error::InvalidKey(format!("Invalid mnemonic: {e}")))?;
+        let privk = derive_private_key(&mnemonic.to_seed(""), STACKS_DERIVATION_PATH)?;
+        Ok(MakeKeychainResult {
+            mnemonic: mnemonic.to_string(),
+            key_info: KeyInfo::from_private_key(&privk, mainnet),
+        })
+    }
+
+    /// Imports a keychain from a mnemonic, a hex-encoded private key or a WIF.
+    /// Keychains imported from a private key have an empty mnemonic.
+    pub fn from_secret(secret: &str, mainnet: bool) -> Result<Self, Error> {
+        let secret = secret.trim();
+        if secret.split_whitespace().nth(1).is_some() {
+            return Self::from_mnemonic(secret, mainnet);
+        }
+
+        let hex = secret.strip_prefix("0x").unwrap_or(secret);
+        let privk = if matches!(hex.len(), 64 | 66) && hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            StacksPrivateKey::from_hex(hex)?
+        } else {
+            from_wif(secret)?.0
+        };
+        Ok(MakeKeychainResult {
+            mnemonic: String::new(),
+            key_info: KeyInfo::from_private_key(&privk, mainnet),
+        })
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+}
+
+impl KeyInfo {
+    /// The keys and addresses of a single private key. The public key is
+    /// compressed or not as the private key says, like when signing. The
+    /// Bitcoin address and WIF use the testnet versions unless `mainnet` is
+    /// set, so that they can be used with a regtest node.
+    pub fn from_private_key(privk: &StacksPrivateKey, mainnet: bool) -> KeyInfo {
+        let public_key = privk.serialize_public_key();
+        KeyInfo {
+            private_key: privk.to_hex(),
+            public_key: hex::encode(&public_key),
+            address: StacksAddress::p2pkh_from_hash(mainnet, Hash160::from_data(&public_key))
+                .to_string(),
+            btc_address: btc_p2pkh_address(privk, mainnet),
+            wif: to_wif(privk, mainnet),
+            index: 0,
+        }
+    }
+}
+
+/// Derives the private key at `path` (e.g. `m/44'/5757'/0'/0/0`) from a BIP39
+/// seed, following BIP32.
+pub fn derive_private_key(seed: &[u8], path: &str) -> Result<StacksPrivateKey, Error> {
+    let invalid_path = || Error::InvalidKey(format!("Invalid derivation path '{path}'"));
+
+    let mut segments = path.split('/');
+    if segments.next() != Some("m") {
+        return Err(invalid_path());
+    }
+
+    let (mut key, mut chain_code) = bip32_split(&hmac_sha512(b"Bitcoin seed", &[seed]))?;
+    for segment in segments {
+        let index = match segment.strip_suffix('\'').or(segment.strip_suffix('h')) {
+            Some(index) => index
+                .parse::<u32>()
+                .ok()
+                .filter(|i| *i < BIP32_HARDENED)
+                .map(|i| i | BIP32_HARDENED),
+            None => segment.parse::<u32>().ok().filter(|i| *i < BIP32_HARDENED),
+        }
+        .ok_or_else(invalid_path)?;
+
+        let data = if index & BIP32_HARDENED != 0 {
+            [&[0u8][..], &key.serialize()].concat()
+        } else {
+            PublicKey::from_secret_key(&key)
+                .serialize_compressed()
+                .to_vec()
+        };
+        let (tweak, child_chain_code) =
+            bip32_split(&hmac_sha512(&chain_code, &[&data, &index.to_be_bytes()]))?;
+        key.tweak_add_assign(&tweak)
+            .map_err(|e| Error::InvalidKey(format!("Failed to derive child key: {e}")))?;
+        chain_code = child_chain_code;
+    }
+
+    Ok(StacksPrivateKey::from_secret_key(key, true))
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> [u8; 64] {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for chunk in data {
+        mac.update(chunk);
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/// Splits a BIP32 HMAC output into its key and chain code halves.
+fn bip32_split(hmac: &[u8; 64]) -> Result<(SecretKey, [u8; 32]), Error> {
+    let key = SecretKey::parse_slice(&hmac[..32])
+        .map_err(|e| Error::InvalidKey(format!("Failed to derive key: {e}")))?;
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&hmac[32..]);
+    Ok((key, chain_code))
+}
+
+/// Encodes a private key in Wallet Import Format, as accepted by e.g.
+/// `bitcoin-cli importprivkey`.
+pub fn to_wif(privk: &StacksPrivateKey, mainnet: bool) -> String {
+    let version = if mainnet {
+        WIF_VERSION_MAINNET
+    } else {
+        WIF_VERSION_TESTNET
+    };
+    let mut payload = vec![version];
+    payload.extend(privk.secret_key().serialize());
+    if privk.compress_public() {
+        payload.push(0x01);
+    }
+    bs58::encode(payload).with_check().into_string()
+}
+
+/// Decodes a WIF private key, returning it along with whether it is for
+/// mainnet.
+pub fn from_wif(wif: &str) -> Result<(StacksPrivateKey, bool), Error> {
+    let payload = bs58::decode(wif)
+        .with_check(None)
+        .into_vec()
+        .map_err(|e| Error::InvalidKey(format!("Invalid WIF: {e}")))?;
+    let mainnet = match payload.first() {
+        Some(&WIF_VERSION_MAINNET) => true,
+        Some(&WIF_VERSION_TESTNET) => false,
+        _ => return Err(Error::InvalidKey("Invalid WIF version".into())),
+    };
+    let compress_public = match payload.len() {
+        33 => false,
+        34 if payload[33] == 0x01 => true,
+        _ => return Err(Error::InvalidKey("Invalid WIF length".into())),
+    };
+    let key = SecretKey::parse_slice(&payload[1..33])
+        .map_err(|e| Error::InvalidKey(format!("Invalid WIF: {e}")))?;
+    Ok((
+        StacksPrivateKey::from_secret_key(key, compress_public),
+        mainnet,
+    ))
+}
+
+/// The legacy P2PKH Bitcoin address of a private key's public key, compressed
+/// or not as the key says.
+pub fn btc_p2pkh_address(privk: &StacksPrivateKey, mainnet: bool) -> String {
+    let version = if mainnet {
+        BTC_ADDRESS_VERSION_MAINNET
+    } else {
+        BTC_ADDRESS_VERSION_TESTNET
+    };
+    let mut payload = vec![version];
+    payload.extend(Hash160::from_data(&privk.serialize_public_key()).0);
+    bs58::encode(payload).with_check().into_string()
+}
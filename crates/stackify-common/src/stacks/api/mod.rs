@@ -1,7 +1,14 @@
+pub mod c32;
+pub mod clarinet;
 pub mod clarity;
+pub mod client;
+pub mod codec;
 pub mod errors;
 pub mod hash;
+pub mod keychain;
 pub mod transactions;
+pub mod v2;
+pub mod v3;
 
 #[cfg(test)]
 mod tests;
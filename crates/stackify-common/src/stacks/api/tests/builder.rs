@@ -0,0 +1,172 @@
+use crate::{
+    stacks::{
+        api::{
+            clarity::{ClarityVersion, PrincipalData, QualifiedContractIdentifier, Value},
+            codec::StacksMessageCodec,
+            hash::Hash160,
+            transactions::{
+                FungibleConditionCode, PostConditionPrincipal, StacksTransaction,
+                StacksTransactionBuilder, TransactionAnchorMode, TransactionPayload,
+                TransactionPostCondition, TransactionPostConditionMode, TransactionVersion,
+            },
+        },
+        StacksAddress, StacksPrivateKey, CHAIN_ID_MAINNET, CHAIN_ID_TESTNET,
+    },
+    types::EnvironmentKeychain,
+};
+
+fn privk(byte: u8) -> StacksPrivateKey {
+    StacksPrivateKey::from_hex(&format!("{}01", hex::encode([byte; 32]))).unwrap()
+}
+
+fn recipient() -> PrincipalData {
+    StacksAddress::p2pkh(false, &privk(0x02).public_key()).into()
+}
+
+fn keychain(privk: &StacksPrivateKey) -> EnvironmentKeychain {
+    EnvironmentKeychain {
+        id: 1,
+        environment_id: 1,
+        stx_address: StacksAddress::p2pkh(false, &privk.public_key()).to_string(),
+        amount: 0,
+        mnemonic: String::new(),
+        private_key: privk.to_hex(),
+        public_key: hex::encode(privk.public_key().serialize_compressed()),
+        btc_address: String::new(),
+        nonce: 0,
+        remark: None,
+    }
+}
+
+#[test]
+fn test_build_token_transfer() {
+    let tx = StacksTransactionBuilder::new(privk(0x01), CHAIN_ID_TESTNET)
+        .nonce(5)
+        .fee(180)
+        .memo("hello")
+        .anchor_mode(TransactionAnchorMode::OnChainOnly)
+        .token_transfer(recipient(), 1_000)
+        .unwrap();
+
+    tx.verify().unwrap();
+    assert_eq!(tx.version, TransactionVersion::Testnet);
+    assert_eq!(tx.chain_id, CHAIN_ID_TESTNET);
+    assert_eq!(tx.get_origin_nonce(), 5);
+    assert_eq!(tx.get_tx_fee(), 180);
+    assert_eq!(tx.anchor_mode, TransactionAnchorMode::OnChainOnly);
+    let TransactionPayload::TokenTransfer(to, amount, memo) = &tx.payload else {
+        panic!("expected a token transfer");
+    };
+    assert_eq!((to, *amount), (&recipient(), 1_000));
+    assert_eq!(&memo.0[..5], b"hello");
+    assert!(memo.0[5..].iter().all(|b| *b == 0));
+
+    let bytes = tx.serialize_to_vec().unwrap();
+    assert_eq!(
+        StacksTransaction::deserialize_from_slice(&bytes).unwrap(),
+        tx
+    );
+
+    let too_long = StacksTransactionBuilder::new(privk(0x01), CHAIN_ID_TESTNET)
+        .memo([0u8; 35])
+        .token_transfer(recipient(), 1);
+    assert!(too_long.is_err());
+}
+
+#[test]
+fn test_build_contract_call_and_deploy() {
+    let builder = StacksTransactionBuilder::new(privk(0x01), CHAIN_ID_MAINNET)
+        .nonce(1)
+        .fee(1_000)
+        .post_condition_mode(TransactionPostConditionMode::Allow)
+        .post_condition(TransactionPostCondition::STX(
+            PostConditionPrincipal::Origin,
+            FungibleConditionCode::SentLe,
+            500,
+        ));
+    assert_eq!(builder.version(), TransactionVersion::Mainnet);
+    assert!(builder.address().is_mainnet());
+
+    let contract = QualifiedContractIdentifier::new(builder.address().into(), "counter").unwrap();
+    let tx = builder
+        .contract_call(&contract, "increment", vec![Value::UInt(2)])
+        .unwrap();
+    tx.verify().unwrap();
+    assert_eq!(tx.post_condition_mode, TransactionPostConditionMode::Allow);
+    assert_eq!(tx.post_conditions.len(), 1);
+    let TransactionPayload::ContractCall(call) = &tx.payload else {
+        panic!("expected a contract call");
+    };
+    assert_eq!(call.address, builder.address());
+    assert_eq!(call.contract_name, "counter");
+    assert_eq!(call.function_args, vec![Value::UInt(2)]);
+    assert!(builder
+        .contract_call(&contract, "bad name", vec![])
+        .is_err());
+
+    let tx = builder
+        .smart_contract(
+            "counter",
+            "(define-data-var count uint u0)",
+            Some(ClarityVersion::Clarity2),
+        )
+        .unwrap();
+    tx.verify().unwrap();
+    assert!(matches!(
+        tx.payload,
+        TransactionPayload::SmartContract(_, Some(ClarityVersion::Clarity2))
+    ));
+    assert!(builder.smart_contract("1counter", "", None).is_err());
+}
+
+#[test]
+fn test_build_sponsored() {
+    let origin = StacksTransactionBuilder::new(privk(0x01), CHAIN_ID_TESTNET)
+        .nonce(2)
+        .fee(999)
+        .sponsored();
+    let unsponsored = origin.token_transfer(recipient(), 10).unwrap();
+    assert!(unsponsored.auth.is_sponsored());
+    unsponsored.verify_origin().unwrap();
+    assert!(unsponsored.verify().is_err());
+
+    let sponsor = StacksTransactionBuilder::new(privk(0x03), CHAIN_ID_TESTNET)
+        .nonce(7)
+        .fee(300);
+    let tx = sponsor.sponsor(&unsponsored).unwrap();
+    tx.verify().unwrap();
+    assert_eq!(tx.get_tx_fee(), 300);
+    assert_eq!(tx.get_origin_nonce(), 2);
+
+    // sponsors can't sign for a different chain
+    let sponsor = StacksTransactionBuilder::new(privk(0x03), CHAIN_ID_MAINNET);
+    assert!(sponsor.sponsor(&unsponsored).is_err());
+}
+
+#[test]
+fn test_builder_from_keychain() {
+    let key = privk(0x04);
+    let builder =
+        StacksTransactionBuilder::from_keychain(&keychain(&key), CHAIN_ID_TESTNET).unwrap();
+    assert_eq!(builder.address().to_string(), keychain(&key).stx_address);
+
+    let mut mismatched = keychain(&key);
+    mismatched.private_key = privk(0x05).to_hex();
+    assert!(StacksTransactionBuilder::from_keychain(&mismatched, CHAIN_ID_TESTNET).is_err());
+
+    // Transactions signed with an uncompressed key come from the address of
+    // the uncompressed public key, not the compressed one.
+    let uncompressed = StacksPrivateKey::from_hex(&hex::encode([0x04; 32])).unwrap();
+    let mut imported = keychain(&uncompressed);
+    assert!(StacksTransactionBuilder::from_keychain(&imported, CHAIN_ID_TESTNET).is_err());
+
+    let address = StacksAddress::p2pkh_from_hash(
+        false,
+        Hash160::from_data(&uncompressed.public_key().serialize()),
+    );
+    imported.stx_address = address.to_string();
+    let builder = StacksTransactionBuilder::from_keychain(&imported, CHAIN_ID_TESTNET).unwrap();
+    assert_eq!(builder.address(), address);
+    let tx = builder.token_transfer(recipient(), 1).unwrap();
+    assert_eq!(*tx.auth.origin().signer(), address.bytes);
+}
@@ -0,0 +1,127 @@
+use std::str::FromStr;
+
+use crate::stacks::{
+    api::{
+        c32::{c32_address, c32_address_decode, c32_decode, c32_encode, c32check_decode},
+        clarity::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData},
+        errors::Error,
+        hash::Hash160,
+    },
+    StacksAddress, C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+};
+
+const HASH: &str = "a46ff88886c2ef9762d970b4d2c63678835bd39d";
+
+fn hash() -> Hash160 {
+    Hash160::from(hex::decode(HASH).unwrap().as_slice())
+}
+
+#[test]
+fn test_c32_encode_decode() {
+    let cases: &[(&[u8], &str)] = &[
+        (b"", ""),
+        (&[0x00], "0"),
+        (&[0x00, 0x00, 0x01], "001"),
+        (b"hello world", "38CNP6RVS0EXQQ4V34"),
+    ];
+    for (bytes, encoded) in cases {
+        assert_eq!(c32_encode(bytes), *encoded);
+        assert_eq!(c32_decode(encoded).unwrap(), *bytes);
+    }
+
+    // decoding is case-insensitive and tolerates commonly-confused characters
+    assert_eq!(
+        c32_decode("38cnp6rvs0exqq4v34").unwrap(),
+        c32_decode("38CNP6RVSOEXQQ4V34").unwrap()
+    );
+    assert!(matches!(c32_decode("U"), Err(Error::InvalidCrockford32)));
+    assert!(matches!(c32_decode("ü"), Err(Error::InvalidCrockford32)));
+}
+
+#[test]
+fn test_c32_address() {
+    let bytes = hex::decode(HASH).unwrap();
+    let cases = [
+        (
+            C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7",
+        ),
+        (
+            C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ",
+        ),
+    ];
+    for (version, addr) in cases {
+        assert_eq!(c32_address(version, &bytes).unwrap(), addr);
+        assert_eq!(c32_address_decode(addr).unwrap(), (version, bytes.clone()));
+    }
+
+    assert!(matches!(
+        c32_address(32, &bytes),
+        Err(Error::InvalidVersion(32))
+    ));
+    assert!(matches!(
+        c32_address_decode("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8"),
+        Err(Error::BadChecksum(_, _))
+    ));
+    assert!(matches!(c32check_decode("P"), Err(Error::TooShort(_))));
+}
+
+#[test]
+fn test_stacks_address_display_from_str() {
+    let addr = StacksAddress::new(C32_ADDRESS_VERSION_TESTNET_SINGLESIG, hash());
+    assert_eq!(
+        addr.to_string(),
+        "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ"
+    );
+    assert_eq!(StacksAddress::from_str(&addr.to_string()).unwrap(), addr);
+
+    // addresses must start with 'S'
+    assert!(StacksAddress::from_str("XT2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR").is_err());
+    // and must hold a 20-byte hash
+    let short = c32_address(C32_ADDRESS_VERSION_TESTNET_SINGLESIG, &[1, 2, 3]).unwrap();
+    assert!(matches!(
+        StacksAddress::from_str(&short),
+        Err(Error::InvalidLength(3))
+    ));
+
+    // versions which can't be c32-encoded are displayed rather than panicking
+    let invalid = StacksAddress::new(32, hash());
+    assert_eq!(
+        invalid.to_string(),
+        format!("<invalid address: version 32, hash {HASH}>")
+    );
+    assert!(StacksAddress::from_str(&invalid.to_string()).is_err());
+    assert_eq!(
+        StandardPrincipalData::from(invalid.clone()).to_string(),
+        invalid.to_string()
+    );
+}
+
+#[test]
+fn test_principal_parsing() {
+    let addr = StacksAddress::new(C32_ADDRESS_VERSION_TESTNET_SINGLESIG, hash());
+    let standard = StandardPrincipalData::from(addr.clone());
+    assert_eq!(StacksAddress::from(standard.clone()), addr);
+    assert_eq!(standard.to_string(), addr.to_string());
+
+    let principal = PrincipalData::from_str(&addr.to_string()).unwrap();
+    assert_eq!(principal, PrincipalData::Standard(standard.clone()));
+    assert_eq!(principal, PrincipalData::from(addr.clone()));
+
+    let contract_str = format!("{addr}.my-contract_v2");
+    let principal = PrincipalData::from_str(&contract_str).unwrap();
+    assert_eq!(
+        principal,
+        PrincipalData::Contract(QualifiedContractIdentifier {
+            issuer: standard,
+            name: "my-contract_v2".into(),
+        })
+    );
+    assert_eq!(principal.to_string(), contract_str);
+
+    assert!(QualifiedContractIdentifier::from_str(&addr.to_string()).is_err());
+    assert!(PrincipalData::from_str(&format!("{addr}.1-bad-name")).is_err());
+    assert!(PrincipalData::from_str(&format!("{addr}.")).is_err());
+    assert!(PrincipalData::from_str("not-an-address.contract").is_err());
+}
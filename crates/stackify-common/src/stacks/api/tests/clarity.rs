@@ -0,0 +1,419 @@
+use serde::{Deserialize, Serialize};
+
+use crate::stacks::api::{
+    clarity::{
+        parse_value, parse_value_typed, value_hex, BufferLength, CallableData, CallableSubtype,
+        ListTypeData, PrincipalData, QualifiedContractIdentifier, SequenceData, SequenceSubtype,
+        StandardPrincipalData, StringSubtype, TraitIdentifier, TypeSignature, Value,
+    },
+    errors::Error,
+};
+
+fn contract(name: &str) -> QualifiedContractIdentifier {
+    QualifiedContractIdentifier {
+        issuer: StandardPrincipalData(26, [0x11; 20]),
+        name: name.into(),
+    }
+}
+
+fn tuple(items: Vec<(&str, Value)>) -> Value {
+    Value::tuple_from_data(
+        items
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    )
+    .unwrap()
+}
+
+fn ascii(s: &str) -> Value {
+    Value::string_ascii_from_bytes(s.as_bytes().to_vec()).unwrap()
+}
+
+fn utf8(s: &str) -> Value {
+    Value::string_utf8_from_bytes(s.as_bytes().to_vec()).unwrap()
+}
+
+#[test]
+fn test_value_hex_vectors() {
+    let hash = "11".repeat(20);
+    let cases = vec![
+        (Value::Int(-1), format!("00{}", "ff".repeat(16))),
+        (Value::UInt(1), format!("01{}01", "00".repeat(15))),
+        (Value::Bool(true), "03".to_string()),
+        (Value::Bool(false), "04".to_string()),
+        (Value::none(), "09".to_string()),
+        (Value::some(Value::Bool(true)), "0a03".to_string()),
+        (Value::okay(Value::Bool(true)), "0703".to_string()),
+        (Value::error(Value::Bool(false)), "0804".to_string()),
+        (
+            Value::buff_from(vec![0xde, 0xad, 0xbe, 0xef]).unwrap(),
+            "0200000004deadbeef".to_string(),
+        ),
+        (ascii("hi"), "0d000000026869".to_string()),
+        (utf8("é"), "0e00000002c3a9".to_string()),
+        (
+            Value::Principal(PrincipalData::Standard(StandardPrincipalData(
+                26, [0x11; 20],
+            ))),
+            format!("051a{hash}"),
+        ),
+        (
+            Value::Principal(PrincipalData::Contract(contract("foo"))),
+            format!("061a{hash}03666f6f"),
+        ),
+        (
+            Value::list_from(vec![Value::Bool(true), Value::Bool(false)]).unwrap(),
+            "0b000000020304".to_string(),
+        ),
+        (
+            tuple(vec![("b", Value::Bool(false)), ("a", Value::Bool(true))]),
+            "0c00000002016103016204".to_string(),
+        ),
+    ];
+
+    for (value, hex_str) in cases {
+        assert_eq!(value.serialize_to_hex().unwrap(), hex_str);
+        assert_eq!(Value::try_deserialize_hex_untyped(&hex_str).unwrap(), value);
+        assert_eq!(
+            Value::try_deserialize_hex_untyped(&format!("0x{hex_str}")).unwrap(),
+            value
+        );
+    }
+}
+
+#[test]
+fn test_value_hex_nested_roundtrip() {
+    let value = Value::okay(tuple(vec![
+        (
+            "items",
+            Value::list_from(vec![
+                Value::some(tuple(vec![("id", Value::UInt(1)), ("memo", ascii("a\"b"))])),
+                Value::none(),
+            ])
+            .unwrap(),
+        ),
+        (
+            "owner",
+            Value::Principal(PrincipalData::Contract(contract("vault"))),
+        ),
+        ("result", Value::error(utf8("héllo"))),
+    ]));
+
+    let hex_str = value.serialize_to_hex().unwrap();
+    assert_eq!(Value::try_deserialize_hex_untyped(&hex_str).unwrap(), value);
+
+    assert!(Value::try_deserialize_hex_untyped("0x").is_err());
+    assert!(Value::try_deserialize_hex_untyped("0x0f").is_err());
+    assert!(Value::try_deserialize_hex_untyped("0x0303").is_err());
+    assert!(Value::try_deserialize_hex_untyped("not hex").is_err());
+}
+
+#[test]
+fn test_value_typed_deserialization() {
+    let trait_id = TraitIdentifier {
+        name: "sip-010-trait".into(),
+        contract_identifier: contract("sip-010"),
+    };
+    let callable = TypeSignature::CallableType(CallableSubtype::Trait(trait_id.clone()));
+    let hex_str = Value::Principal(PrincipalData::Contract(contract("token")))
+        .serialize_to_hex()
+        .unwrap();
+
+    // contract principals are decoded as callable contracts when a trait is expected
+    assert_eq!(
+        Value::try_deserialize_hex(&hex_str, &callable).unwrap(),
+        Value::CallableContract(CallableData {
+            contract_identifier: contract("token"),
+            trait_identifier: Some(trait_id),
+        })
+    );
+    assert!(matches!(
+        Value::try_deserialize_hex(&hex_str, &TypeSignature::PrincipalType).unwrap(),
+        Value::Principal(_)
+    ));
+    assert!(Value::try_deserialize_hex(&hex_str, &TypeSignature::UIntType).is_err());
+
+    // nested types are checked too
+    let list_type = TypeSignature::SequenceType(SequenceSubtype::ListType(ListTypeData::new_list(
+        TypeSignature::OptionalType(Box::new(callable)),
+        2,
+    )));
+    let hex_str = Value::list_from(vec![
+        Value::some(Value::Principal(PrincipalData::Contract(contract("a")))),
+        Value::none(),
+    ])
+    .unwrap()
+    .serialize_to_hex()
+    .unwrap();
+    let value = Value::try_deserialize_hex(&hex_str, &list_type).unwrap();
+    assert_eq!(
+        value.to_string(),
+        format!("(list (some '{}) none)", contract("a"))
+    );
+
+    let hex_str = ascii("too long").serialize_to_hex().unwrap();
+    let short_string = TypeSignature::SequenceType(SequenceSubtype::StringType(
+        StringSubtype::ASCII(BufferLength::try_from(4u32).unwrap()),
+    ));
+    assert!(Value::try_deserialize_hex(&hex_str, &short_string).is_err());
+}
+
+#[test]
+fn test_conform_to_type_errors() {
+    let tuple_type = TypeSignature::type_of(&tuple(vec![
+        ("a", Value::UInt(1)),
+        ("b", Value::Bool(true)),
+    ]));
+
+    let err = tuple(vec![("a", Value::Int(1)), ("b", Value::Bool(true))])
+        .conform_to_type(&tuple_type)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Clarity type error: tuple field 'a': expected uint, got 1"
+    );
+
+    let err = tuple(vec![("a", Value::UInt(1))])
+        .conform_to_type(&tuple_type)
+        .unwrap_err();
+    assert!(err.to_string().contains("tuple field 'b' is missing"));
+
+    let err = tuple(vec![
+        ("a", Value::UInt(1)),
+        ("b", Value::Bool(true)),
+        ("c", Value::Bool(true)),
+    ])
+    .conform_to_type(&tuple_type)
+    .unwrap_err();
+    assert!(err.to_string().contains("unexpected tuple field 'c'"));
+}
+
+#[test]
+fn test_value_display() {
+    let cases = vec![
+        (Value::Int(-5), "-5"),
+        (Value::UInt(1), "u1"),
+        (Value::okay(Value::UInt(1)), "(ok u1)"),
+        (Value::error(Value::Int(2)), "(err 2)"),
+        (Value::some(Value::none()), "(some none)"),
+        (Value::Bool(true), "true"),
+        (Value::buff_from(vec![0xde, 0xad]).unwrap(), "0xdead"),
+        (ascii("say \"hi\"\n"), r#""say \"hi\"\n""#),
+        (utf8("héllo"), r#"u"h\u{e9}llo""#),
+        (Value::list_from(vec![]).unwrap(), "(list)"),
+        (
+            tuple(vec![("b", ascii("x")), ("a", Value::Int(1))]),
+            r#"(tuple (a 1) (b "x"))"#,
+        ),
+    ];
+    for (value, expected) in cases {
+        assert_eq!(value.to_string(), expected);
+    }
+
+    let principal = Value::Principal(PrincipalData::Contract(contract("foo")));
+    assert_eq!(principal.to_string(), format!("'{}", contract("foo")));
+    assert!(principal.to_string().starts_with("'ST"));
+}
+
+#[test]
+fn test_type_signature_display() {
+    let value = Value::okay(tuple(vec![
+        ("a", Value::list_from(vec![Value::UInt(1)]).unwrap()),
+        ("b", Value::some(ascii("xy"))),
+        ("c", Value::buff_from(vec![1, 2, 3]).unwrap()),
+    ]));
+    assert_eq!(
+        TypeSignature::type_of(&value).to_string(),
+        "(response (tuple (a (list 1 uint)) (b (optional (string-ascii 2))) (c (buff 3))) UnknownType)"
+    );
+}
+
+#[test]
+fn test_value_hex_json() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct ReadOnlyResult {
+        okay: bool,
+        #[serde(with = "value_hex")]
+        result: Value,
+    }
+
+    let json = format!(
+        r#"{{"okay":true,"result":"0x07{}"}}"#,
+        Value::UInt(7).serialize_to_hex().unwrap()
+    );
+    let parsed: ReadOnlyResult = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.result, Value::okay(Value::UInt(7)));
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+
+    assert!(serde_json::from_str::<ReadOnlyResult>(r#"{"okay":true,"result":"0xzz"}"#).is_err());
+}
+
+fn list_type(entry_type: TypeSignature, max_len: u32) -> TypeSignature {
+    TypeSignature::SequenceType(SequenceSubtype::ListType(ListTypeData::new_list(
+        entry_type, max_len,
+    )))
+}
+
+/// Asserts that parsing fails at the (1-based) column of `at` within `input`.
+fn assert_parse_error(result: Result<Value, Error>, input: &str, at: &str) {
+    let expected_column = input.find(at).unwrap() + 1;
+    match result {
+        Err(Error::ClarityParseError(column, msg)) => {
+            assert_eq!(
+                column, expected_column,
+                "wrong position for '{input}': {msg}"
+            )
+        }
+        other => panic!("expected a parse error for '{input}', got {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_value() {
+    let addr = StandardPrincipalData(26, [0x11; 20]);
+    let standard = format!("'{addr}");
+    let contract_principal = format!("'{}", contract("my-contract"));
+    let cases = vec![
+        ("u100", Value::UInt(100)),
+        ("-42", Value::Int(-42)),
+        ("true", Value::Bool(true)),
+        (
+            "0xdeadbeef",
+            Value::buff_from(vec![0xde, 0xad, 0xbe, 0xef]).unwrap(),
+        ),
+        ("0x", Value::buff_from(vec![]).unwrap()),
+        ("(some none)", Value::some(Value::none())),
+        ("(ok true)", Value::okay(Value::Bool(true))),
+        ("( err  u1 )", Value::error(Value::UInt(1))),
+        (
+            "(list u1 u2)",
+            Value::list_from(vec![Value::UInt(1), Value::UInt(2)]).unwrap(),
+        ),
+        ("(list)", Value::list_from(vec![]).unwrap()),
+        (r#""a \"b\"\n""#, ascii("a \"b\"\n")),
+        (r#"u"h\u{e9}llo wörld""#, utf8("héllo wörld")),
+        (
+            standard.as_str(),
+            Value::Principal(PrincipalData::Standard(addr.clone())),
+        ),
+        (
+            contract_principal.as_str(),
+            Value::Principal(PrincipalData::Contract(contract("my-contract"))),
+        ),
+        (
+            r#"(tuple (a 1) (b "x"))"#,
+            tuple(vec![("a", Value::Int(1)), ("b", ascii("x"))]),
+        ),
+        (
+            r#"{ a: 1, b: "x" }"#,
+            tuple(vec![("a", Value::Int(1)), ("b", ascii("x"))]),
+        ),
+    ];
+
+    for (input, expected) in cases {
+        let value = parse_value(input).unwrap();
+        assert_eq!(value, expected, "parsing '{input}'");
+        // the literal display parses back to the same value
+        assert_eq!(value.to_string().parse::<Value>().unwrap(), value);
+    }
+}
+
+#[test]
+fn test_parse_value_errors() {
+    let cases = [
+        ("", ""),
+        ("u1 u2", "u2"),
+        ("(list u1 2)", "(list"),
+        ("(tuple (a 1) (a 2))", "a 2"),
+        ("(tuple)", "(tuple"),
+        ("(maybe u1)", "maybe"),
+        ("(some u1", ""),
+        ("0xabc", "0xabc"),
+        ("u340282366920938463463374607431768211456", "u3"),
+        ("\"héllo\"", "é"),
+        ("\"unterminated", "\"unterminated"),
+        ("'ST000", "'ST000"),
+        ("'.contract", "'.contract"),
+        ("(ok wat)", "wat"),
+    ];
+    for (input, at) in cases {
+        let at = if at.is_empty() && !input.is_empty() {
+            // errors at the end of the input
+            assert_parse_error(parse_value(input), &format!("{input}$"), "$");
+            continue;
+        } else {
+            at
+        };
+        assert_parse_error(parse_value(input), input, at);
+    }
+
+    let nested = format!("{}u1{}", "(some ".repeat(40), ")".repeat(40));
+    assert!(parse_value(&nested).is_err());
+}
+
+#[test]
+fn test_parse_value_typed() {
+    let trait_id = TraitIdentifier {
+        name: "sip-010-trait".into(),
+        contract_identifier: contract("sip-010"),
+    };
+    let args_type = TypeSignature::type_of(&tuple(vec![
+        ("amount", Value::UInt(0)),
+        ("memo", Value::some(Value::buff_from(vec![0; 34]).unwrap())),
+    ]));
+
+    let input = "(tuple (amount u10) (memo (some 0x01)))";
+    let value = parse_value_typed(input, &args_type).unwrap();
+    assert_eq!(TypeSignature::type_of(&value), args_type);
+
+    let input = "(tuple (amount 10) (memo none))";
+    assert_parse_error(parse_value_typed(input, &args_type), input, "10");
+
+    let input = "{ amount: u1, memo: none, extra: true }";
+    assert_parse_error(parse_value_typed(input, &args_type), input, "extra");
+
+    let input = "{ amount: u1 }";
+    assert_parse_error(parse_value_typed(input, &args_type), input, "{");
+
+    let input = "(list u1 u2 u3)";
+    assert_parse_error(
+        parse_value_typed(input, &list_type(TypeSignature::UIntType, 2)),
+        input,
+        "(list",
+    );
+    let input = "(list u1 2)";
+    assert_parse_error(
+        parse_value_typed(input, &list_type(TypeSignature::UIntType, 2)),
+        input,
+        "2)",
+    );
+
+    let input = "(ok u1)";
+    assert_parse_error(
+        parse_value_typed(input, &TypeSignature::UIntType),
+        input,
+        "(ok",
+    );
+
+    // contract principals become callable contracts when a trait is expected
+    let input = format!("(list '{})", contract("token"));
+    let value = parse_value_typed(
+        &input,
+        &list_type(
+            TypeSignature::CallableType(CallableSubtype::Trait(trait_id.clone())),
+            1,
+        ),
+    )
+    .unwrap();
+    let Value::Sequence(SequenceData::List(list)) = value else {
+        panic!("expected a list, got {value}");
+    };
+    assert_eq!(
+        list.data,
+        vec![Value::CallableContract(CallableData {
+            contract_identifier: contract("token"),
+            trait_identifier: Some(trait_id),
+        })]
+    );
+}
@@ -0,0 +1,188 @@
+use crate::stacks::{
+    api::{
+        keychain::{
+            btc_p2pkh_address, derive_private_key, from_wif, to_wif, KeyInfo, MakeKeychainResult,
+        },
+        transactions::TransactionSpendingCondition,
+    },
+    StacksAddress, StacksPrivateKey, TransactionPublicKeyEncoding,
+};
+
+/// The Clarinet devnet `deployer` account.
+const DEPLOYER_MNEMONIC: &str = "twice kind fence tip hidden tilt action fragile skin nothing glory cousin green tomorrow spring wrist shed math olympic multiply hip blue scout claw";
+
+#[test]
+fn test_bip32_vectors() {
+    // BIP32 test vector 1.
+    let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+    let cases = [
+        (
+            "m",
+            "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35",
+        ),
+        (
+            "m/0'",
+            "edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea",
+        ),
+        (
+            "m/0'/1",
+            "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368",
+        ),
+        (
+            "m/0h/1/2'",
+            "cbce0d719ecf7431d88e6a89fa1483e02e35092af60c042b1df2ff59fa424dca",
+        ),
+    ];
+    for (path, expected) in cases {
+        let privk = derive_private_key(&seed, path).unwrap();
+        assert_eq!(privk.to_hex(), format!("{expected}01"), "{path}");
+    }
+
+    for path in ["", "0/1", "m/x", "m/2147483648", "m/1/'"] {
+        assert!(derive_private_key(&seed, path).is_err(), "{path}");
+    }
+}
+
+#[test]
+fn test_keychain_from_mnemonic() {
+    let keychain = MakeKeychainResult::from_mnemonic(DEPLOYER_MNEMONIC, false).unwrap();
+    assert_eq!(keychain.mnemonic, DEPLOYER_MNEMONIC);
+    assert_eq!(
+        keychain.key_info.private_key,
+        "753b7cc01a1a2e86221266a154af739463fce51219d97e4f856cd7200c3bd2a601"
+    );
+    assert_eq!(
+        keychain.key_info.address,
+        "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM"
+    );
+    assert_eq!(keychain.key_info.index, 0);
+
+    // Extra whitespace is ignored.
+    let padded = format!("  {}\n", DEPLOYER_MNEMONIC.replace(' ', "  "));
+    assert_eq!(
+        MakeKeychainResult::from_mnemonic(&padded, false).unwrap(),
+        keychain
+    );
+    assert!(MakeKeychainResult::from_mnemonic("twice kind fence", false).is_err());
+
+    let mainnet = MakeKeychainResult::from_mnemonic(DEPLOYER_MNEMONIC, true).unwrap();
+    assert_eq!(
+        mainnet.key_info.address,
+        "SP1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRCBGD7R"
+    );
+    assert_eq!(mainnet.key_info.private_key, keychain.key_info.private_key);
+}
+
+#[test]
+fn test_generate_keychain() {
+    let keychain = MakeKeychainResult::generate(false).unwrap();
+    assert_eq!(keychain.mnemonic.split_whitespace().count(), 24);
+
+    let address: StacksAddress = keychain.key_info.address.parse().unwrap();
+    assert!(!address.is_mainnet());
+    let privk = StacksPrivateKey::from_hex(&keychain.key_info.private_key).unwrap();
+    assert_eq!(StacksAddress::p2pkh(false, &privk.public_key()), address);
+    assert_eq!(
+        MakeKeychainResult::from_mnemonic(&keychain.mnemonic, false).unwrap(),
+        keychain
+    );
+
+    let json = keychain.to_json().unwrap();
+    assert!(json.contains("\"keyInfo\"") && json.contains("\"btcAddress\""));
+    assert_eq!(MakeKeychainResult::from_json(&json).unwrap(), keychain);
+}
+
+#[test]
+fn test_wif_and_btc_address() {
+    // The well-known private key `1`.
+    let privk = StacksPrivateKey::from_hex(&format!("{}01", "00".repeat(31) + "01")).unwrap();
+    assert_eq!(
+        to_wif(&privk, true),
+        "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn"
+    );
+    assert_eq!(
+        btc_p2pkh_address(&privk, true),
+        "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+    );
+    assert_eq!(
+        btc_p2pkh_address(&privk, false),
+        "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r"
+    );
+
+    let testnet_wif = to_wif(&privk, false);
+    assert!(testnet_wif.starts_with('c'));
+    assert_eq!(from_wif(&testnet_wif).unwrap(), (privk.clone(), false));
+    assert_eq!(
+        from_wif("KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn").unwrap(),
+        (privk.clone(), true)
+    );
+    assert!(from_wif("KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWo").is_err());
+
+    let key_info = KeyInfo::from_private_key(&privk, false);
+    assert_eq!(key_info.wif, testnet_wif);
+    assert_eq!(key_info.btc_address, "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r");
+    assert_eq!(
+        key_info.public_key,
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+    );
+}
+
+#[test]
+fn test_keychain_from_secret() {
+    let deployer = MakeKeychainResult::from_mnemonic(DEPLOYER_MNEMONIC, false).unwrap();
+    assert_eq!(
+        MakeKeychainResult::from_secret(DEPLOYER_MNEMONIC, false).unwrap(),
+        deployer
+    );
+
+    let privk = StacksPrivateKey::from_hex(&deployer.key_info.private_key).unwrap();
+    let secrets = [
+        deployer.key_info.private_key.clone(),
+        format!("0x{}", deployer.key_info.private_key),
+        deployer.key_info.wif.clone(),
+        to_wif(&privk, true),
+    ];
+    for secret in secrets {
+        let imported = MakeKeychainResult::from_secret(&secret, false).unwrap();
+        assert_eq!(imported.mnemonic, "", "{secret}");
+        assert_eq!(imported.key_info, deployer.key_info, "{secret}");
+    }
+
+    // 32-byte keys are kept as-is, without the compression flag.
+    let uncompressed =
+        MakeKeychainResult::from_secret(&deployer.key_info.private_key[..64], false).unwrap();
+    assert_eq!(uncompressed.key_info.private_key.len(), 64);
+
+    // Uncompressed keys have their uncompressed public key hashed into their
+    // addresses, the same as when signing with them.
+    let uncompressed = MakeKeychainResult::from_secret(
+        "5HpHagT65TZzG1PH3CSu63k8DbpvD8s5ip4nEB3kEsreAnchuDf",
+        true,
+    )
+    .unwrap();
+    assert_eq!(
+        uncompressed.key_info.public_key,
+        "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+         483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"
+    );
+    assert_eq!(
+        uncompressed.key_info.btc_address,
+        "1EHNa6Q4Jz2uvNExL497mE43ikXhwF6kZm"
+    );
+    let privk = StacksPrivateKey::from_hex(&uncompressed.key_info.private_key).unwrap();
+    let signer = TransactionSpendingCondition::new_singlesig_p2pkh(
+        &privk.public_key(),
+        TransactionPublicKeyEncoding::Uncompressed,
+    );
+    assert_eq!(
+        uncompressed.key_info.address,
+        StacksAddress::p2pkh_from_hash(true, *signer.signer()).to_string()
+    );
+
+    for secret in ["", "not a mnemonic", "zz", "0x1234"] {
+        assert!(
+            MakeKeychainResult::from_secret(secret, false).is_err(),
+            "{secret}"
+        );
+    }
+}
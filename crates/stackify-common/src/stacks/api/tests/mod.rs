@@ -1,3 +1,31 @@
+mod builder;
+mod c32;
+mod clarinet;
+mod clarity;
+mod keychain;
 mod serialization;
+mod signing;
 mod v2;
 mod v3;
+
+use mockito::{Mock, ServerGuard};
+
+use super::client::StacksNodeClient;
+
+/// Starts a mock node, returning it along with a client pointed at it.
+async fn mock_node() -> (ServerGuard, StacksNodeClient) {
+    let server = mockito::Server::new_async().await;
+    let client = StacksNodeClient::new(&server.url()).unwrap();
+    (server, client)
+}
+
+/// Mocks a JSON response for the given method and path.
+async fn mock_json(server: &mut ServerGuard, method: &str, path: &str, body: &str) -> Mock {
+    server
+        .mock(method, path)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(body)
+        .create_async()
+        .await
+}
@@ -5,15 +5,25 @@ use rand::thread_rng;
 
 use crate::stacks::{
     api::{
-        clarity::ClarityVersion,
+        clarity::{
+            ClarityVersion, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
+            Value,
+        },
+        codec::StacksMessageCodec,
+        hash::Hash160,
         transactions::{
-            SinglesigHashMode, SinglesigSpendingCondition, StacksTransaction,
-            TransactionAnchorMode, TransactionAuth, TransactionPayload,
-            TransactionPostConditionMode, TransactionSmartContract, TransactionSpendingCondition,
-            TransactionVersion,
+            AssetInfo, CoinbasePayload, FungibleConditionCode, MultisigHashMode,
+            MultisigSpendingCondition, NonfungibleConditionCode, OrderIndependentMultisigHashMode,
+            OrderIndependentMultisigSpendingCondition, PostConditionPrincipal, SinglesigHashMode,
+            SinglesigSpendingCondition, StacksMicroblockHeader, StacksTransaction,
+            TenureChangeCause, TenureChangePayload, TokenTransferMemo, TransactionAnchorMode,
+            TransactionAuth, TransactionAuthField, TransactionContractCall, TransactionPayload,
+            TransactionPostCondition, TransactionPostConditionMode, TransactionSmartContract,
+            TransactionSpendingCondition, TransactionVersion,
         },
     },
-    MessageSignature, TransactionPublicKeyEncoding,
+    BlockHeaderHash, ConsensusHash, MessageSignature, Sha512Trunc256Sum, StacksAddress,
+    StacksBlockId, TransactionPublicKeyEncoding, VRFProof,
 };
 
 #[test]
@@ -50,3 +60,423 @@ pub fn test_publish_contract() {
 
     let trx_json = serde_json::to_string(&trx).unwrap();
 }
+
+// The round-trip vectors below are built by hand from the wire format. They
+// still need to be complemented with serialized transactions taken from
+// stackslib's transaction tests (`chainstate/stacks/transaction.rs`), each
+// deserialized, re-serialized and compared byte for byte: a token transfer, a
+// contract call with post-conditions, a sponsored multisig, a Nakamoto
+// coinbase with an alt recipient, a tenure change and a poison microblock.
+fn assert_roundtrip<T>(value: &T, expected_hex: &str)
+where
+    T: StacksMessageCodec + PartialEq + std::fmt::Debug,
+{
+    let bytes = value.serialize_to_vec().unwrap();
+    assert_eq!(hex::encode(&bytes), expected_hex);
+    let decoded = T::deserialize_from_slice(&hex::decode(expected_hex).unwrap()).unwrap();
+    assert_eq!(&decoded, value);
+}
+
+fn singlesig_auth(nonce: u64, tx_fee: u64) -> TransactionSpendingCondition {
+    TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+        hash_mode: SinglesigHashMode::P2PKH,
+        signer: Hash160([0x11; 20]),
+        nonce,
+        tx_fee,
+        key_encoding: TransactionPublicKeyEncoding::Compressed,
+        signature: MessageSignature([0x01; 65]),
+    })
+}
+
+fn testnet_tx(auth: TransactionAuth, payload: TransactionPayload) -> StacksTransaction {
+    StacksTransaction {
+        version: TransactionVersion::Testnet,
+        chain_id: 0x80000000,
+        auth,
+        anchor_mode: TransactionAnchorMode::Any,
+        post_condition_mode: TransactionPostConditionMode::Deny,
+        post_conditions: vec![],
+        payload,
+    }
+}
+
+/// A testnet, standard-auth, P2PKH header with nonce 5 and fee 180.
+const STANDARD_SINGLESIG_HEADER: &str = concat!(
+    "80",                                       // version: testnet
+    "80000000",                                 // chain id
+    "04",                                       // auth: standard
+    "00",                                       // hash mode: P2PKH
+    "1111111111111111111111111111111111111111", // signer
+    "0000000000000005",                         // nonce
+    "00000000000000b4",                         // fee
+    "00",                                       // key encoding: compressed
+    "010101010101010101010101010101010101010101010101010101010101010101",
+    "0101010101010101010101010101010101010101010101010101010101010101", // signature
+);
+
+#[test]
+fn test_token_transfer_roundtrip() {
+    let mut memo = [0u8; 34];
+    memo[..5].copy_from_slice(b"hello");
+    let tx = testnet_tx(
+        TransactionAuth::Standard(singlesig_auth(5, 180)),
+        TransactionPayload::TokenTransfer(
+            PrincipalData::Standard(StandardPrincipalData(26, [0x22; 20])),
+            12345,
+            TokenTransferMemo(memo),
+        ),
+    );
+
+    let expected = [
+        STANDARD_SINGLESIG_HEADER,
+        "03",       // anchor mode: any
+        "02",       // post-condition mode: deny
+        "00000000", // no post-conditions
+        "00",       // payload: token transfer
+        "05",       // recipient: standard principal
+        "1a2222222222222222222222222222222222222222",
+        "0000000000003039", // amount
+        "68656c6c6f0000000000000000000000000000000000000000000000000000000000", // memo
+    ]
+    .concat();
+
+    assert_roundtrip(&tx, &expected);
+    // SHA-512/256 of the serialized transaction, computed independently of
+    // this crate.
+    assert_eq!(
+        tx.txid().unwrap().to_string(),
+        "882765645b0fc8e3c73b6961a6e9f57c4eea26b0bf33e4d8121c08f71c015f67"
+    );
+}
+
+#[test]
+fn test_contract_call_with_post_conditions_roundtrip() {
+    let contract_address = StacksAddress::new(26, Hash160([0x33; 20]));
+    let mut tx = testnet_tx(
+        TransactionAuth::Standard(singlesig_auth(5, 180)),
+        TransactionPayload::ContractCall(TransactionContractCall {
+            address: contract_address.clone(),
+            contract_name: "token".into(),
+            function_name: "transfer".into(),
+            function_args: vec![
+                Value::UInt(1),
+                Value::Principal(PrincipalData::Contract(QualifiedContractIdentifier {
+                    issuer: StandardPrincipalData(26, [0x22; 20]),
+                    name: "vault".into(),
+                })),
+                Value::some(Value::buff_from(vec![0xde, 0xad]).unwrap()),
+            ],
+        }),
+    );
+    let asset_info = AssetInfo {
+        contract_address,
+        contract_name: "token".into(),
+        asset_name: "nft".into(),
+    };
+    tx.post_conditions = vec![
+        TransactionPostCondition::STX(
+            PostConditionPrincipal::Origin,
+            FungibleConditionCode::SentLe,
+            1000,
+        ),
+        TransactionPostCondition::Fungible(
+            PostConditionPrincipal::Standard(StacksAddress::new(26, Hash160([0x22; 20]))),
+            asset_info.clone(),
+            FungibleConditionCode::SentEq,
+            1,
+        ),
+        TransactionPostCondition::Nonfungible(
+            PostConditionPrincipal::Contract(
+                StacksAddress::new(26, Hash160([0x22; 20])),
+                "vault".into(),
+            ),
+            asset_info,
+            Value::UInt(7),
+            NonfungibleConditionCode::Sent,
+        ),
+    ];
+
+    let asset_info_hex = concat!(
+        "1a3333333333333333333333333333333333333333", // contract address
+        "05746f6b656e",                               // contract name
+        "036e6674",                                   // asset name
+    );
+    let expected = [
+        STANDARD_SINGLESIG_HEADER,
+        "03",
+        "02",
+        "00000003", // three post-conditions
+        // STX, origin, sent-le, 1000
+        "00",
+        "01",
+        "05",
+        "00000000000003e8",
+        // fungible, standard principal, sent-eq, 1
+        "01",
+        "021a2222222222222222222222222222222222222222",
+        asset_info_hex,
+        "01",
+        "0000000000000001",
+        // non-fungible, contract principal, u7, sent
+        "02",
+        "031a2222222222222222222222222222222222222222057661756c74",
+        asset_info_hex,
+        "0100000000000000000000000000000007",
+        "10",
+        // payload: contract call
+        "02",
+        "1a3333333333333333333333333333333333333333",
+        "05746f6b656e",
+        "087472616e73666572",
+        "00000003",
+        "0100000000000000000000000000000001",
+        "061a2222222222222222222222222222222222222222057661756c74",
+        "0a0200000002dead",
+    ]
+    .concat();
+
+    assert_roundtrip(&tx, &expected);
+}
+
+#[test]
+fn test_versioned_smart_contract_roundtrip() {
+    let tx = testnet_tx(
+        TransactionAuth::Standard(singlesig_auth(5, 180)),
+        TransactionPayload::SmartContract(
+            TransactionSmartContract {
+                name: "hello".into(),
+                code_body: "(ok u1)".into(),
+            },
+            Some(ClarityVersion::Clarity2),
+        ),
+    );
+    let expected = [
+        STANDARD_SINGLESIG_HEADER,
+        "03",
+        "02",
+        "00000000",
+        "06",                     // payload: versioned smart contract
+        "02",                     // Clarity 2
+        "0568656c6c6f",           // name
+        "00000007286f6b20753129", // code body
+    ]
+    .concat();
+    assert_roundtrip(&tx, &expected);
+
+    // Unversioned contracts use the legacy payload type.
+    let mut legacy = tx.clone();
+    legacy.payload = TransactionPayload::SmartContract(
+        TransactionSmartContract {
+            name: "hello".into(),
+            code_body: "(ok u1)".into(),
+        },
+        None,
+    );
+    let bytes = legacy.serialize_to_vec().unwrap();
+    assert_eq!(bytes[bytes.len() - 18], 0x01);
+    assert_eq!(
+        StacksTransaction::deserialize_from_slice(&bytes).unwrap(),
+        legacy
+    );
+}
+
+#[test]
+fn test_sponsored_multisig_roundtrip() {
+    let pubkey = PublicKey::from_secret_key(&SecretKey::parse(&[0x42; 32]).unwrap());
+    let origin = TransactionSpendingCondition::Multisig(MultisigSpendingCondition {
+        hash_mode: MultisigHashMode::P2SH,
+        signer: Hash160([0x44; 20]),
+        nonce: 1,
+        tx_fee: 0,
+        fields: vec![
+            TransactionAuthField::Signature(
+                TransactionPublicKeyEncoding::Compressed,
+                MessageSignature([0x02; 65]),
+            ),
+            TransactionAuthField::PublicKey(TransactionPublicKeyEncoding::Uncompressed, pubkey),
+        ],
+        signatures_required: 1,
+    });
+    let sponsor = TransactionSpendingCondition::OrderIndependentMultisig(
+        OrderIndependentMultisigSpendingCondition {
+            hash_mode: OrderIndependentMultisigHashMode::P2WSH,
+            signer: Hash160([0x55; 20]),
+            nonce: 2,
+            tx_fee: 300,
+            fields: vec![TransactionAuthField::PublicKey(
+                TransactionPublicKeyEncoding::Compressed,
+                pubkey,
+            )],
+            signatures_required: 1,
+        },
+    );
+    let tx = testnet_tx(
+        TransactionAuth::Sponsored(origin, sponsor),
+        TransactionPayload::Coinbase(CoinbasePayload([0x66; 32]), None, None),
+    );
+
+    let pubkey_hex = hex::encode(pubkey.serialize_compressed());
+    let uncompressed_pubkey_hex = hex::encode(pubkey.serialize());
+    let expected = [
+        "80",
+        "80000000",
+        "05", // auth: sponsored
+        // origin: P2SH multisig
+        "01",
+        "4444444444444444444444444444444444444444",
+        "0000000000000001",
+        "0000000000000000",
+        "00000002",
+        "02",
+        &"02".repeat(65),
+        "01",
+        &uncompressed_pubkey_hex,
+        "0001",
+        // sponsor: order-independent P2WSH multisig
+        "07",
+        "5555555555555555555555555555555555555555",
+        "0000000000000002",
+        "000000000000012c",
+        "00000001",
+        "00",
+        &pubkey_hex,
+        "0001",
+        "03",
+        "02",
+        "00000000",
+        "04", // payload: coinbase
+        &"66".repeat(32),
+    ]
+    .concat();
+
+    assert_roundtrip(&tx, &expected);
+
+    // the P2WSH sponsor can't carry an uncompressed public key
+    let uncompressed_sponsor = expected.replacen(
+        &format!("00{pubkey_hex}"),
+        &format!("01{uncompressed_pubkey_hex}"),
+        1,
+    );
+    assert!(
+        StacksTransaction::deserialize_from_slice(&hex::decode(uncompressed_sponsor).unwrap())
+            .is_err()
+    );
+}
+
+#[test]
+fn test_coinbase_variants_roundtrip() {
+    let recipient = PrincipalData::Standard(StandardPrincipalData(26, [0x22; 20]));
+
+    let alt_recipient =
+        TransactionPayload::Coinbase(CoinbasePayload([0x66; 32]), Some(recipient.clone()), None);
+    let expected = [
+        "05",
+        &"66".repeat(32),
+        "051a2222222222222222222222222222222222222222",
+    ]
+    .concat();
+    assert_roundtrip(&alt_recipient, &expected);
+
+    let nakamoto = TransactionPayload::Coinbase(
+        CoinbasePayload([0x66; 32]),
+        Some(recipient),
+        Some(VRFProof([0x77; 80])),
+    );
+    let expected = [
+        "08",
+        &"66".repeat(32),
+        "0a051a2222222222222222222222222222222222222222", // (some 'ST...)
+        &"77".repeat(80),
+    ]
+    .concat();
+    assert_roundtrip(&nakamoto, &expected);
+
+    let nakamoto_no_recipient = TransactionPayload::Coinbase(
+        CoinbasePayload([0x66; 32]),
+        None,
+        Some(VRFProof([0x77; 80])),
+    );
+    let expected = ["08", &"66".repeat(32), "09", &"77".repeat(80)].concat();
+    assert_roundtrip(&nakamoto_no_recipient, &expected);
+}
+
+#[test]
+fn test_tenure_change_roundtrip() {
+    let payload = TransactionPayload::TenureChange(TenureChangePayload {
+        tenure_consensus_hash: ConsensusHash([0x01; 20]),
+        prev_tenure_consensus_hash: ConsensusHash([0x02; 20]),
+        burn_view_consensus_hash: ConsensusHash([0x03; 20]),
+        previous_tenure_end: StacksBlockId([0x04; 32]),
+        previous_tenure_blocks: 10,
+        cause: TenureChangeCause::Extended,
+        pubkey_hash: Hash160([0x05; 20]),
+    });
+    let expected = [
+        "07",
+        &"01".repeat(20),
+        &"02".repeat(20),
+        &"03".repeat(20),
+        &"04".repeat(32),
+        "0000000a",
+        "01",
+        &"05".repeat(20),
+    ]
+    .concat();
+    assert_roundtrip(&payload, &expected);
+}
+
+#[test]
+fn test_poison_microblock_roundtrip() {
+    let header = |sequence| StacksMicroblockHeader {
+        version: 0,
+        sequence,
+        prev_block: BlockHeaderHash([0x08; 32]),
+        tx_merkle_root: Sha512Trunc256Sum([0x09; 32]),
+        signature: MessageSignature([0x0a; 65]),
+    };
+    let payload = TransactionPayload::PoisonMicroblock(header(1), header(1));
+    let header_hex = [
+        "00",
+        "0001",
+        &"08".repeat(32),
+        &"09".repeat(32),
+        &"0a".repeat(65),
+    ]
+    .concat();
+    let expected = ["03", &header_hex, &header_hex].concat();
+    assert_roundtrip(&payload, &expected);
+}
+
+#[test]
+fn test_deserialize_rejects_malformed_transactions() {
+    let tx = testnet_tx(
+        TransactionAuth::Standard(singlesig_auth(5, 180)),
+        TransactionPayload::Coinbase(CoinbasePayload([0x66; 32]), None, None),
+    );
+    let bytes = tx.serialize_to_vec().unwrap();
+
+    // trailing bytes
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(StacksTransaction::deserialize_from_slice(&trailing).is_err());
+
+    // truncated
+    assert!(StacksTransaction::deserialize_from_slice(&bytes[..bytes.len() - 1]).is_err());
+
+    // unknown payload type
+    let mut bad_payload = bytes.clone();
+    let payload_offset = bytes.len() - 33;
+    bad_payload[payload_offset] = 0x42;
+    assert!(StacksTransaction::deserialize_from_slice(&bad_payload).is_err());
+
+    // invalid contract names can't be serialized
+    let mut bad_name = tx.clone();
+    bad_name.payload = TransactionPayload::SmartContract(
+        TransactionSmartContract {
+            name: "not a name".into(),
+            code_body: "".into(),
+        },
+        None,
+    );
+    assert!(bad_name.serialize_to_vec().is_err());
+}
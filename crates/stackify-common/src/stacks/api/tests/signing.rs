@@ -0,0 +1,296 @@
+use crate::stacks::{
+    api::{
+        clarity::{PrincipalData, StandardPrincipalData},
+        codec::StacksMessageCodec,
+        transactions::{
+            MultisigHashMode, MultisigSpendingCondition, OrderIndependentMultisigHashMode,
+            StacksTransaction, StacksTransactionSigner, TokenTransferMemo, TransactionAuth,
+            TransactionAuthField, TransactionPayload, TransactionSpendingCondition,
+            TransactionVersion,
+        },
+    },
+    encoded_public_keys_to_address_hash, StacksPrivateKey, TransactionPublicKeyEncoding,
+};
+
+fn privk(byte: u8) -> StacksPrivateKey {
+    StacksPrivateKey::from_hex(&format!("{}01", hex::encode([byte; 32]))).unwrap()
+}
+
+fn token_transfer(auth: TransactionAuth) -> StacksTransaction {
+    let mut tx = StacksTransaction::new(
+        TransactionVersion::Testnet,
+        auth,
+        TransactionPayload::TokenTransfer(
+            PrincipalData::Standard(StandardPrincipalData(26, [0x22; 20])),
+            1000,
+            TokenTransferMemo([0u8; 34]),
+        ),
+    );
+    tx.set_tx_fee(180);
+    tx.set_origin_nonce(3);
+    tx
+}
+
+#[test]
+fn test_private_key_hex() {
+    let compressed = privk(0x01);
+    assert!(compressed.compress_public());
+    assert_eq!(compressed.to_hex(), format!("{}01", "01".repeat(32)));
+
+    let uncompressed = StacksPrivateKey::from_hex(&"01".repeat(32)).unwrap();
+    assert!(!uncompressed.compress_public());
+    assert_eq!(uncompressed.to_hex(), "01".repeat(32));
+
+    assert!(StacksPrivateKey::from_hex(&format!("{}02", "01".repeat(32))).is_err());
+    assert!(StacksPrivateKey::from_hex("not hex").is_err());
+}
+
+#[test]
+fn test_sign_verify_singlesig() {
+    for key in [
+        privk(0x01),
+        StacksPrivateKey::from_hex(&"02".repeat(32)).unwrap(),
+    ] {
+        let tx = token_transfer(TransactionAuth::from_p2pkh(&key));
+
+        let mut signer = StacksTransactionSigner::new(&tx).unwrap();
+        signer.sign_origin(&key).unwrap();
+        let signed = signer.get_tx().expect("transaction should verify");
+
+        // signatures survive a serialization round-trip
+        let bytes = signed.serialize_to_vec().unwrap();
+        StacksTransaction::deserialize_from_slice(&bytes)
+            .unwrap()
+            .verify()
+            .unwrap();
+
+        // the fee and nonce are covered by the signature
+        let mut tampered = signed.clone();
+        tampered.set_tx_fee(1);
+        assert!(tampered.verify().is_err());
+
+        let mut tampered = signed.clone();
+        tampered.set_origin_nonce(4);
+        assert!(tampered.verify().is_err());
+    }
+}
+
+#[test]
+fn test_sign_verify_singlesig_p2wpkh() {
+    let key = privk(0x03);
+    let tx = token_transfer(TransactionAuth::Standard(
+        TransactionSpendingCondition::new_singlesig_p2wpkh(&key.public_key()),
+    ));
+
+    let mut signer = StacksTransactionSigner::new(&tx).unwrap();
+    signer.sign_origin(&key).unwrap();
+    assert!(signer.get_tx().is_some());
+
+    // signing with the wrong key produces a signer mismatch
+    let mut signer = StacksTransactionSigner::new(&tx).unwrap();
+    signer.sign_origin(&privk(0x04)).unwrap();
+    assert!(signer.get_tx().is_none());
+}
+
+#[test]
+fn test_sign_verify_multisig() {
+    let keys = [privk(0x05), privk(0x06), privk(0x07)];
+    let pubkeys = keys.iter().map(|k| k.public_key()).collect::<Vec<_>>();
+
+    for hash_mode in [MultisigHashMode::P2SH, MultisigHashMode::P2WSH] {
+        let tx = token_transfer(TransactionAuth::Standard(
+            TransactionSpendingCondition::new_multisig(hash_mode, 2, &pubkeys),
+        ));
+
+        let mut signer = StacksTransactionSigner::new(&tx).unwrap();
+        signer.sign_origin(&keys[0]).unwrap();
+        // not enough signatures yet
+        assert!(signer.get_tx().is_none());
+        signer.sign_origin(&keys[1]).unwrap();
+        signer
+            .append_origin(TransactionPublicKeyEncoding::Compressed, pubkeys[2])
+            .unwrap();
+        let signed = signer.get_tx().expect("transaction should verify");
+
+        let bytes = signed.serialize_to_vec().unwrap();
+        StacksTransaction::deserialize_from_slice(&bytes)
+            .unwrap()
+            .verify()
+            .unwrap();
+
+        // legacy multisig signatures are chained, so order matters
+        let mut signer = StacksTransactionSigner::new(&tx).unwrap();
+        signer.sign_origin(&keys[1]).unwrap();
+        signer.sign_origin(&keys[0]).unwrap();
+        signer
+            .append_origin(TransactionPublicKeyEncoding::Compressed, pubkeys[2])
+            .unwrap();
+        assert!(signer.get_tx().is_none());
+
+        // legacy multisig takes exactly the required number of signatures
+        let mut signer = StacksTransactionSigner::new(&tx).unwrap();
+        for key in &keys {
+            signer.sign_origin(key).unwrap();
+        }
+        assert!(signer.get_tx().is_none());
+    }
+}
+
+#[test]
+fn test_sign_verify_multisig_uncompressed() {
+    let keys = [
+        privk(0x05),
+        StacksPrivateKey::from_hex(&"06".repeat(32)).unwrap(),
+        privk(0x07),
+    ];
+    let pubkeys = keys
+        .iter()
+        .map(|k| (k.key_encoding(), k.public_key()))
+        .collect::<Vec<_>>();
+
+    for (hash_mode, verifies) in [
+        (MultisigHashMode::P2SH, true),
+        (MultisigHashMode::P2WSH, false),
+    ] {
+        let tx = token_transfer(TransactionAuth::Standard(
+            TransactionSpendingCondition::Multisig(MultisigSpendingCondition {
+                signer: encoded_public_keys_to_address_hash(
+                    &hash_mode.to_address_hash_mode(),
+                    2,
+                    &pubkeys,
+                ),
+                hash_mode,
+                nonce: 0,
+                tx_fee: 0,
+                fields: vec![],
+                signatures_required: 2,
+            }),
+        ));
+
+        let mut signer = StacksTransactionSigner::new(&tx).unwrap();
+        signer.sign_origin(&keys[0]).unwrap();
+        signer.sign_origin(&keys[1]).unwrap();
+        signer
+            .append_origin(TransactionPublicKeyEncoding::Compressed, pubkeys[2].1)
+            .unwrap();
+        // segwit multisig requires compressed public keys
+        assert_eq!(signer.get_tx().is_some(), verifies);
+    }
+}
+
+#[test]
+fn test_sign_verify_order_independent_multisig() {
+    let keys = [privk(0x08), privk(0x09), privk(0x0a)];
+    let pubkeys = keys.iter().map(|k| k.public_key()).collect::<Vec<_>>();
+
+    let tx = token_transfer(TransactionAuth::Standard(
+        TransactionSpendingCondition::new_order_independent_multisig(
+            OrderIndependentMultisigHashMode::P2SH,
+            2,
+            &pubkeys,
+        ),
+    ));
+
+    let mut signer = StacksTransactionSigner::new(&tx).unwrap();
+    signer
+        .append_origin(TransactionPublicKeyEncoding::Compressed, pubkeys[0])
+        .unwrap();
+    signer.sign_origin(&keys[1]).unwrap();
+    signer.sign_origin(&keys[2]).unwrap();
+    let signed = signer.get_tx().expect("transaction should verify");
+
+    // Each signer signs the initial sighash, so signatures can be produced
+    // independently of each other.
+    let initial_sighash = tx.sign_begin().unwrap();
+    let (sig, _) = TransactionSpendingCondition::next_signature(
+        &initial_sighash,
+        &crate::stacks::api::transactions::TransactionAuthFlags::AuthStandard,
+        180,
+        3,
+        &keys[2],
+    );
+    let TransactionAuth::Standard(TransactionSpendingCondition::OrderIndependentMultisig(cond)) =
+        &signed.auth
+    else {
+        panic!("expected an order-independent multisig origin");
+    };
+    assert_eq!(
+        cond.fields[2],
+        TransactionAuthField::Signature(TransactionPublicKeyEncoding::Compressed, sig)
+    );
+
+    // signatures beyond the required number are accepted
+    let mut signer = StacksTransactionSigner::new(&tx).unwrap();
+    for key in &keys {
+        signer.sign_origin(key).unwrap();
+    }
+    signer.get_tx().expect("transaction should verify");
+
+    // but not fewer
+    let mut signer = StacksTransactionSigner::new(&tx).unwrap();
+    signer.sign_origin(&keys[0]).unwrap();
+    signer
+        .append_origin(TransactionPublicKeyEncoding::Compressed, pubkeys[1])
+        .unwrap();
+    signer
+        .append_origin(TransactionPublicKeyEncoding::Compressed, pubkeys[2])
+        .unwrap();
+    assert!(signer.get_tx().is_none());
+}
+
+#[test]
+fn test_sign_verify_sponsored() {
+    let origin_key = privk(0x0b);
+    let sponsor_keys = [privk(0x0c), privk(0x0d)];
+    let sponsor_pubkeys = sponsor_keys
+        .iter()
+        .map(|k| k.public_key())
+        .collect::<Vec<_>>();
+
+    let origin = TransactionSpendingCondition::new_singlesig_p2pkh(
+        &origin_key.public_key(),
+        TransactionPublicKeyEncoding::Compressed,
+    );
+    let mut tx = StacksTransaction::new(
+        TransactionVersion::Testnet,
+        TransactionAuth::Sponsored(origin, TransactionSpendingCondition::new_initial_sighash()),
+        TransactionPayload::TokenTransfer(
+            PrincipalData::Standard(StandardPrincipalData(26, [0x22; 20])),
+            1000,
+            TokenTransferMemo([0u8; 34]),
+        ),
+    );
+    tx.set_origin_nonce(1);
+
+    // The origin signs first, without knowing who the sponsor will be.
+    let mut signer = StacksTransactionSigner::new(&tx).unwrap();
+    signer.sign_origin(&origin_key).unwrap();
+    let origin_signed = signer.get_tx_incomplete();
+    origin_signed.verify_origin().unwrap();
+
+    // The sponsor then sets its own spending condition (including the fee)
+    // and signs over the origin's signature.
+    let mut sponsor =
+        TransactionSpendingCondition::new_multisig(MultisigHashMode::P2SH, 1, &sponsor_pubkeys);
+    sponsor.set_nonce(7);
+    sponsor.set_tx_fee(300);
+    let mut signer = StacksTransactionSigner::new_sponsor(&origin_signed, sponsor).unwrap();
+    signer.sign_sponsor(&sponsor_keys[0]).unwrap();
+    signer
+        .append_sponsor(TransactionPublicKeyEncoding::Compressed, sponsor_pubkeys[1])
+        .unwrap();
+    let signed = signer.get_tx().expect("transaction should verify");
+    assert_eq!(signed.get_tx_fee(), 300);
+
+    let mut tampered = signed.clone();
+    tampered.set_tx_fee(1);
+    assert!(tampered.verify().is_err());
+
+    // sponsors can't sign standard transactions
+    let standard = token_transfer(TransactionAuth::from_p2pkh(&origin_key));
+    assert!(StacksTransactionSigner::new_sponsor(
+        &standard,
+        TransactionSpendingCondition::new_initial_sighash()
+    )
+    .is_err());
+}
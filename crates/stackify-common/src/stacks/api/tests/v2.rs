@@ -1,2 +1,234 @@
 mod data_var;
 mod transactions;
+
+use crate::stacks::{
+    api::{
+        clarity::{
+            BufferLength, PrincipalData, QualifiedContractIdentifier, SequenceSubtype,
+            StandardPrincipalData, StringSubtype, TypeSignature,
+        },
+        v2::{ContractInterfaceAtomType, ContractInterfaceFunctionAccess},
+    },
+    StacksAddress,
+};
+
+use super::{mock_json, mock_node};
+
+fn contract(name: &str) -> QualifiedContractIdentifier {
+    QualifiedContractIdentifier::new(StandardPrincipalData(26, [0x11; 20]), name).unwrap()
+}
+
+#[tokio::test]
+async fn test_get_info() {
+    let (mut server, client) = mock_node().await;
+    let mock = mock_json(
+        &mut server,
+        "GET",
+        "/v2/info",
+        r#"{
+            "peer_version": 4207599113,
+            "pox_consensus": "17f76e597bab45646956f38dd39573085d72cbc0",
+            "burn_block_height": 108,
+            "stable_pox_consensus": "8e7b63ebc2bb4bdf1b48f8d2d2b6a7fa78c4a7d9",
+            "stable_burn_block_height": 101,
+            "server_version": "stacks-node 0.0.1 (:, release build, linux [x86_64])",
+            "network_id": 2147483648,
+            "parent_network_id": 3669344250,
+            "stacks_tip_height": 7,
+            "stacks_tip": "b1807a2d3f7f8c7922f7c1d60d7c34145ade05d789640dc7dc9ec1021e07bb54",
+            "stacks_tip_consensus_hash": "17f76e597bab45646956f38dd39573085d72cbc0",
+            "genesis_chainstate_hash": "74237aa39aa50a83de11a4f53e9d3bb7d43461d1de9873f402e5453ae60bc59b",
+            "unanchored_tip": null,
+            "unanchored_seq": null,
+            "exit_at_block_height": null,
+            "is_fully_synced": true,
+            "node_public_key": "023b2e9ce9e8a1e1e3b2c8e6c6a7b8e3a7b8e3a7b8e3a7b8e3a7b8e3a7b8e3a7b8",
+            "node_public_key_hash": "a4d3f0b4b7f1f5c7b4b7f1f5c7b4b7f1f5c7b4b7",
+            "affirmations": {"heaviest": "", "stacks_tip": "", "sortition_tip": "", "tentative_best": ""},
+            "last_pox_anchor": {"anchor_block_hash": "", "anchor_block_txid": ""},
+            "stackerdbs": [],
+            "tenure_height": 5
+        }"#,
+    )
+    .await;
+
+    let info = client.get_info().await.unwrap();
+    mock.assert_async().await;
+    assert_eq!(info.burn_block_height, 108);
+    assert_eq!(info.stacks_tip_height, 7);
+    assert_eq!(info.network_id, 0x80000000);
+    assert_eq!(info.tenure_height, Some(5));
+    assert_eq!(info.is_fully_synced, Some(true));
+}
+
+#[tokio::test]
+async fn test_get_pox_info() {
+    let (mut server, client) = mock_node().await;
+    mock_json(
+        &mut server,
+        "GET",
+        "/v2/pox",
+        r#"{
+            "contract_id": "ST000000000000000000002AMW42H.pox-4",
+            "pox_activation_threshold_ustx": 700073322473389,
+            "first_burnchain_block_height": 0,
+            "current_burnchain_block_height": 108,
+            "prepare_phase_block_length": 1,
+            "reward_phase_block_length": 4,
+            "reward_slots": 8,
+            "rejection_fraction": null,
+            "total_liquid_supply_ustx": 70007332247338900,
+            "current_cycle": {
+                "id": 21,
+                "min_threshold_ustx": 583400000000,
+                "stacked_ustx": 0,
+                "is_pox_active": false
+            },
+            "next_cycle": {
+                "id": 22,
+                "min_threshold_ustx": 583400000000,
+                "min_increment_ustx": 8750916530917,
+                "stacked_ustx": 0,
+                "prepare_phase_start_block_height": 109,
+                "blocks_until_prepare_phase": 1,
+                "reward_phase_start_block_height": 110,
+                "blocks_until_reward_phase": 2,
+                "ustx_until_pox_rejection": null
+            },
+            "min_amount_ustx": 583400000000,
+            "prepare_cycle_length": 1,
+            "reward_cycle_id": 21,
+            "reward_cycle_length": 5,
+            "rejection_votes_left_required": null,
+            "next_reward_cycle_in": 2,
+            "contract_versions": [
+                {
+                    "contract_id": "ST000000000000000000002AMW42H.pox-4",
+                    "activation_burnchain_block_height": 107,
+                    "first_reward_cycle_id": 22
+                }
+            ]
+        }"#,
+    )
+    .await;
+
+    let pox = client.get_pox_info().await.unwrap();
+    assert_eq!(pox.reward_cycle_id, 21);
+    assert_eq!(pox.next_cycle.blocks_until_prepare_phase, 1);
+    assert!(!pox.current_cycle.is_pox_active);
+    assert_eq!(pox.contract_versions[0].first_reward_cycle_id, 22);
+}
+
+#[tokio::test]
+async fn test_get_account() {
+    let (mut server, client) = mock_node().await;
+    let addr = StacksAddress::new(26, crate::stacks::api::hash::Hash160([0x22; 20]));
+    let mock = mock_json(
+        &mut server,
+        "GET",
+        format!("/v2/accounts/{addr}?proof=0").as_str(),
+        r#"{
+            "balance": "0x0000000000000000000000003b9aca00",
+            "locked": "0x00000000000000000000000000000000",
+            "unlock_height": 0,
+            "nonce": 3
+        }"#,
+    )
+    .await;
+
+    let account = client
+        .get_account(&PrincipalData::from(addr))
+        .await
+        .unwrap();
+    mock.assert_async().await;
+    assert_eq!(account.balance, 1_000_000_000);
+    assert_eq!(account.locked, 0);
+    assert_eq!(account.nonce, 3);
+}
+
+#[tokio::test]
+async fn test_get_contract_interface() {
+    let (mut server, client) = mock_node().await;
+    let contract = contract("counter");
+    mock_json(
+        &mut server,
+        "GET",
+        format!("/v2/contracts/interface/{}/counter", contract.issuer).as_str(),
+        r#"{
+            "functions": [
+                {
+                    "name": "increment",
+                    "access": "public",
+                    "args": [
+                        {"name": "by", "type": "uint128"},
+                        {"name": "memo", "type": {"optional": {"buffer": {"length": 34}}}},
+                        {"name": "token", "type": "trait_reference"}
+                    ],
+                    "outputs": {"type": {"response": {"ok": "bool", "error": "uint128"}}}
+                },
+                {
+                    "name": "get-count",
+                    "access": "read_only",
+                    "args": [],
+                    "outputs": {"type": {"tuple": [
+                        {"name": "count", "type": "uint128"},
+                        {"name": "names", "type": {"list": {"type": {"string_ascii": {"length": 10}}, "length": 5}}}
+                    ]}}
+                }
+            ],
+            "variables": [{"name": "count", "access": "variable", "type": "uint128"}],
+            "maps": [{"name": "owners", "key": "principal", "value": {"string_utf8": {"length": 20}}}],
+            "fungible_tokens": [],
+            "non_fungible_tokens": [{"name": "nft", "type": "uint128"}],
+            "epoch": "Epoch25",
+            "clarity_version": "Clarity2"
+        }"#,
+    )
+    .await;
+
+    let interface = client.get_contract_interface(&contract).await.unwrap();
+    assert_eq!(interface.clarity_version, "Clarity2");
+    assert_eq!(interface.maps[0].key, ContractInterfaceAtomType::Principal);
+
+    let increment = interface.function("increment").unwrap();
+    assert_eq!(increment.access, ContractInterfaceFunctionAccess::Public);
+    let arg_types = increment
+        .args
+        .iter()
+        .map(|arg| TypeSignature::try_from(&arg.arg_type).unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(arg_types, ["uint", "(optional (buff 34))", "principal"]);
+
+    let get_count = interface.function("get-count").unwrap();
+    assert_eq!(get_count.access, ContractInterfaceFunctionAccess::ReadOnly);
+    assert_eq!(
+        TypeSignature::try_from(&get_count.outputs.output_type)
+            .unwrap()
+            .to_string(),
+        "(tuple (count uint) (names (list 5 (string-ascii 10))))"
+    );
+    assert_eq!(
+        TypeSignature::try_from(&ContractInterfaceAtomType::StringAscii { length: 3 }).unwrap(),
+        TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+            BufferLength::try_from(3u32).unwrap()
+        )))
+    );
+}
+
+#[tokio::test]
+async fn test_http_errors() {
+    let (mut server, client) = mock_node().await;
+    server
+        .mock("GET", "/v2/info")
+        .with_status(503)
+        .with_body("node is starting")
+        .create_async()
+        .await;
+
+    match client.get_info().await {
+        Err(crate::stacks::api::errors::Error::HttpStatus(503, body)) => {
+            assert_eq!(body, "node is starting")
+        }
+        other => panic!("expected an HTTP 503 error, got {other:?}"),
+    }
+}
@@ -1 +1,110 @@
+use mockito::Matcher;
 
+use crate::stacks::api::{
+    clarity::{PrincipalData, StandardPrincipalData, Value},
+    errors::Error,
+};
+
+use super::{contract, mock_json, mock_node};
+
+fn hex(value: &Value) -> String {
+    format!("0x{}", value.serialize_to_hex().unwrap())
+}
+
+#[tokio::test]
+async fn test_get_data_var() {
+    let (mut server, client) = mock_node().await;
+    let contract = contract("counter");
+    let mock = mock_json(
+        &mut server,
+        "GET",
+        format!("/v2/data_var/{}/counter/count?proof=0", contract.issuer).as_str(),
+        &format!(r#"{{"data": "{}"}}"#, hex(&Value::UInt(42))),
+    )
+    .await;
+
+    let value = client.get_data_var(&contract, "count").await.unwrap();
+    mock.assert_async().await;
+    assert_eq!(value, Value::UInt(42));
+}
+
+#[tokio::test]
+async fn test_get_map_entry() {
+    let (mut server, client) = mock_node().await;
+    let contract = contract("registry");
+    let key = Value::tuple_from_data(vec![("id".into(), Value::UInt(1))]).unwrap();
+    let entry = Value::some(Value::string_ascii_from_bytes(b"alice".to_vec()).unwrap());
+
+    let mock = server
+        .mock(
+            "POST",
+            format!("/v2/map_entry/{}/registry/names?proof=0", contract.issuer).as_str(),
+        )
+        .match_body(Matcher::Json(serde_json::Value::String(hex(&key))))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(format!(r#"{{"data": "{}"}}"#, hex(&entry)))
+        .create_async()
+        .await;
+
+    let value = client
+        .get_map_entry(&contract, "names", &key)
+        .await
+        .unwrap();
+    mock.assert_async().await;
+    assert_eq!(value, entry);
+    assert_eq!(value.to_string(), r#"(some "alice")"#);
+}
+
+#[tokio::test]
+async fn test_call_read_only() {
+    let (mut server, client) = mock_node().await;
+    let contract = contract("counter");
+    let sender = PrincipalData::Standard(StandardPrincipalData(26, [0x33; 20]));
+    let path = format!(
+        "/v2/contracts/call-read/{}/counter/get-count",
+        contract.issuer
+    );
+
+    let mock = server
+        .mock("POST", path.as_str())
+        .match_body(Matcher::Json(serde_json::json!({
+            "sender": sender.to_string(),
+            "arguments": [hex(&Value::UInt(1)), hex(&Value::Bool(true))],
+        })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(format!(
+            r#"{{"okay": true, "result": "{}"}}"#,
+            hex(&Value::okay(Value::UInt(7)))
+        ))
+        .create_async()
+        .await;
+
+    let result = client
+        .call_read_only(
+            &contract,
+            "get-count",
+            &sender,
+            &[Value::UInt(1), Value::Bool(true)],
+        )
+        .await
+        .unwrap();
+    mock.assert_async().await;
+    assert_eq!(result.to_string(), "(ok u7)");
+
+    mock_json(
+        &mut server,
+        "POST",
+        &path,
+        r#"{"okay": false, "cause": "Unchecked(NoSuchContract(\"counter\"))"}"#,
+    )
+    .await;
+    match client
+        .call_read_only(&contract, "get-count", &sender, &[])
+        .await
+    {
+        Err(Error::RpcError(cause)) => assert!(cause.contains("NoSuchContract")),
+        other => panic!("expected an RPC error, got {other:?}"),
+    }
+}
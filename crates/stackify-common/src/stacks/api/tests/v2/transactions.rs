@@ -1 +1,67 @@
+use mockito::Matcher;
 
+use crate::stacks::{
+    api::{
+        codec::StacksMessageCodec,
+        errors::Error,
+        transactions::{StacksTransaction, StacksTransactionBuilder},
+    },
+    StacksAddress, StacksPrivateKey, CHAIN_ID_TESTNET,
+};
+
+use super::{mock_json, mock_node};
+
+fn signed_transfer() -> StacksTransaction {
+    let privk = StacksPrivateKey::from_hex(&format!("{}01", "01".repeat(32))).unwrap();
+    let recipient = StacksAddress::p2pkh(false, &privk.public_key());
+    StacksTransactionBuilder::new(privk, CHAIN_ID_TESTNET)
+        .fee(180)
+        .token_transfer(recipient.into(), 1_000)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_broadcast_transaction() {
+    let (mut server, client) = mock_node().await;
+    let tx = signed_transfer();
+    let txid = tx.txid().unwrap();
+
+    let mock = server
+        .mock("POST", "/v2/transactions")
+        .match_header("content-type", "application/octet-stream")
+        .match_body(Matcher::from(tx.serialize_to_vec().unwrap()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(format!(r#""{txid}""#))
+        .create_async()
+        .await;
+
+    assert_eq!(client.broadcast_transaction(&tx).await.unwrap(), txid);
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_broadcast_transaction_rejected() {
+    let (mut server, client) = mock_node().await;
+    let tx = signed_transfer();
+
+    server
+        .mock("POST", "/v2/transactions")
+        .with_status(400)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error": "transaction rejected", "reason": "BadNonce"}"#)
+        .create_async()
+        .await;
+
+    match client.broadcast_transaction(&tx).await {
+        Err(Error::HttpStatus(400, body)) => assert!(body.contains("BadNonce")),
+        other => panic!("expected a rejection, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_get_transfer_fee_rate() {
+    let (mut server, client) = mock_node().await;
+    mock_json(&mut server, "GET", "/v2/fees/transfer", "1").await;
+    assert_eq!(client.get_transfer_fee_rate().await.unwrap(), 1);
+}
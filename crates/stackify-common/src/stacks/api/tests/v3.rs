@@ -1 +1,367 @@
+use mockito::Matcher;
 
+use crate::stacks::{
+    api::{
+        codec::StacksMessageCodec,
+        errors::Error,
+        hash::Hash160,
+        transactions::{
+            StacksTransactionBuilder, TenureChangeCause, TenureChangePayload, TransactionPayload,
+        },
+        v3::{BitVec, NakamotoBlock, NakamotoBlockHeader, SortitionQuery},
+    },
+    ConsensusHash, MessageSignature, Sha512Trunc256Sum, StacksBlockId, StacksPrivateKey, TrieHash,
+    CHAIN_ID_TESTNET,
+};
+
+use super::{mock_json, mock_node};
+
+fn privk(byte: u8) -> StacksPrivateKey {
+    StacksPrivateKey::from_hex(&format!("{}01", hex::encode([byte; 32]))).unwrap()
+}
+
+fn tenure_change() -> TenureChangePayload {
+    TenureChangePayload {
+        tenure_consensus_hash: ConsensusHash([0x01; 20]),
+        prev_tenure_consensus_hash: ConsensusHash([0x02; 20]),
+        burn_view_consensus_hash: ConsensusHash([0x01; 20]),
+        previous_tenure_end: StacksBlockId([0x03; 32]),
+        previous_tenure_blocks: 5,
+        cause: TenureChangeCause::BlockFound,
+        pubkey_hash: Hash160([0x04; 20]),
+    }
+}
+
+/// A block signed by a miner (key `0x01..`) and two signers (keys `0x02..`
+/// and `0x03..`).
+fn signed_block(chain_length: u64) -> NakamotoBlock {
+    let tx = StacksTransactionBuilder::new(privk(1), CHAIN_ID_TESTNET)
+        .build(TransactionPayload::TenureChange(tenure_change()))
+        .unwrap();
+
+    let mut pox_treatment = BitVec::zeros(2).unwrap();
+    pox_treatment.set(1, true).unwrap();
+
+    let mut header = NakamotoBlockHeader {
+        version: 0,
+        chain_length,
+        burn_spent: 20_000,
+        consensus_hash: ConsensusHash([0x01; 20]),
+        parent_block_id: StacksBlockId([0x03; 32]),
+        tx_merkle_root: Sha512Trunc256Sum([0x05; 32]),
+        state_index_root: TrieHash([0x06; 32]),
+        timestamp: 1_718_000_000,
+        miner_signature: MessageSignature::empty(),
+        signer_signature: vec![],
+        pox_treatment,
+    };
+    header.miner_signature = privk(1).sign(&header.miner_signature_hash().unwrap().0);
+    let signer_hash = header.signer_signature_hash().unwrap();
+    header.signer_signature = vec![privk(2).sign(&signer_hash.0), privk(3).sign(&signer_hash.0)];
+
+    NakamotoBlock {
+        header,
+        txs: vec![tx],
+    }
+}
+
+#[test]
+fn test_bitvec_codec() {
+    let mut bits = BitVec::zeros(10).unwrap();
+    bits.set(0, true).unwrap();
+    bits.set(9, true).unwrap();
+    assert_eq!(bits.get(0), Some(true));
+    assert_eq!(bits.get(1), Some(false));
+    assert_eq!(bits.get(10), None);
+    assert!(bits.set(10, true).is_err());
+
+    let bytes = bits.serialize_to_vec().unwrap();
+    assert_eq!(hex::encode(&bytes), "000a000000020102");
+    assert_eq!(BitVec::deserialize_from_slice(&bytes).unwrap(), bits);
+
+    // The data must be exactly as long as the bit length requires.
+    assert!(BitVec::deserialize_from_slice(&hex::decode("000a00000001ff").unwrap()).is_err());
+    assert!(BitVec::deserialize_from_slice(&hex::decode("000000000000").unwrap()).is_err());
+    assert!(BitVec::zeros(0).is_err());
+}
+
+#[test]
+fn test_nakamoto_block_roundtrip() {
+    let block = signed_block(10);
+    let bytes = block.serialize_to_vec().unwrap();
+    let decoded = NakamotoBlock::deserialize_from_slice(&bytes).unwrap();
+    assert_eq!(decoded, block);
+    assert_eq!(decoded.header.signer_signature.len(), 2);
+    assert_eq!(decoded.tenure_change(), Some(&tenure_change()));
+
+    // Truncated blocks are rejected.
+    assert!(NakamotoBlock::deserialize_from_slice(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn test_nakamoto_block_signatures() {
+    let header = signed_block(10).header;
+
+    assert_eq!(header.recover_miner_key().unwrap(), privk(1).public_key());
+    assert_eq!(
+        header.recover_signer_keys().unwrap(),
+        vec![privk(2).public_key(), privk(3).public_key()]
+    );
+
+    // The block hash covers the miner's signature, but not the signers'.
+    let mut unsigned = header.clone();
+    unsigned.signer_signature.clear();
+    assert_eq!(unsigned.block_hash().unwrap(), header.block_hash().unwrap());
+    unsigned.miner_signature = MessageSignature::empty();
+    assert_ne!(unsigned.block_hash().unwrap(), header.block_hash().unwrap());
+    assert_eq!(
+        unsigned.miner_signature_hash().unwrap(),
+        header.miner_signature_hash().unwrap()
+    );
+
+    let block_hash = header.block_hash().unwrap();
+    let expected = Sha512Trunc256Sum::from_data(&[block_hash.0.as_slice(), &[0x01; 20]].concat());
+    assert_eq!(header.block_id().unwrap(), StacksBlockId(expected.0));
+}
+
+#[test]
+fn test_block_id_hex() {
+    let id = StacksBlockId([0xab; 32]);
+    assert_eq!(id.to_string(), "ab".repeat(32));
+    assert_eq!(id.to_string().parse::<StacksBlockId>().unwrap(), id);
+    assert_eq!(
+        format!("0x{id}").parse::<StacksBlockId>().unwrap(),
+        StacksBlockId([0xab; 32])
+    );
+    assert!(matches!(
+        "abcd".parse::<StacksBlockId>(),
+        Err(Error::InvalidLength(2))
+    ));
+}
+
+#[tokio::test]
+async fn test_get_nakamoto_block() {
+    let (mut server, client) = mock_node().await;
+    let block = signed_block(10);
+    let block_id = block.header.block_id().unwrap();
+
+    let mock = server
+        .mock("GET", format!("/v3/blocks/{block_id}").as_str())
+        .with_status(200)
+        .with_header("content-type", "application/octet-stream")
+        .with_body(block.serialize_to_vec().unwrap())
+        .create_async()
+        .await;
+
+    assert_eq!(client.get_nakamoto_block(&block_id).await.unwrap(), block);
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_get_tenure_blocks() {
+    let (mut server, client) = mock_node().await;
+    let blocks = vec![signed_block(11), signed_block(10)];
+    let tip = blocks[0].header.block_id().unwrap();
+    let stop = StacksBlockId([0x03; 32]);
+
+    let mut body = vec![];
+    for block in &blocks {
+        body.extend(block.serialize_to_vec().unwrap());
+    }
+    let mock = server
+        .mock("GET", format!("/v3/tenures/{tip}").as_str())
+        .match_query(Matcher::UrlEncoded("stop".into(), stop.to_string()))
+        .with_status(200)
+        .with_header("content-type", "application/octet-stream")
+        .with_body(body)
+        .create_async()
+        .await;
+
+    assert_eq!(
+        client.get_tenure_blocks(&tip, Some(&stop)).await.unwrap(),
+        blocks
+    );
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_get_tenure_info() {
+    let (mut server, client) = mock_node().await;
+    mock_json(
+        &mut server,
+        "GET",
+        "/v3/tenures/info",
+        &format!(
+            r#"{{
+                "consensus_hash": "{}",
+                "tenure_start_block_id": "{}",
+                "parent_consensus_hash": "{}",
+                "parent_tenure_start_block_id": "{}",
+                "tip_block_id": "{}",
+                "tip_height": 42,
+                "reward_cycle": 12
+            }}"#,
+            "01".repeat(20),
+            "02".repeat(32),
+            "03".repeat(20),
+            "04".repeat(32),
+            "05".repeat(32)
+        ),
+    )
+    .await;
+
+    let info = client.get_tenure_info().await.unwrap();
+    assert_eq!(info.consensus_hash, ConsensusHash([0x01; 20]));
+    assert_eq!(info.parent_tenure_start_block_id, StacksBlockId([0x04; 32]));
+    assert_eq!(info.tip_block_id, StacksBlockId([0x05; 32]));
+    assert_eq!(info.tip_height, 42);
+    assert_eq!(info.reward_cycle, 12);
+}
+
+#[tokio::test]
+async fn test_get_sortitions() {
+    let (mut server, client) = mock_node().await;
+    let body = format!(
+        r#"[
+            {{
+                "burn_block_hash": "0x{0}",
+                "burn_block_height": 230,
+                "burn_header_timestamp": 1718000000,
+                "sortition_id": "0x{0}",
+                "parent_sortition_id": "0x{0}",
+                "consensus_hash": "0x{1}",
+                "was_sortition": true,
+                "miner_pk_hash160": "0x{2}",
+                "stacks_parent_ch": "0x{2}",
+                "last_sortition_ch": "0x{2}",
+                "committed_block_hash": "0x{0}",
+                "vrf_seed": "0x{0}"
+            }},
+            {{
+                "burn_block_hash": "0x{0}",
+                "burn_block_height": 229,
+                "burn_header_timestamp": 1717999000,
+                "sortition_id": "0x{0}",
+                "parent_sortition_id": "0x{0}",
+                "consensus_hash": "0x{2}",
+                "was_sortition": false,
+                "miner_pk_hash160": null,
+                "stacks_parent_ch": null,
+                "last_sortition_ch": null,
+                "committed_block_hash": null
+            }}
+        ]"#,
+        "aa".repeat(32),
+        "bb".repeat(20),
+        "cc".repeat(20)
+    );
+    let mock = mock_json(&mut server, "GET", "/v3/sortitions/latest_and_last", &body).await;
+
+    let sortitions = client
+        .get_sortitions(&SortitionQuery::LatestAndLast)
+        .await
+        .unwrap();
+    mock.assert_async().await;
+    assert_eq!(sortitions.len(), 2);
+    assert_eq!(sortitions[0].consensus_hash, ConsensusHash([0xbb; 20]));
+    assert_eq!(
+        sortitions[0].stacks_parent_ch,
+        Some(ConsensusHash([0xcc; 20]))
+    );
+    assert!(!sortitions[1].was_sortition);
+    assert_eq!(sortitions[1].committed_block_hash, None);
+    assert_eq!(sortitions[1].vrf_seed, None);
+
+    let mock = mock_json(&mut server, "GET", "/v3/sortitions/burn_height/229", "[]").await;
+    assert!(client
+        .get_sortitions(&SortitionQuery::BurnHeight(229))
+        .await
+        .unwrap()
+        .is_empty());
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_get_signer_blocks_signed() {
+    let (mut server, client) = mock_node().await;
+    let key = privk(2).public_key();
+    mock_json(
+        &mut server,
+        "GET",
+        format!("/v3/signer/{}/12", hex::encode(key.serialize_compressed())).as_str(),
+        r#"{"blocks_signed": 7}"#,
+    )
+    .await;
+
+    assert_eq!(client.get_signer_blocks_signed(&key, 12).await.unwrap(), 7);
+}
+
+#[tokio::test]
+async fn test_get_stacker_set() {
+    let (mut server, client) = mock_node().await;
+    let key = privk(2).public_key();
+    mock_json(
+        &mut server,
+        "GET",
+        "/v3/stacker_set/12",
+        format!(
+            r#"{{
+                "stacker_set": {{
+                    "rewarded_addresses": [],
+                    "start_cycle_state": {{"missed_reward_slots": []}},
+                    "pox_ticket_threshold": 100,
+                    "signers": [
+                        {{"signing_key": "0x{}", "weight": 3, "stacked_amt": 300}}
+                    ]
+                }}
+            }}"#,
+            hex::encode(key.serialize_compressed())
+        )
+        .as_str(),
+    )
+    .await;
+
+    let set = client.get_stacker_set(12).await.unwrap();
+    let signer = set.signer(&key).unwrap();
+    assert_eq!(signer.weight, 3);
+    assert_eq!(signer.stacked_amt, 300);
+    assert!(set.signer(&privk(3).public_key()).is_none());
+}
+
+#[tokio::test]
+async fn test_propose_block() {
+    let (mut server, client) = mock_node().await;
+    let block = signed_block(10);
+
+    let mock = server
+        .mock("POST", "/v3/block_proposal")
+        .match_header("authorization", "secret")
+        .match_body(Matcher::Json(serde_json::json!({
+            "block": hex::encode(block.serialize_to_vec().unwrap()),
+            "chain_id": CHAIN_ID_TESTNET,
+        })))
+        .with_status(202)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"result": "Accepted", "message": "Block proposal is processing"}"#)
+        .create_async()
+        .await;
+
+    let response = client
+        .propose_block(&block, CHAIN_ID_TESTNET, "secret")
+        .await
+        .unwrap();
+    mock.assert_async().await;
+    assert_eq!(response.result, "Accepted");
+
+    server
+        .mock("POST", "/v3/block_proposal")
+        .with_status(401)
+        .create_async()
+        .await;
+    assert!(matches!(
+        client
+            .propose_block(&block, CHAIN_ID_TESTNET, "wrong")
+            .await,
+        Err(Error::HttpStatus(401, _))
+    ));
+}
@@ -9,17 +9,25 @@ use serde_big_array::BigArray;
 
 use crate::stacks::{
     BlockHeaderHash, ConsensusHash, MessageSignature, Sha512Trunc256Sum, StacksAddress,
-    StacksBlockId, TransactionPublicKeyEncoding, C32_ADDRESS_VERSION_MAINNET_MULTISIG,
-    C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_MULTISIG,
-    C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+    StacksBlockId, TransactionPublicKeyEncoding, Txid, VRFProof,
+    C32_ADDRESS_VERSION_MAINNET_MULTISIG, C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+    C32_ADDRESS_VERSION_TESTNET_MULTISIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
 
 use super::{
     clarity::{ClarityVersion, PrincipalData, Value},
+    codec::StacksMessageCodec,
     errors::Error,
     hash::Hash160,
 };
 
+mod auth;
+mod builder;
+mod codec;
+
+pub use auth::StacksTransactionSigner;
+pub use builder::StacksTransactionBuilder;
+
 /// Post-condition modes for unspecified assets
 #[repr(u8)]
 #[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
@@ -28,6 +36,14 @@ pub enum TransactionPostConditionMode {
     Deny = 0x02,  // deny any other changes not specified
 }
 
+/// Transaction authorization types
+#[repr(u8)]
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
+pub enum TransactionAuthFlags {
+    AuthStandard = 0x04,
+    AuthSponsored = 0x05,
+}
+
 /// Stacks transaction versions
 #[repr(u8)]
 #[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
@@ -206,7 +222,7 @@ impl OrderIndependentMultisigHashMode {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TransactionAuthField {
-    PublicKey(PublicKey),
+    PublicKey(TransactionPublicKeyEncoding, PublicKey),
     Signature(TransactionPublicKeyEncoding, MessageSignature),
 }
 
@@ -383,7 +399,7 @@ pub enum TransactionPayload {
     SmartContract(TransactionSmartContract, Option<ClarityVersion>),
     // the previous epoch leader sent two microblocks with the same sequence, and this is proof
     PoisonMicroblock(StacksMicroblockHeader, StacksMicroblockHeader),
-    Coinbase(CoinbasePayload, Option<PrincipalData>, Option<String>),
+    Coinbase(CoinbasePayload, Option<PrincipalData>, Option<VRFProof>),
     TenureChange(TenureChangePayload),
 }
 
@@ -397,3 +413,11 @@ pub struct StacksTransaction {
     pub post_conditions: Vec<TransactionPostCondition>,
     pub payload: TransactionPayload,
 }
+
+impl StacksTransaction {
+    /// The transaction ID, i.e. the SHA512/256 hash of the consensus-serialized
+    /// transaction.
+    pub fn txid(&self) -> Result<Txid, Error> {
+        Ok(Txid::from_data(&self.serialize_to_vec()?))
+    }
+}
@@ -0,0 +1,824 @@
+//! Transaction signing and signature verification.
+//!
+//! Signatures are chained: each signer signs a "presign" sighash computed
+//! from the previous sighash, the auth flag, fee and nonce, and the next
+//! signer continues from a "postsign" sighash which also commits to the
+//! previous signature. Order-independent multisig signers all sign the same
+//! initial sighash instead.
+
+use libsecp256k1::PublicKey;
+
+use crate::stacks::{
+    api::{errors::Error, hash::Hash160},
+    encoded_public_keys_to_address_hash, public_keys_to_address_hash, MessageSignature,
+    StacksPrivateKey, TransactionPublicKeyEncoding, Txid,
+};
+
+use super::*;
+
+impl TransactionSpendingCondition {
+    pub fn new_singlesig_p2pkh(
+        pubkey: &PublicKey,
+        key_encoding: TransactionPublicKeyEncoding,
+    ) -> TransactionSpendingCondition {
+        TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+            signer: singlesig_signer_hash(&SinglesigHashMode::P2PKH, pubkey, key_encoding),
+            hash_mode: SinglesigHashMode::P2PKH,
+            nonce: 0,
+            tx_fee: 0,
+            key_encoding,
+            signature: MessageSignature::empty(),
+        })
+    }
+
+    pub fn new_singlesig_p2wpkh(pubkey: &PublicKey) -> TransactionSpendingCondition {
+        let key_encoding = TransactionPublicKeyEncoding::Compressed;
+        TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+            signer: singlesig_signer_hash(&SinglesigHashMode::P2WPKH, pubkey, key_encoding),
+            hash_mode: SinglesigHashMode::P2WPKH,
+            nonce: 0,
+            tx_fee: 0,
+            key_encoding,
+            signature: MessageSignature::empty(),
+        })
+    }
+
+    pub fn new_multisig(
+        hash_mode: MultisigHashMode,
+        signatures_required: u16,
+        pubkeys: &Vec<PublicKey>,
+    ) -> TransactionSpendingCondition {
+        TransactionSpendingCondition::Multisig(MultisigSpendingCondition {
+            signer: public_keys_to_address_hash(
+                &hash_mode.to_address_hash_mode(),
+                signatures_required as usize,
+                pubkeys,
+            ),
+            hash_mode,
+            nonce: 0,
+            tx_fee: 0,
+            fields: vec![],
+            signatures_required,
+        })
+    }
+
+    pub fn new_order_independent_multisig(
+        hash_mode: OrderIndependentMultisigHashMode,
+        signatures_required: u16,
+        pubkeys: &Vec<PublicKey>,
+    ) -> TransactionSpendingCondition {
+        TransactionSpendingCondition::OrderIndependentMultisig(
+            OrderIndependentMultisigSpendingCondition {
+                signer: public_keys_to_address_hash(
+                    &hash_mode.to_address_hash_mode(),
+                    signatures_required as usize,
+                    pubkeys,
+                ),
+                hash_mode,
+                nonce: 0,
+                tx_fee: 0,
+                fields: vec![],
+                signatures_required,
+            },
+        )
+    }
+
+    /// The placeholder spending condition used in place of the sponsor when
+    /// computing the initial sighash.
+    pub fn new_initial_sighash() -> TransactionSpendingCondition {
+        TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
+            signer: Hash160([0u8; 20]),
+            hash_mode: SinglesigHashMode::P2PKH,
+            key_encoding: TransactionPublicKeyEncoding::Compressed,
+            nonce: 0,
+            tx_fee: 0,
+            signature: MessageSignature::empty(),
+        })
+    }
+
+    pub fn signer(&self) -> &Hash160 {
+        match self {
+            TransactionSpendingCondition::Singlesig(data) => &data.signer,
+            TransactionSpendingCondition::Multisig(data) => &data.signer,
+            TransactionSpendingCondition::OrderIndependentMultisig(data) => &data.signer,
+        }
+    }
+
+    pub fn nonce(&self) -> u64 {
+        match self {
+            TransactionSpendingCondition::Singlesig(data) => data.nonce,
+            TransactionSpendingCondition::Multisig(data) => data.nonce,
+            TransactionSpendingCondition::OrderIndependentMultisig(data) => data.nonce,
+        }
+    }
+
+    pub fn tx_fee(&self) -> u64 {
+        match self {
+            TransactionSpendingCondition::Singlesig(data) => data.tx_fee,
+            TransactionSpendingCondition::Multisig(data) => data.tx_fee,
+            TransactionSpendingCondition::OrderIndependentMultisig(data) => data.tx_fee,
+        }
+    }
+
+    pub fn set_nonce(&mut self, nonce: u64) {
+        match self {
+            TransactionSpendingCondition::Singlesig(data) => data.nonce = nonce,
+            TransactionSpendingCondition::Multisig(data) => data.nonce = nonce,
+            TransactionSpendingCondition::OrderIndependentMultisig(data) => data.nonce = nonce,
+        }
+    }
+
+    pub fn set_tx_fee(&mut self, tx_fee: u64) {
+        match self {
+            TransactionSpendingCondition::Singlesig(data) => data.tx_fee = tx_fee,
+            TransactionSpendingCondition::Multisig(data) => data.tx_fee = tx_fee,
+            TransactionSpendingCondition::OrderIndependentMultisig(data) => data.tx_fee = tx_fee,
+        }
+    }
+
+    /// Clear the fee, nonce and any signatures, as is done when computing the
+    /// initial sighash.
+    pub fn clear(&mut self) {
+        match self {
+            TransactionSpendingCondition::Singlesig(data) => {
+                data.tx_fee = 0;
+                data.nonce = 0;
+                data.signature = MessageSignature::empty();
+            }
+            TransactionSpendingCondition::Multisig(data) => {
+                data.tx_fee = 0;
+                data.nonce = 0;
+                data.fields.clear();
+            }
+            TransactionSpendingCondition::OrderIndependentMultisig(data) => {
+                data.tx_fee = 0;
+                data.nonce = 0;
+                data.fields.clear();
+            }
+        }
+    }
+
+    /// sighash-presign = SHA512/256(cur_sighash || auth_flag || fee || nonce)
+    pub fn make_sighash_presign(
+        cur_sighash: &Txid,
+        cond_code: &TransactionAuthFlags,
+        tx_fee: u64,
+        nonce: u64,
+    ) -> Txid {
+        let mut bytes = Vec::with_capacity(32 + 1 + 8 + 8);
+        bytes.extend_from_slice(&cur_sighash.0);
+        bytes.push(*cond_code as u8);
+        bytes.extend_from_slice(&tx_fee.to_be_bytes());
+        bytes.extend_from_slice(&nonce.to_be_bytes());
+        Txid::from_data(&bytes)
+    }
+
+    /// sighash-postsign = SHA512/256(presign_sighash || key_encoding || signature)
+    pub fn make_sighash_postsign(
+        cur_sighash: &Txid,
+        key_encoding: TransactionPublicKeyEncoding,
+        sig: &MessageSignature,
+    ) -> Txid {
+        let mut bytes = Vec::with_capacity(32 + 1 + 65);
+        bytes.extend_from_slice(&cur_sighash.0);
+        bytes.push(key_encoding as u8);
+        bytes.extend_from_slice(&sig.0);
+        Txid::from_data(&bytes)
+    }
+
+    /// Sign the next presign sighash, returning the signature and the
+    /// sighash the next signer should continue from.
+    pub fn next_signature(
+        cur_sighash: &Txid,
+        cond_code: &TransactionAuthFlags,
+        tx_fee: u64,
+        nonce: u64,
+        privk: &StacksPrivateKey,
+    ) -> (MessageSignature, Txid) {
+        let sighash_presign = Self::make_sighash_presign(cur_sighash, cond_code, tx_fee, nonce);
+        let sig = privk.sign(&sighash_presign.0);
+        let next_sighash =
+            Self::make_sighash_postsign(&sighash_presign, privk.key_encoding(), &sig);
+        (sig, next_sighash)
+    }
+
+    /// Recover the public key behind the next signature, returning it along
+    /// with the sighash the next signature should be checked against.
+    pub fn next_verification(
+        cur_sighash: &Txid,
+        cond_code: &TransactionAuthFlags,
+        tx_fee: u64,
+        nonce: u64,
+        key_encoding: TransactionPublicKeyEncoding,
+        sig: &MessageSignature,
+    ) -> Result<(PublicKey, Txid), Error> {
+        let sighash_presign = Self::make_sighash_presign(cur_sighash, cond_code, tx_fee, nonce);
+        let pubkey = sig.recover_public_key(&sighash_presign.0)?;
+        let next_sighash = Self::make_sighash_postsign(&sighash_presign, key_encoding, sig);
+        Ok((pubkey, next_sighash))
+    }
+
+    /// Verify the signature(s) of this spending condition, returning the
+    /// sighash which the next spending condition (if any) signs over.
+    pub fn verify(
+        &self,
+        initial_sighash: &Txid,
+        cond_code: &TransactionAuthFlags,
+    ) -> Result<Txid, Error> {
+        match self {
+            TransactionSpendingCondition::Singlesig(data) => {
+                data.verify(initial_sighash, cond_code)
+            }
+            TransactionSpendingCondition::Multisig(data) => data.verify(initial_sighash, cond_code),
+            TransactionSpendingCondition::OrderIndependentMultisig(data) => {
+                data.verify(initial_sighash, cond_code)
+            }
+        }
+    }
+}
+
+/// Computes the signer hash of a single public key.
+fn singlesig_signer_hash(
+    hash_mode: &SinglesigHashMode,
+    pubkey: &PublicKey,
+    key_encoding: TransactionPublicKeyEncoding,
+) -> Hash160 {
+    match (hash_mode, key_encoding) {
+        (SinglesigHashMode::P2PKH, TransactionPublicKeyEncoding::Uncompressed) => {
+            Hash160::from_data(&pubkey.serialize())
+        }
+        _ => public_keys_to_address_hash(&hash_mode.to_address_hash_mode(), 1, &vec![*pubkey]),
+    }
+}
+
+impl SinglesigSpendingCondition {
+    pub fn verify(
+        &self,
+        initial_sighash: &Txid,
+        cond_code: &TransactionAuthFlags,
+    ) -> Result<Txid, Error> {
+        if self.hash_mode == SinglesigHashMode::P2WPKH
+            && self.key_encoding != TransactionPublicKeyEncoding::Compressed
+        {
+            return Err(Error::VerifyingError(
+                "P2WPKH spending conditions require a compressed public key".into(),
+            ));
+        }
+
+        let (pubkey, next_sighash) = TransactionSpendingCondition::next_verification(
+            initial_sighash,
+            cond_code,
+            self.tx_fee,
+            self.nonce,
+            self.key_encoding,
+            &self.signature,
+        )?;
+
+        if singlesig_signer_hash(&self.hash_mode, &pubkey, self.key_encoding) != self.signer {
+            return Err(Error::VerifyingError(format!(
+                "Signer hash does not equal hash of public key(s): {} != {}",
+                hex::encode(self.signer.0),
+                hex::encode(singlesig_signer_hash(&self.hash_mode, &pubkey, self.key_encoding).0)
+            )));
+        }
+
+        Ok(next_sighash)
+    }
+}
+
+/// Collects the public keys of a multisig spending condition, recovering
+/// them from signatures where necessary, and checks them against the signer.
+/// Returns the number of signatures.
+fn verify_multisig_fields(
+    address_hash_mode: AddressHashMode,
+    signer: &Hash160,
+    signatures_required: u16,
+    fields: &[TransactionAuthField],
+    mut next_pubkey: impl FnMut(
+        TransactionPublicKeyEncoding,
+        &MessageSignature,
+    ) -> Result<PublicKey, Error>,
+) -> Result<u16, Error> {
+    let mut pubkeys = Vec::with_capacity(fields.len());
+    let mut num_sigs: u16 = 0;
+    for field in fields {
+        let (key_encoding, pubkey) = match field {
+            TransactionAuthField::PublicKey(key_encoding, pubkey) => (*key_encoding, *pubkey),
+            TransactionAuthField::Signature(key_encoding, sig) => {
+                num_sigs = num_sigs
+                    .checked_add(1)
+                    .ok_or_else(|| Error::VerifyingError("Too many signatures".into()))?;
+                (*key_encoding, next_pubkey(*key_encoding, sig)?)
+            }
+        };
+        // segwit requires compressed public keys
+        if address_hash_mode == AddressHashMode::SerializeP2WSH
+            && key_encoding == TransactionPublicKeyEncoding::Uncompressed
+        {
+            return Err(Error::VerifyingError(
+                "P2WSH spending conditions require compressed public keys".into(),
+            ));
+        }
+        pubkeys.push((key_encoding, pubkey));
+    }
+
+    let hash = encoded_public_keys_to_address_hash(
+        &address_hash_mode,
+        signatures_required as usize,
+        &pubkeys,
+    );
+    if hash != *signer {
+        return Err(Error::VerifyingError(format!(
+            "Signer hash does not equal hash of public key(s): {} != {}",
+            hex::encode(signer.0),
+            hex::encode(hash.0)
+        )));
+    }
+
+    Ok(num_sigs)
+}
+
+impl MultisigSpendingCondition {
+    pub fn push_signature(
+        &mut self,
+        key_encoding: TransactionPublicKeyEncoding,
+        signature: MessageSignature,
+    ) {
+        self.fields
+            .push(TransactionAuthField::Signature(key_encoding, signature));
+    }
+
+    pub fn push_public_key(
+        &mut self,
+        key_encoding: TransactionPublicKeyEncoding,
+        public_key: PublicKey,
+    ) {
+        self.fields
+            .push(TransactionAuthField::PublicKey(key_encoding, public_key));
+    }
+
+    pub fn verify(
+        &self,
+        initial_sighash: &Txid,
+        cond_code: &TransactionAuthFlags,
+    ) -> Result<Txid, Error> {
+        let mut cur_sighash = *initial_sighash;
+        let num_sigs = verify_multisig_fields(
+            self.hash_mode.to_address_hash_mode(),
+            &self.signer,
+            self.signatures_required,
+            &self.fields,
+            |key_encoding, sig| {
+                let (pubkey, next_sighash) = TransactionSpendingCondition::next_verification(
+                    &cur_sighash,
+                    cond_code,
+                    self.tx_fee,
+                    self.nonce,
+                    key_encoding,
+                    sig,
+                )?;
+                cur_sighash = next_sighash;
+                Ok(pubkey)
+            },
+        )?;
+        if num_sigs != self.signatures_required {
+            return Err(Error::VerifyingError(format!(
+                "Incorrect number of signatures: expected {}, got {num_sigs}",
+                self.signatures_required
+            )));
+        }
+        Ok(cur_sighash)
+    }
+}
+
+impl OrderIndependentMultisigSpendingCondition {
+    pub fn push_signature(
+        &mut self,
+        key_encoding: TransactionPublicKeyEncoding,
+        signature: MessageSignature,
+    ) {
+        self.fields
+            .push(TransactionAuthField::Signature(key_encoding, signature));
+    }
+
+    pub fn push_public_key(
+        &mut self,
+        key_encoding: TransactionPublicKeyEncoding,
+        public_key: PublicKey,
+    ) {
+        self.fields
+            .push(TransactionAuthField::PublicKey(key_encoding, public_key));
+    }
+
+    pub fn verify(
+        &self,
+        initial_sighash: &Txid,
+        cond_code: &TransactionAuthFlags,
+    ) -> Result<Txid, Error> {
+        let num_sigs = verify_multisig_fields(
+            self.hash_mode.to_address_hash_mode(),
+            &self.signer,
+            self.signatures_required,
+            &self.fields,
+            |key_encoding, sig| {
+                // every signer signs the same initial sighash
+                let (pubkey, _) = TransactionSpendingCondition::next_verification(
+                    initial_sighash,
+                    cond_code,
+                    self.tx_fee,
+                    self.nonce,
+                    key_encoding,
+                    sig,
+                )?;
+                Ok(pubkey)
+            },
+        )?;
+        // extra signatures are fine, as they don't depend on each other
+        if num_sigs < self.signatures_required {
+            return Err(Error::VerifyingError(format!(
+                "Not enough signatures: expected at least {}, got {num_sigs}",
+                self.signatures_required
+            )));
+        }
+        Ok(*initial_sighash)
+    }
+}
+
+impl TransactionAuth {
+    pub fn from_p2pkh(privk: &StacksPrivateKey) -> TransactionAuth {
+        TransactionAuth::Standard(TransactionSpendingCondition::new_singlesig_p2pkh(
+            &privk.public_key(),
+            privk.key_encoding(),
+        ))
+    }
+
+    pub fn is_standard(&self) -> bool {
+        matches!(self, TransactionAuth::Standard(_))
+    }
+
+    pub fn is_sponsored(&self) -> bool {
+        matches!(self, TransactionAuth::Sponsored(_, _))
+    }
+
+    pub fn origin(&self) -> &TransactionSpendingCondition {
+        match self {
+            TransactionAuth::Standard(origin) => origin,
+            TransactionAuth::Sponsored(origin, _) => origin,
+        }
+    }
+
+    pub fn sponsor(&self) -> Option<&TransactionSpendingCondition> {
+        match self {
+            TransactionAuth::Standard(_) => None,
+            TransactionAuth::Sponsored(_, sponsor) => Some(sponsor),
+        }
+    }
+
+    /// Replace the sponsor spending condition. Fails on non-sponsored auth.
+    pub fn set_sponsor(&mut self, sponsor: TransactionSpendingCondition) -> Result<(), Error> {
+        match self {
+            TransactionAuth::Sponsored(_, ref mut s) => {
+                *s = sponsor;
+                Ok(())
+            }
+            TransactionAuth::Standard(_) => Err(Error::SigningError(
+                "Cannot set a sponsor on a non-sponsored transaction".into(),
+            )),
+        }
+    }
+
+    /// The fee is paid by the sponsor, if there is one.
+    pub fn get_tx_fee(&self) -> u64 {
+        match self {
+            TransactionAuth::Standard(origin) => origin.tx_fee(),
+            TransactionAuth::Sponsored(_, sponsor) => sponsor.tx_fee(),
+        }
+    }
+
+    pub fn set_tx_fee(&mut self, tx_fee: u64) {
+        match self {
+            TransactionAuth::Standard(ref mut origin) => origin.set_tx_fee(tx_fee),
+            TransactionAuth::Sponsored(_, ref mut sponsor) => sponsor.set_tx_fee(tx_fee),
+        }
+    }
+
+    pub fn set_origin_nonce(&mut self, nonce: u64) {
+        match self {
+            TransactionAuth::Standard(ref mut origin) => origin.set_nonce(nonce),
+            TransactionAuth::Sponsored(ref mut origin, _) => origin.set_nonce(nonce),
+        }
+    }
+
+    pub fn set_sponsor_nonce(&mut self, nonce: u64) -> Result<(), Error> {
+        match self {
+            TransactionAuth::Sponsored(_, ref mut sponsor) => {
+                sponsor.set_nonce(nonce);
+                Ok(())
+            }
+            TransactionAuth::Standard(_) => Err(Error::SigningError(
+                "Cannot set a sponsor nonce on a non-sponsored transaction".into(),
+            )),
+        }
+    }
+
+    /// The auth used to compute the initial sighash: the origin is cleared
+    /// and the sponsor is replaced with a placeholder.
+    pub fn into_initial_sighash_auth(self) -> TransactionAuth {
+        match self {
+            TransactionAuth::Standard(mut origin) => {
+                origin.clear();
+                TransactionAuth::Standard(origin)
+            }
+            TransactionAuth::Sponsored(mut origin, _) => {
+                origin.clear();
+                TransactionAuth::Sponsored(
+                    origin,
+                    TransactionSpendingCondition::new_initial_sighash(),
+                )
+            }
+        }
+    }
+
+    /// Verify the origin's signature(s). The origin always signs with the
+    /// standard auth flag, even when the transaction is sponsored.
+    pub fn verify_origin(&self, initial_sighash: &Txid) -> Result<Txid, Error> {
+        self.origin()
+            .verify(initial_sighash, &TransactionAuthFlags::AuthStandard)
+    }
+
+    pub fn verify(&self, initial_sighash: &Txid) -> Result<(), Error> {
+        let origin_sighash = self.verify_origin(initial_sighash)?;
+        if let TransactionAuth::Sponsored(_, sponsor) = self {
+            sponsor.verify(&origin_sighash, &TransactionAuthFlags::AuthSponsored)?;
+        }
+        Ok(())
+    }
+}
+
+impl StacksTransaction {
+    pub fn new(
+        version: TransactionVersion,
+        auth: TransactionAuth,
+        payload: TransactionPayload,
+    ) -> StacksTransaction {
+        let chain_id = match version {
+            TransactionVersion::Mainnet => crate::stacks::CHAIN_ID_MAINNET,
+            TransactionVersion::Testnet => crate::stacks::CHAIN_ID_TESTNET,
+        };
+        StacksTransaction {
+            version,
+            chain_id,
+            auth,
+            anchor_mode: TransactionAnchorMode::Any,
+            post_condition_mode: TransactionPostConditionMode::Deny,
+            post_conditions: vec![],
+            payload,
+        }
+    }
+
+    pub fn get_tx_fee(&self) -> u64 {
+        self.auth.get_tx_fee()
+    }
+
+    pub fn set_tx_fee(&mut self, tx_fee: u64) {
+        self.auth.set_tx_fee(tx_fee);
+    }
+
+    pub fn get_origin_nonce(&self) -> u64 {
+        self.auth.origin().nonce()
+    }
+
+    pub fn set_origin_nonce(&mut self, nonce: u64) {
+        self.auth.set_origin_nonce(nonce);
+    }
+
+    pub fn set_sponsor_nonce(&mut self, nonce: u64) -> Result<(), Error> {
+        self.auth.set_sponsor_nonce(nonce)
+    }
+
+    /// The initial sighash, i.e. the txid of this transaction with its auth
+    /// cleared.
+    pub fn sign_begin(&self) -> Result<Txid, Error> {
+        let mut tx = self.clone();
+        tx.auth = tx.auth.into_initial_sighash_auth();
+        tx.txid()
+    }
+
+    pub fn verify_begin(&self) -> Result<Txid, Error> {
+        self.sign_begin()
+    }
+
+    fn sign_and_append(
+        condition: &mut TransactionSpendingCondition,
+        cur_sighash: &Txid,
+        auth_flag: &TransactionAuthFlags,
+        privk: &StacksPrivateKey,
+    ) -> Result<Txid, Error> {
+        let (sig, next_sighash) = TransactionSpendingCondition::next_signature(
+            cur_sighash,
+            auth_flag,
+            condition.tx_fee(),
+            condition.nonce(),
+            privk,
+        );
+        match condition {
+            TransactionSpendingCondition::Singlesig(ref mut cond) => {
+                if cond.key_encoding != privk.key_encoding() {
+                    return Err(Error::SigningError(
+                        "Private key compression does not match the spending condition".into(),
+                    ));
+                }
+                cond.signature = sig;
+                Ok(next_sighash)
+            }
+            TransactionSpendingCondition::Multisig(ref mut cond) => {
+                cond.push_signature(privk.key_encoding(), sig);
+                Ok(next_sighash)
+            }
+            TransactionSpendingCondition::OrderIndependentMultisig(ref mut cond) => {
+                cond.push_signature(privk.key_encoding(), sig);
+                Ok(*cur_sighash)
+            }
+        }
+    }
+
+    fn append_public_key(
+        condition: &mut TransactionSpendingCondition,
+        key_encoding: TransactionPublicKeyEncoding,
+        pubkey: PublicKey,
+    ) -> Result<(), Error> {
+        match condition {
+            TransactionSpendingCondition::Singlesig(_) => Err(Error::SigningError(
+                "Cannot append a public key to a singlesig spending condition".into(),
+            )),
+            TransactionSpendingCondition::Multisig(ref mut cond) => {
+                cond.push_public_key(key_encoding, pubkey);
+                Ok(())
+            }
+            TransactionSpendingCondition::OrderIndependentMultisig(ref mut cond) => {
+                cond.push_public_key(key_encoding, pubkey);
+                Ok(())
+            }
+        }
+    }
+
+    /// Sign the origin's spending condition, returning the next sighash.
+    pub fn sign_next_origin(
+        &mut self,
+        cur_sighash: &Txid,
+        privk: &StacksPrivateKey,
+    ) -> Result<Txid, Error> {
+        let origin = match self.auth {
+            TransactionAuth::Standard(ref mut origin) => origin,
+            TransactionAuth::Sponsored(ref mut origin, _) => origin,
+        };
+        Self::sign_and_append(
+            origin,
+            cur_sighash,
+            &TransactionAuthFlags::AuthStandard,
+            privk,
+        )
+    }
+
+    /// Append a (non-signing) public key to a multisig origin.
+    pub fn append_next_origin(
+        &mut self,
+        key_encoding: TransactionPublicKeyEncoding,
+        pubkey: PublicKey,
+    ) -> Result<(), Error> {
+        let origin = match self.auth {
+            TransactionAuth::Standard(ref mut origin) => origin,
+            TransactionAuth::Sponsored(ref mut origin, _) => origin,
+        };
+        Self::append_public_key(origin, key_encoding, pubkey)
+    }
+
+    /// Sign the sponsor's spending condition, returning the next sighash.
+    pub fn sign_next_sponsor(
+        &mut self,
+        cur_sighash: &Txid,
+        privk: &StacksPrivateKey,
+    ) -> Result<Txid, Error> {
+        match self.auth {
+            TransactionAuth::Sponsored(_, ref mut sponsor) => Self::sign_and_append(
+                sponsor,
+                cur_sighash,
+                &TransactionAuthFlags::AuthSponsored,
+                privk,
+            ),
+            TransactionAuth::Standard(_) => Err(Error::SigningError(
+                "Cannot sign the sponsor of a non-sponsored transaction".into(),
+            )),
+        }
+    }
+
+    /// Append a (non-signing) public key to a multisig sponsor.
+    pub fn append_next_sponsor(
+        &mut self,
+        key_encoding: TransactionPublicKeyEncoding,
+        pubkey: PublicKey,
+    ) -> Result<(), Error> {
+        match self.auth {
+            TransactionAuth::Sponsored(_, ref mut sponsor) => {
+                Self::append_public_key(sponsor, key_encoding, pubkey)
+            }
+            TransactionAuth::Standard(_) => Err(Error::SigningError(
+                "Cannot append to the sponsor of a non-sponsored transaction".into(),
+            )),
+        }
+    }
+
+    /// Verify the origin's signature(s), returning the sighash the sponsor
+    /// signs over.
+    pub fn verify_origin(&self) -> Result<Txid, Error> {
+        self.auth.verify_origin(&self.verify_begin()?)
+    }
+
+    /// Verify all of this transaction's signatures.
+    pub fn verify(&self) -> Result<(), Error> {
+        self.auth.verify(&self.verify_begin()?)
+    }
+}
+
+/// Incrementally signs a transaction: first the origin, then (for sponsored
+/// transactions) the sponsor.
+#[derive(Debug, Clone)]
+pub struct StacksTransactionSigner {
+    tx: StacksTransaction,
+    sighash: Txid,
+    origin_done: bool,
+}
+
+impl StacksTransactionSigner {
+    pub fn new(tx: &StacksTransaction) -> Result<StacksTransactionSigner, Error> {
+        Ok(StacksTransactionSigner {
+            tx: tx.clone(),
+            sighash: tx.sign_begin()?,
+            origin_done: false,
+        })
+    }
+
+    /// Start signing as the sponsor of an origin-signed transaction, using the
+    /// given sponsor spending condition.
+    pub fn new_sponsor(
+        tx: &StacksTransaction,
+        sponsor: TransactionSpendingCondition,
+    ) -> Result<StacksTransactionSigner, Error> {
+        let mut tx = tx.clone();
+        tx.auth.set_sponsor(sponsor)?;
+        let sighash = tx.verify_origin()?;
+        Ok(StacksTransactionSigner {
+            tx,
+            sighash,
+            origin_done: true,
+        })
+    }
+
+    pub fn sign_origin(&mut self, privk: &StacksPrivateKey) -> Result<(), Error> {
+        if self.origin_done {
+            return Err(Error::SigningError(
+                "Cannot sign the origin after the sponsor".into(),
+            ));
+        }
+        self.sighash = self.tx.sign_next_origin(&self.sighash, privk)?;
+        Ok(())
+    }
+
+    pub fn append_origin(
+        &mut self,
+        key_encoding: TransactionPublicKeyEncoding,
+        pubkey: PublicKey,
+    ) -> Result<(), Error> {
+        if self.origin_done {
+            return Err(Error::SigningError(
+                "Cannot append to the origin after the sponsor".into(),
+            ));
+        }
+        self.tx.append_next_origin(key_encoding, pubkey)
+    }
+
+    pub fn sign_sponsor(&mut self, privk: &StacksPrivateKey) -> Result<(), Error> {
+        self.origin_done = true;
+        self.sighash = self.tx.sign_next_sponsor(&self.sighash, privk)?;
+        Ok(())
+    }
+
+    pub fn append_sponsor(
+        &mut self,
+        key_encoding: TransactionPublicKeyEncoding,
+        pubkey: PublicKey,
+    ) -> Result<(), Error> {
+        self.origin_done = true;
+        self.tx.append_next_sponsor(key_encoding, pubkey)
+    }
+
+    /// The signed transaction, if all of its signatures verify.
+    pub fn get_tx(&self) -> Option<StacksTransaction> {
+        self.tx.verify().ok().map(|_| self.tx.clone())
+    }
+
+    /// The transaction as signed so far, whether or not it is complete.
+    pub fn get_tx_incomplete(&self) -> StacksTransaction {
+        self.tx.clone()
+    }
+}
@@ -0,0 +1,256 @@
+//! A fluent builder for signed single-sig transactions.
+//!
+//! ```ignore
+//! let tx = StacksTransactionBuilder::new(privk, CHAIN_ID_TESTNET)
+//!     .nonce(3)
+//!     .fee(180)
+//!     .memo("hello")
+//!     .token_transfer(recipient, 1_000_000)?;
+//! ```
+
+use crate::{
+    stacks::{
+        api::{
+            clarity::{
+                is_valid_clarity_name, is_valid_contract_name, ClarityVersion, PrincipalData,
+                QualifiedContractIdentifier, Value,
+            },
+            errors::Error,
+        },
+        StacksAddress, StacksPrivateKey, CHAIN_ID_MAINNET,
+    },
+    types::EnvironmentKeychain,
+};
+
+use super::*;
+
+/// Builds and signs transactions from a single private key. The same builder
+/// can also act as the sponsor of transactions built by someone else, see
+/// [`StacksTransactionBuilder::sponsor`].
+#[derive(Debug, Clone)]
+pub struct StacksTransactionBuilder {
+    privk: StacksPrivateKey,
+    chain_id: u32,
+    nonce: u64,
+    fee: u64,
+    anchor_mode: TransactionAnchorMode,
+    post_condition_mode: TransactionPostConditionMode,
+    post_conditions: Vec<TransactionPostCondition>,
+    memo: Vec<u8>,
+    sponsored: bool,
+}
+
+impl StacksTransactionBuilder {
+    /// The transaction version is derived from `chain_id`: anything other
+    /// than mainnet is treated as a testnet.
+    pub fn new(privk: StacksPrivateKey, chain_id: u32) -> Self {
+        StacksTransactionBuilder {
+            privk,
+            chain_id,
+            nonce: 0,
+            fee: 0,
+            anchor_mode: TransactionAnchorMode::Any,
+            post_condition_mode: TransactionPostConditionMode::Deny,
+            post_conditions: vec![],
+            memo: vec![],
+            sponsored: false,
+        }
+    }
+
+    /// Creates a builder which signs with the given keychain's private key,
+    /// checking that the key actually belongs to the keychain's address.
+    pub fn from_keychain(keychain: &EnvironmentKeychain, chain_id: u32) -> Result<Self, Error> {
+        let privk = StacksPrivateKey::from_hex(&keychain.private_key)?;
+        let address: StacksAddress = keychain.stx_address.parse()?;
+        // Only compare the hashes, keychain addresses may be for either network.
+        if *spending_condition(&privk).signer() != address.bytes {
+            return Err(Error::InvalidKey(format!(
+                "The private key does not belong to {}",
+                keychain.stx_address
+            )));
+        }
+        Ok(Self::new(privk, chain_id))
+    }
+
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// The fee, in micro-STX. For sponsored transactions the origin's fee is
+    /// always zero and this is ignored; the sponsor sets the fee instead.
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    pub fn anchor_mode(mut self, anchor_mode: TransactionAnchorMode) -> Self {
+        self.anchor_mode = anchor_mode;
+        self
+    }
+
+    pub fn post_condition_mode(mut self, mode: TransactionPostConditionMode) -> Self {
+        self.post_condition_mode = mode;
+        self
+    }
+
+    pub fn post_condition(mut self, post_condition: TransactionPostCondition) -> Self {
+        self.post_conditions.push(post_condition);
+        self
+    }
+
+    /// The memo attached to token transfers, at most 34 bytes.
+    pub fn memo(mut self, memo: impl AsRef<[u8]>) -> Self {
+        self.memo = memo.as_ref().to_vec();
+        self
+    }
+
+    /// Builds origin-signed transactions which still need a sponsor, see
+    /// [`StacksTransactionBuilder::sponsor`].
+    pub fn sponsored(mut self) -> Self {
+        self.sponsored = true;
+        self
+    }
+
+    pub fn version(&self) -> TransactionVersion {
+        if self.chain_id == CHAIN_ID_MAINNET {
+            TransactionVersion::Mainnet
+        } else {
+            TransactionVersion::Testnet
+        }
+    }
+
+    /// The address of the signing key on the builder's network.
+    pub fn address(&self) -> StacksAddress {
+        StacksAddress::p2pkh_from_hash(
+            self.version() == TransactionVersion::Mainnet,
+            *spending_condition(&self.privk).signer(),
+        )
+    }
+
+    pub fn token_transfer(
+        &self,
+        recipient: PrincipalData,
+        amount: u64,
+    ) -> Result<StacksTransaction, Error> {
+        if self.memo.len() > 34 {
+            return Err(Error::SerializeError(format!(
+                "Memo of {} bytes exceeds the maximum of 34 bytes",
+                self.memo.len()
+            )));
+        }
+        let mut memo = [0u8; 34];
+        memo[..self.memo.len()].copy_from_slice(&self.memo);
+
+        self.build(TransactionPayload::TokenTransfer(
+            recipient,
+            amount,
+            TokenTransferMemo(memo),
+        ))
+    }
+
+    pub fn contract_call(
+        &self,
+        contract: &QualifiedContractIdentifier,
+        function_name: &str,
+        function_args: Vec<Value>,
+    ) -> Result<StacksTransaction, Error> {
+        if !is_valid_clarity_name(function_name) {
+            return Err(Error::SerializeError(format!(
+                "Invalid function name: '{function_name}'"
+            )));
+        }
+
+        self.build(TransactionPayload::ContractCall(TransactionContractCall {
+            address: contract.issuer.clone().into(),
+            contract_name: contract.name.clone(),
+            function_name: function_name.to_string(),
+            function_args,
+        }))
+    }
+
+    /// Deploys a contract. Without a Clarity version the node uses the
+    /// default version for the current epoch.
+    pub fn smart_contract(
+        &self,
+        name: &str,
+        code_body: &str,
+        clarity_version: Option<ClarityVersion>,
+    ) -> Result<StacksTransaction, Error> {
+        if !is_valid_contract_name(name) {
+            return Err(Error::SerializeError(format!(
+                "Invalid contract name: '{name}'"
+            )));
+        }
+
+        self.build(TransactionPayload::SmartContract(
+            TransactionSmartContract {
+                name: name.to_string(),
+                code_body: code_body.to_string(),
+            },
+            clarity_version,
+        ))
+    }
+
+    /// Builds and signs a transaction with an arbitrary payload.
+    pub fn build(&self, payload: TransactionPayload) -> Result<StacksTransaction, Error> {
+        let origin = spending_condition(&self.privk);
+        let auth = if self.sponsored {
+            TransactionAuth::Sponsored(origin, TransactionSpendingCondition::new_initial_sighash())
+        } else {
+            TransactionAuth::Standard(origin)
+        };
+
+        let mut tx = StacksTransaction::new(self.version(), auth, payload);
+        tx.chain_id = self.chain_id;
+        tx.anchor_mode = self.anchor_mode;
+        tx.post_condition_mode = self.post_condition_mode;
+        tx.post_conditions = self.post_conditions.clone();
+        tx.set_origin_nonce(self.nonce);
+        if !self.sponsored {
+            tx.set_tx_fee(self.fee);
+        }
+
+        let mut signer = StacksTransactionSigner::new(&tx)?;
+        signer.sign_origin(&self.privk)?;
+        if self.sponsored {
+            // The sponsor's signature is still missing, so only the origin's
+            // can be checked.
+            let tx = signer.get_tx_incomplete();
+            tx.verify_origin()?;
+            Ok(tx)
+        } else {
+            signer
+                .get_tx()
+                .ok_or_else(|| Error::SigningError("Signed transaction failed to verify".into()))
+        }
+    }
+
+    /// Sponsors an origin-signed transaction, paying its fee from this
+    /// builder's key with this builder's nonce and fee.
+    pub fn sponsor(&self, tx: &StacksTransaction) -> Result<StacksTransaction, Error> {
+        if tx.chain_id != self.chain_id {
+            return Err(Error::SigningError(format!(
+                "Transaction is for chain id {:#x}, but the sponsor is on {:#x}",
+                tx.chain_id, self.chain_id
+            )));
+        }
+
+        let mut sponsor = spending_condition(&self.privk);
+        sponsor.set_nonce(self.nonce);
+        sponsor.set_tx_fee(self.fee);
+
+        let mut signer = StacksTransactionSigner::new_sponsor(tx, sponsor)?;
+        signer.sign_sponsor(&self.privk)?;
+        signer
+            .get_tx()
+            .ok_or_else(|| Error::SigningError("Sponsored transaction failed to verify".into()))
+    }
+}
+
+/// The single-signature spending condition the key signs with. Its signer hash
+/// is that of the key's address, with the public key compressed or not as the
+/// key says.
+fn spending_condition(privk: &StacksPrivateKey) -> TransactionSpendingCondition {
+    TransactionSpendingCondition::new_singlesig_p2pkh(&privk.public_key(), privk.key_encoding())
+}
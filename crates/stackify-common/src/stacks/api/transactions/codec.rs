@@ -0,0 +1,830 @@
+//! Consensus serialization of Stacks transactions, as specified in SIP-005.
+
+use std::io::{Read, Write};
+
+use libsecp256k1::PublicKey;
+
+use crate::stacks::{
+    api::{
+        clarity::{OptionalData, PrincipalData},
+        codec::{
+            read_clarity_name, read_contract_name, read_next, read_next_at_most,
+            read_stacks_string, write_clarity_name, write_contract_name, write_next,
+            write_stacks_string, StacksMessageCodec, MAX_MESSAGE_LEN,
+        },
+        errors::Error,
+    },
+    TransactionPublicKeyEncoding,
+};
+
+use super::*;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TransactionPayloadID {
+    TokenTransfer = 0,
+    SmartContract = 1,
+    ContractCall = 2,
+    PoisonMicroblock = 3,
+    Coinbase = 4,
+    CoinbaseToAltRecipient = 5,
+    VersionedSmartContract = 6,
+    TenureChange = 7,
+    NakamotoCoinbase = 8,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TransactionAuthFieldID {
+    PublicKeyCompressed = 0x00,
+    PublicKeyUncompressed = 0x01,
+    SignatureCompressed = 0x02,
+    SignatureUncompressed = 0x03,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AssetInfoID {
+    Stx = 0,
+    FungibleAsset = 1,
+    NonfungibleAsset = 2,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PostConditionPrincipalID {
+    Origin = 0x01,
+    Standard = 0x02,
+    Contract = 0x03,
+}
+
+fn deserialize_error(msg: impl Into<String>) -> Error {
+    Error::DeserializeError(msg.into())
+}
+
+impl StacksMessageCodec for TransactionVersion {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &(*self as u8))
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        match read_next::<u8, _>(fd)? {
+            x if x == TransactionVersion::Mainnet as u8 => Ok(TransactionVersion::Mainnet),
+            x if x == TransactionVersion::Testnet as u8 => Ok(TransactionVersion::Testnet),
+            x => Err(deserialize_error(format!(
+                "Unrecognized transaction version: {x:#04x}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for TransactionAnchorMode {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &(*self as u8))
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        match read_next::<u8, _>(fd)? {
+            x if x == TransactionAnchorMode::OnChainOnly as u8 => {
+                Ok(TransactionAnchorMode::OnChainOnly)
+            }
+            x if x == TransactionAnchorMode::OffChainOnly as u8 => {
+                Ok(TransactionAnchorMode::OffChainOnly)
+            }
+            x if x == TransactionAnchorMode::Any as u8 => Ok(TransactionAnchorMode::Any),
+            x => Err(deserialize_error(format!(
+                "Unrecognized transaction anchor mode: {x:#04x}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for TransactionPostConditionMode {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &(*self as u8))
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        match read_next::<u8, _>(fd)? {
+            x if x == TransactionPostConditionMode::Allow as u8 => {
+                Ok(TransactionPostConditionMode::Allow)
+            }
+            x if x == TransactionPostConditionMode::Deny as u8 => {
+                Ok(TransactionPostConditionMode::Deny)
+            }
+            x => Err(deserialize_error(format!(
+                "Unrecognized post-condition mode: {x:#04x}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for TransactionPublicKeyEncoding {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &(*self as u8))
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        match read_next::<u8, _>(fd)? {
+            x if x == TransactionPublicKeyEncoding::Compressed as u8 => {
+                Ok(TransactionPublicKeyEncoding::Compressed)
+            }
+            x if x == TransactionPublicKeyEncoding::Uncompressed as u8 => {
+                Ok(TransactionPublicKeyEncoding::Uncompressed)
+            }
+            x => Err(deserialize_error(format!(
+                "Unrecognized public key encoding: {x:#04x}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for TransactionAuthField {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        match self {
+            TransactionAuthField::PublicKey(TransactionPublicKeyEncoding::Compressed, pubkey) => {
+                write_next(fd, &(TransactionAuthFieldID::PublicKeyCompressed as u8))?;
+                write_next(fd, &pubkey.serialize_compressed())
+            }
+            TransactionAuthField::PublicKey(TransactionPublicKeyEncoding::Uncompressed, pubkey) => {
+                write_next(fd, &(TransactionAuthFieldID::PublicKeyUncompressed as u8))?;
+                write_next(fd, &pubkey.serialize())
+            }
+            TransactionAuthField::Signature(key_encoding, signature) => {
+                let field_id = match key_encoding {
+                    TransactionPublicKeyEncoding::Compressed => {
+                        TransactionAuthFieldID::SignatureCompressed
+                    }
+                    TransactionPublicKeyEncoding::Uncompressed => {
+                        TransactionAuthFieldID::SignatureUncompressed
+                    }
+                };
+                write_next(fd, &(field_id as u8))?;
+                write_next(fd, signature)
+            }
+        }
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let field_id: u8 = read_next(fd)?;
+        match field_id {
+            x if x == TransactionAuthFieldID::PublicKeyCompressed as u8 => {
+                let bytes: [u8; 33] = read_next(fd)?;
+                let pubkey = PublicKey::parse_compressed(&bytes)
+                    .map_err(|e| deserialize_error(format!("Invalid public key: {e}")))?;
+                Ok(TransactionAuthField::PublicKey(
+                    TransactionPublicKeyEncoding::Compressed,
+                    pubkey,
+                ))
+            }
+            x if x == TransactionAuthFieldID::PublicKeyUncompressed as u8 => {
+                let bytes: [u8; 65] = read_next(fd)?;
+                let pubkey = PublicKey::parse(&bytes)
+                    .map_err(|e| deserialize_error(format!("Invalid public key: {e}")))?;
+                Ok(TransactionAuthField::PublicKey(
+                    TransactionPublicKeyEncoding::Uncompressed,
+                    pubkey,
+                ))
+            }
+            x if x == TransactionAuthFieldID::SignatureCompressed as u8 => {
+                Ok(TransactionAuthField::Signature(
+                    TransactionPublicKeyEncoding::Compressed,
+                    read_next(fd)?,
+                ))
+            }
+            x if x == TransactionAuthFieldID::SignatureUncompressed as u8 => {
+                Ok(TransactionAuthField::Signature(
+                    TransactionPublicKeyEncoding::Uncompressed,
+                    read_next(fd)?,
+                ))
+            }
+            x => Err(deserialize_error(format!(
+                "Unrecognized auth field ID: {x:#04x}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for SinglesigSpendingCondition {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &(self.hash_mode.clone() as u8))?;
+        write_next(fd, &self.signer)?;
+        write_next(fd, &self.nonce)?;
+        write_next(fd, &self.tx_fee)?;
+        write_next(fd, &self.key_encoding)?;
+        write_next(fd, &self.signature)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let hash_mode_u8: u8 = read_next(fd)?;
+        let hash_mode = SinglesigHashMode::from_u8(hash_mode_u8).ok_or_else(|| {
+            deserialize_error(format!(
+                "Unrecognized singlesig hash mode: {hash_mode_u8:#04x}"
+            ))
+        })?;
+        let signer = read_next(fd)?;
+        let nonce = read_next(fd)?;
+        let tx_fee = read_next(fd)?;
+        let key_encoding = read_next(fd)?;
+        let signature = read_next(fd)?;
+
+        // segwit requires compressed public keys
+        if hash_mode == SinglesigHashMode::P2WPKH
+            && key_encoding != TransactionPublicKeyEncoding::Compressed
+        {
+            return Err(deserialize_error(
+                "P2WPKH spending conditions require a compressed public key",
+            ));
+        }
+
+        Ok(SinglesigSpendingCondition {
+            hash_mode,
+            signer,
+            nonce,
+            tx_fee,
+            key_encoding,
+            signature,
+        })
+    }
+}
+
+/// Returns whether any of the given auth fields uses an uncompressed key.
+fn has_uncompressed_fields(fields: &[TransactionAuthField]) -> bool {
+    fields.iter().any(|field| {
+        matches!(
+            field,
+            TransactionAuthField::PublicKey(TransactionPublicKeyEncoding::Uncompressed, _)
+                | TransactionAuthField::Signature(TransactionPublicKeyEncoding::Uncompressed, _)
+        )
+    })
+}
+
+impl StacksMessageCodec for MultisigSpendingCondition {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &(self.hash_mode.clone() as u8))?;
+        write_next(fd, &self.signer)?;
+        write_next(fd, &self.nonce)?;
+        write_next(fd, &self.tx_fee)?;
+        write_next(fd, &self.fields)?;
+        write_next(fd, &self.signatures_required)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let hash_mode_u8: u8 = read_next(fd)?;
+        let hash_mode = MultisigHashMode::from_u8(hash_mode_u8).ok_or_else(|| {
+            deserialize_error(format!(
+                "Unrecognized multisig hash mode: {hash_mode_u8:#04x}"
+            ))
+        })?;
+        let signer = read_next(fd)?;
+        let nonce = read_next(fd)?;
+        let tx_fee = read_next(fd)?;
+        let fields: Vec<TransactionAuthField> = read_next(fd)?;
+        let signatures_required = read_next(fd)?;
+
+        if hash_mode == MultisigHashMode::P2WSH && has_uncompressed_fields(&fields) {
+            return Err(deserialize_error(
+                "P2WSH spending conditions require compressed public keys",
+            ));
+        }
+
+        Ok(MultisigSpendingCondition {
+            hash_mode,
+            signer,
+            nonce,
+            tx_fee,
+            fields,
+            signatures_required,
+        })
+    }
+}
+
+impl StacksMessageCodec for OrderIndependentMultisigSpendingCondition {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &(self.hash_mode.clone() as u8))?;
+        write_next(fd, &self.signer)?;
+        write_next(fd, &self.nonce)?;
+        write_next(fd, &self.tx_fee)?;
+        write_next(fd, &self.fields)?;
+        write_next(fd, &self.signatures_required)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let hash_mode_u8: u8 = read_next(fd)?;
+        let hash_mode =
+            OrderIndependentMultisigHashMode::from_u8(hash_mode_u8).ok_or_else(|| {
+                deserialize_error(format!(
+                    "Unrecognized order-independent multisig hash mode: {hash_mode_u8:#04x}"
+                ))
+            })?;
+        let signer = read_next(fd)?;
+        let nonce = read_next(fd)?;
+        let tx_fee = read_next(fd)?;
+        let fields: Vec<TransactionAuthField> = read_next(fd)?;
+        let signatures_required = read_next(fd)?;
+
+        if hash_mode == OrderIndependentMultisigHashMode::P2WSH && has_uncompressed_fields(&fields)
+        {
+            return Err(deserialize_error(
+                "P2WSH spending conditions require compressed public keys",
+            ));
+        }
+
+        Ok(OrderIndependentMultisigSpendingCondition {
+            hash_mode,
+            signer,
+            nonce,
+            tx_fee,
+            fields,
+            signatures_required,
+        })
+    }
+}
+
+impl StacksMessageCodec for TransactionSpendingCondition {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        match self {
+            TransactionSpendingCondition::Singlesig(data) => write_next(fd, data),
+            TransactionSpendingCondition::Multisig(data) => write_next(fd, data),
+            TransactionSpendingCondition::OrderIndependentMultisig(data) => write_next(fd, data),
+        }
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        // Peek at the hash mode to figure out which kind of condition this is,
+        // then hand the whole thing (hash mode included) to the right decoder.
+        let hash_mode: u8 = read_next(fd)?;
+        let prefix = [hash_mode];
+        let mut fd = prefix.as_slice().chain(fd);
+
+        if SinglesigHashMode::from_u8(hash_mode).is_some() {
+            Ok(TransactionSpendingCondition::Singlesig(read_next(&mut fd)?))
+        } else if MultisigHashMode::from_u8(hash_mode).is_some() {
+            Ok(TransactionSpendingCondition::Multisig(read_next(&mut fd)?))
+        } else if OrderIndependentMultisigHashMode::from_u8(hash_mode).is_some() {
+            Ok(TransactionSpendingCondition::OrderIndependentMultisig(
+                read_next(&mut fd)?,
+            ))
+        } else {
+            Err(deserialize_error(format!(
+                "Unrecognized spending condition hash mode: {hash_mode:#04x}"
+            )))
+        }
+    }
+}
+
+impl StacksMessageCodec for TransactionAuth {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        match self {
+            TransactionAuth::Standard(origin) => {
+                write_next(fd, &(TransactionAuthFlags::AuthStandard as u8))?;
+                write_next(fd, origin)
+            }
+            TransactionAuth::Sponsored(origin, sponsor) => {
+                write_next(fd, &(TransactionAuthFlags::AuthSponsored as u8))?;
+                write_next(fd, origin)?;
+                write_next(fd, sponsor)
+            }
+        }
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        match read_next::<u8, _>(fd)? {
+            x if x == TransactionAuthFlags::AuthStandard as u8 => {
+                Ok(TransactionAuth::Standard(read_next(fd)?))
+            }
+            x if x == TransactionAuthFlags::AuthSponsored as u8 => {
+                let origin = read_next(fd)?;
+                let sponsor = read_next(fd)?;
+                Ok(TransactionAuth::Sponsored(origin, sponsor))
+            }
+            x => Err(deserialize_error(format!(
+                "Unrecognized transaction auth flag: {x:#04x}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for PostConditionPrincipal {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        match self {
+            PostConditionPrincipal::Origin => {
+                write_next(fd, &(PostConditionPrincipalID::Origin as u8))
+            }
+            PostConditionPrincipal::Standard(address) => {
+                write_next(fd, &(PostConditionPrincipalID::Standard as u8))?;
+                write_next(fd, address)
+            }
+            PostConditionPrincipal::Contract(address, contract_name) => {
+                write_next(fd, &(PostConditionPrincipalID::Contract as u8))?;
+                write_next(fd, address)?;
+                write_contract_name(fd, contract_name)
+            }
+        }
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        match read_next::<u8, _>(fd)? {
+            x if x == PostConditionPrincipalID::Origin as u8 => Ok(PostConditionPrincipal::Origin),
+            x if x == PostConditionPrincipalID::Standard as u8 => {
+                Ok(PostConditionPrincipal::Standard(read_next(fd)?))
+            }
+            x if x == PostConditionPrincipalID::Contract as u8 => {
+                let address = read_next(fd)?;
+                let contract_name = read_contract_name(fd)?;
+                Ok(PostConditionPrincipal::Contract(address, contract_name))
+            }
+            x => Err(deserialize_error(format!(
+                "Unrecognized post-condition principal ID: {x:#04x}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for AssetInfo {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.contract_address)?;
+        write_contract_name(fd, &self.contract_name)?;
+        write_clarity_name(fd, &self.asset_name)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let contract_address = read_next(fd)?;
+        let contract_name = read_contract_name(fd)?;
+        let asset_name = read_clarity_name(fd)?;
+        Ok(AssetInfo {
+            contract_address,
+            contract_name,
+            asset_name,
+        })
+    }
+}
+
+impl StacksMessageCodec for FungibleConditionCode {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &(*self as u8))
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        use FungibleConditionCode::*;
+        match read_next::<u8, _>(fd)? {
+            x if x == SentEq as u8 => Ok(SentEq),
+            x if x == SentGt as u8 => Ok(SentGt),
+            x if x == SentGe as u8 => Ok(SentGe),
+            x if x == SentLt as u8 => Ok(SentLt),
+            x if x == SentLe as u8 => Ok(SentLe),
+            x => Err(deserialize_error(format!(
+                "Unrecognized fungible condition code: {x:#04x}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for NonfungibleConditionCode {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &(*self as u8))
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        match read_next::<u8, _>(fd)? {
+            x if x == NonfungibleConditionCode::Sent as u8 => Ok(NonfungibleConditionCode::Sent),
+            x if x == NonfungibleConditionCode::NotSent as u8 => {
+                Ok(NonfungibleConditionCode::NotSent)
+            }
+            x => Err(deserialize_error(format!(
+                "Unrecognized non-fungible condition code: {x:#04x}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for TransactionPostCondition {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        match self {
+            TransactionPostCondition::STX(principal, condition_code, amount) => {
+                write_next(fd, &(AssetInfoID::Stx as u8))?;
+                write_next(fd, principal)?;
+                write_next(fd, condition_code)?;
+                write_next(fd, amount)
+            }
+            TransactionPostCondition::Fungible(principal, asset_info, condition_code, amount) => {
+                write_next(fd, &(AssetInfoID::FungibleAsset as u8))?;
+                write_next(fd, principal)?;
+                write_next(fd, asset_info)?;
+                write_next(fd, condition_code)?;
+                write_next(fd, amount)
+            }
+            TransactionPostCondition::Nonfungible(
+                principal,
+                asset_info,
+                asset_value,
+                condition_code,
+            ) => {
+                write_next(fd, &(AssetInfoID::NonfungibleAsset as u8))?;
+                write_next(fd, principal)?;
+                write_next(fd, asset_info)?;
+                write_next(fd, asset_value)?;
+                write_next(fd, condition_code)
+            }
+        }
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        match read_next::<u8, _>(fd)? {
+            x if x == AssetInfoID::Stx as u8 => {
+                let principal = read_next(fd)?;
+                let condition_code = read_next(fd)?;
+                let amount = read_next(fd)?;
+                Ok(TransactionPostCondition::STX(
+                    principal,
+                    condition_code,
+                    amount,
+                ))
+            }
+            x if x == AssetInfoID::FungibleAsset as u8 => {
+                let principal = read_next(fd)?;
+                let asset_info = read_next(fd)?;
+                let condition_code = read_next(fd)?;
+                let amount = read_next(fd)?;
+                Ok(TransactionPostCondition::Fungible(
+                    principal,
+                    asset_info,
+                    condition_code,
+                    amount,
+                ))
+            }
+            x if x == AssetInfoID::NonfungibleAsset as u8 => {
+                let principal = read_next(fd)?;
+                let asset_info = read_next(fd)?;
+                let asset_value = read_next(fd)?;
+                let condition_code = read_next(fd)?;
+                Ok(TransactionPostCondition::Nonfungible(
+                    principal,
+                    asset_info,
+                    asset_value,
+                    condition_code,
+                ))
+            }
+            x => Err(deserialize_error(format!(
+                "Unrecognized post-condition asset info ID: {x:#04x}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for TokenTransferMemo {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.0)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        Ok(TokenTransferMemo(read_next(fd)?))
+    }
+}
+
+impl StacksMessageCodec for TransactionContractCall {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.address)?;
+        write_contract_name(fd, &self.contract_name)?;
+        write_clarity_name(fd, &self.function_name)?;
+        write_next(fd, &self.function_args)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let address = read_next(fd)?;
+        let contract_name = read_contract_name(fd)?;
+        let function_name = read_clarity_name(fd)?;
+        let function_args = read_next_at_most(fd, MAX_MESSAGE_LEN)?;
+        Ok(TransactionContractCall {
+            address,
+            contract_name,
+            function_name,
+            function_args,
+        })
+    }
+}
+
+impl StacksMessageCodec for TransactionSmartContract {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_contract_name(fd, &self.name)?;
+        write_stacks_string(fd, &self.code_body)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let name = read_contract_name(fd)?;
+        let code_body = read_stacks_string(fd)?;
+        Ok(TransactionSmartContract { name, code_body })
+    }
+}
+
+impl StacksMessageCodec for StacksMicroblockHeader {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.version)?;
+        write_next(fd, &self.sequence)?;
+        write_next(fd, &self.prev_block)?;
+        write_next(fd, &self.tx_merkle_root)?;
+        write_next(fd, &self.signature)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        Ok(StacksMicroblockHeader {
+            version: read_next(fd)?,
+            sequence: read_next(fd)?,
+            prev_block: read_next(fd)?,
+            tx_merkle_root: read_next(fd)?,
+            signature: read_next(fd)?,
+        })
+    }
+}
+
+impl StacksMessageCodec for CoinbasePayload {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.0)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        Ok(CoinbasePayload(read_next(fd)?))
+    }
+}
+
+impl StacksMessageCodec for TenureChangeCause {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &(*self as u8))
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        match read_next::<u8, _>(fd)? {
+            x if x == TenureChangeCause::BlockFound as u8 => Ok(TenureChangeCause::BlockFound),
+            x if x == TenureChangeCause::Extended as u8 => Ok(TenureChangeCause::Extended),
+            x => Err(deserialize_error(format!(
+                "Unrecognized tenure change cause: {x:#04x}"
+            ))),
+        }
+    }
+}
+
+impl StacksMessageCodec for TenureChangePayload {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.tenure_consensus_hash)?;
+        write_next(fd, &self.prev_tenure_consensus_hash)?;
+        write_next(fd, &self.burn_view_consensus_hash)?;
+        write_next(fd, &self.previous_tenure_end)?;
+        write_next(fd, &self.previous_tenure_blocks)?;
+        write_next(fd, &self.cause)?;
+        write_next(fd, &self.pubkey_hash)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        Ok(TenureChangePayload {
+            tenure_consensus_hash: read_next(fd)?,
+            prev_tenure_consensus_hash: read_next(fd)?,
+            burn_view_consensus_hash: read_next(fd)?,
+            previous_tenure_end: read_next(fd)?,
+            previous_tenure_blocks: read_next(fd)?,
+            cause: read_next(fd)?,
+            pubkey_hash: read_next(fd)?,
+        })
+    }
+}
+
+impl StacksMessageCodec for TransactionPayload {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        match self {
+            TransactionPayload::TokenTransfer(recipient, amount, memo) => {
+                write_next(fd, &(TransactionPayloadID::TokenTransfer as u8))?;
+                write_next(fd, recipient)?;
+                write_next(fd, amount)?;
+                write_next(fd, memo)
+            }
+            TransactionPayload::ContractCall(contract_call) => {
+                write_next(fd, &(TransactionPayloadID::ContractCall as u8))?;
+                write_next(fd, contract_call)
+            }
+            TransactionPayload::SmartContract(smart_contract, None) => {
+                write_next(fd, &(TransactionPayloadID::SmartContract as u8))?;
+                write_next(fd, smart_contract)
+            }
+            TransactionPayload::SmartContract(smart_contract, Some(version)) => {
+                write_next(fd, &(TransactionPayloadID::VersionedSmartContract as u8))?;
+                write_next(fd, version)?;
+                write_next(fd, smart_contract)
+            }
+            TransactionPayload::PoisonMicroblock(h1, h2) => {
+                write_next(fd, &(TransactionPayloadID::PoisonMicroblock as u8))?;
+                write_next(fd, h1)?;
+                write_next(fd, h2)
+            }
+            TransactionPayload::Coinbase(payload, None, None) => {
+                write_next(fd, &(TransactionPayloadID::Coinbase as u8))?;
+                write_next(fd, payload)
+            }
+            TransactionPayload::Coinbase(payload, Some(recipient), None) => {
+                write_next(fd, &(TransactionPayloadID::CoinbaseToAltRecipient as u8))?;
+                write_next(fd, payload)?;
+                write_next(fd, recipient)
+            }
+            TransactionPayload::Coinbase(payload, recipient, Some(vrf_proof)) => {
+                write_next(fd, &(TransactionPayloadID::NakamotoCoinbase as u8))?;
+                write_next(fd, payload)?;
+                // The recipient is encoded as a Clarity `(optional principal)`.
+                let recipient = match recipient {
+                    Some(recipient) => Value::some(Value::Principal(recipient.clone())),
+                    None => Value::none(),
+                };
+                write_next(fd, &recipient)?;
+                write_next(fd, vrf_proof)
+            }
+            TransactionPayload::TenureChange(tenure_change) => {
+                write_next(fd, &(TransactionPayloadID::TenureChange as u8))?;
+                write_next(fd, tenure_change)
+            }
+        }
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let payload_id: u8 = read_next(fd)?;
+        let payload = match payload_id {
+            x if x == TransactionPayloadID::TokenTransfer as u8 => {
+                let recipient = read_next(fd)?;
+                let amount = read_next(fd)?;
+                let memo = read_next(fd)?;
+                TransactionPayload::TokenTransfer(recipient, amount, memo)
+            }
+            x if x == TransactionPayloadID::ContractCall as u8 => {
+                TransactionPayload::ContractCall(read_next(fd)?)
+            }
+            x if x == TransactionPayloadID::SmartContract as u8 => {
+                TransactionPayload::SmartContract(read_next(fd)?, None)
+            }
+            x if x == TransactionPayloadID::VersionedSmartContract as u8 => {
+                let version = read_next(fd)?;
+                TransactionPayload::SmartContract(read_next(fd)?, Some(version))
+            }
+            x if x == TransactionPayloadID::PoisonMicroblock as u8 => {
+                let h1 = read_next(fd)?;
+                let h2 = read_next(fd)?;
+                TransactionPayload::PoisonMicroblock(h1, h2)
+            }
+            x if x == TransactionPayloadID::Coinbase as u8 => {
+                TransactionPayload::Coinbase(read_next(fd)?, None, None)
+            }
+            x if x == TransactionPayloadID::CoinbaseToAltRecipient as u8 => {
+                let payload = read_next(fd)?;
+                let recipient: PrincipalData = read_next(fd)?;
+                TransactionPayload::Coinbase(payload, Some(recipient), None)
+            }
+            x if x == TransactionPayloadID::NakamotoCoinbase as u8 => {
+                let payload = read_next(fd)?;
+                let recipient = match read_next::<Value, _>(fd)? {
+                    Value::Optional(OptionalData { data: None }) => None,
+                    Value::Optional(OptionalData { data: Some(value) }) => match *value {
+                        Value::Principal(recipient) => Some(recipient),
+                        _ => {
+                            return Err(deserialize_error("Coinbase recipient is not a principal"))
+                        }
+                    },
+                    _ => {
+                        return Err(deserialize_error(
+                            "Coinbase recipient is not an optional principal",
+                        ))
+                    }
+                };
+                let vrf_proof = read_next(fd)?;
+                TransactionPayload::Coinbase(payload, recipient, Some(vrf_proof))
+            }
+            x if x == TransactionPayloadID::TenureChange as u8 => {
+                TransactionPayload::TenureChange(read_next(fd)?)
+            }
+            x => {
+                return Err(deserialize_error(format!(
+                    "Unrecognized transaction payload ID: {x:#04x}"
+                )))
+            }
+        };
+        Ok(payload)
+    }
+}
+
+impl StacksMessageCodec for StacksTransaction {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.version)?;
+        write_next(fd, &self.chain_id)?;
+        write_next(fd, &self.auth)?;
+        write_next(fd, &self.anchor_mode)?;
+        write_next(fd, &self.post_condition_mode)?;
+        write_next(fd, &self.post_conditions)?;
+        write_next(fd, &self.payload)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        Ok(StacksTransaction {
+            version: read_next(fd)?,
+            chain_id: read_next(fd)?,
+            auth: read_next(fd)?,
+            anchor_mode: read_next(fd)?,
+            post_condition_mode: read_next(fd)?,
+            post_conditions: read_next_at_most(fd, MAX_MESSAGE_LEN)?,
+            payload: read_next(fd)?,
+        })
+    }
+}
@@ -0,0 +1,427 @@
+//! Typed access to the node's `/v2` RPC endpoints.
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::{
+    clarity::{
+        value_hex, BufferLength, ListTypeData, PrincipalData, QualifiedContractIdentifier,
+        SequenceSubtype, StringSubtype, StringUTF8Length, TupleTypeSignature, TypeSignature, Value,
+    },
+    client::StacksNodeClient,
+    codec::StacksMessageCodec,
+    errors::Error,
+    transactions::StacksTransaction,
+};
+use crate::stacks::Txid;
+
+/// `GET /v2/info`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    pub peer_version: u32,
+    pub pox_consensus: String,
+    pub burn_block_height: u64,
+    pub stable_pox_consensus: String,
+    pub stable_burn_block_height: u64,
+    pub server_version: String,
+    pub network_id: u32,
+    pub parent_network_id: u32,
+    pub stacks_tip_height: u64,
+    pub stacks_tip: String,
+    pub stacks_tip_consensus_hash: String,
+    pub genesis_chainstate_hash: String,
+    #[serde(default)]
+    pub unanchored_tip: Option<String>,
+    #[serde(default)]
+    pub unanchored_seq: Option<u16>,
+    #[serde(default)]
+    pub exit_at_block_height: Option<u64>,
+    #[serde(default)]
+    pub is_fully_synced: Option<bool>,
+    #[serde(default)]
+    pub node_public_key: Option<String>,
+    #[serde(default)]
+    pub node_public_key_hash: Option<String>,
+    /// Only reported by Nakamoto (3.0+) nodes.
+    #[serde(default)]
+    pub tenure_height: Option<u64>,
+}
+
+/// `GET /v2/pox`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoxInfo {
+    pub contract_id: String,
+    pub pox_activation_threshold_ustx: u64,
+    pub first_burnchain_block_height: u64,
+    pub current_burnchain_block_height: u64,
+    pub prepare_phase_block_length: u64,
+    pub reward_phase_block_length: u64,
+    pub reward_slots: u64,
+    #[serde(default)]
+    pub rejection_fraction: Option<u64>,
+    pub total_liquid_supply_ustx: u64,
+    pub current_cycle: PoxCurrentCycleInfo,
+    pub next_cycle: PoxNextCycleInfo,
+    pub min_amount_ustx: u64,
+    pub prepare_cycle_length: u64,
+    pub reward_cycle_id: u64,
+    pub reward_cycle_length: u64,
+    #[serde(default)]
+    pub rejection_votes_left_required: Option<u64>,
+    pub next_reward_cycle_in: u64,
+    #[serde(default)]
+    pub contract_versions: Vec<PoxContractVersion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoxCurrentCycleInfo {
+    pub id: u64,
+    pub min_threshold_ustx: u64,
+    pub stacked_ustx: u64,
+    pub is_pox_active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoxNextCycleInfo {
+    pub id: u64,
+    pub min_threshold_ustx: u64,
+    pub min_increment_ustx: u64,
+    pub stacked_ustx: u64,
+    pub prepare_phase_start_block_height: u64,
+    pub blocks_until_prepare_phase: i64,
+    pub reward_phase_start_block_height: u64,
+    pub blocks_until_reward_phase: u64,
+    #[serde(default)]
+    pub ustx_until_pox_rejection: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoxContractVersion {
+    pub contract_id: String,
+    pub activation_burnchain_block_height: u64,
+    pub first_reward_cycle_id: u64,
+}
+
+/// `GET /v2/accounts/{principal}`. Balances are in micro-STX.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountInfo {
+    #[serde(deserialize_with = "u128_hex_deserialize")]
+    pub balance: u128,
+    #[serde(deserialize_with = "u128_hex_deserialize")]
+    pub locked: u128,
+    pub unlock_height: u64,
+    pub nonce: u64,
+}
+
+fn u128_hex_deserialize<'de, D>(deserializer: D) -> Result<u128, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    u128::from_str_radix(s.strip_prefix("0x").unwrap_or(&s), 16).map_err(serde::de::Error::custom)
+}
+
+#[derive(Debug, Serialize)]
+struct ReadOnlyCallRequest {
+    sender: String,
+    arguments: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadOnlyCallResponse {
+    okay: bool,
+    #[serde(default, deserialize_with = "optional_value_hex_deserialize")]
+    result: Option<Value>,
+    #[serde(default)]
+    cause: Option<String>,
+}
+
+fn optional_value_hex_deserialize<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(with = "value_hex")] Value);
+
+    Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(value)| value))
+}
+
+/// The response of `/v2/data_var` and `/v2/map_entry`.
+#[derive(Debug, Deserialize)]
+struct DataResponse {
+    #[serde(with = "value_hex")]
+    data: Value,
+}
+
+/// `GET /v2/contracts/interface/{address}/{name}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractInterface {
+    pub functions: Vec<ContractInterfaceFunction>,
+    pub variables: Vec<ContractInterfaceVariable>,
+    pub maps: Vec<ContractInterfaceMap>,
+    pub fungible_tokens: Vec<ContractInterfaceFungibleToken>,
+    pub non_fungible_tokens: Vec<ContractInterfaceNonFungibleToken>,
+    pub epoch: String,
+    pub clarity_version: String,
+}
+
+impl ContractInterface {
+    pub fn function(&self, name: &str) -> Option<&ContractInterfaceFunction> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractInterfaceFunction {
+    pub name: String,
+    pub access: ContractInterfaceFunctionAccess,
+    pub args: Vec<ContractInterfaceFunctionArg>,
+    pub outputs: ContractInterfaceFunctionOutput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractInterfaceFunctionAccess {
+    Private,
+    Public,
+    ReadOnly,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractInterfaceFunctionArg {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub arg_type: ContractInterfaceAtomType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractInterfaceFunctionOutput {
+    #[serde(rename = "type")]
+    pub output_type: ContractInterfaceAtomType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractInterfaceVariable {
+    pub name: String,
+    pub access: ContractInterfaceVariableAccess,
+    #[serde(rename = "type")]
+    pub var_type: ContractInterfaceAtomType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractInterfaceVariableAccess {
+    Constant,
+    Variable,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractInterfaceMap {
+    pub name: String,
+    pub key: ContractInterfaceAtomType,
+    pub value: ContractInterfaceAtomType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractInterfaceFungibleToken {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractInterfaceNonFungibleToken {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub asset_type: ContractInterfaceAtomType,
+}
+
+/// A Clarity type, as described by the contract interface endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractInterfaceAtomType {
+    None,
+    Int128,
+    Uint128,
+    Bool,
+    Principal,
+    Buffer {
+        length: u32,
+    },
+    StringUtf8 {
+        length: u32,
+    },
+    StringAscii {
+        length: u32,
+    },
+    Tuple(Vec<ContractInterfaceTupleEntryType>),
+    Optional(Box<ContractInterfaceAtomType>),
+    Response {
+        ok: Box<ContractInterfaceAtomType>,
+        error: Box<ContractInterfaceAtomType>,
+    },
+    List {
+        #[serde(rename = "type")]
+        list_type: Box<ContractInterfaceAtomType>,
+        length: u32,
+    },
+    TraitReference,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractInterfaceTupleEntryType {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub entry_type: ContractInterfaceAtomType,
+}
+
+/// Trait references are converted to `principal`: the interface doesn't say
+/// which trait is expected, and contract principals are serialized the same
+/// way either way.
+impl TryFrom<&ContractInterfaceAtomType> for TypeSignature {
+    type Error = Error;
+
+    fn try_from(atom: &ContractInterfaceAtomType) -> Result<Self, Self::Error> {
+        use ContractInterfaceAtomType::*;
+
+        let sig = match atom {
+            None => TypeSignature::NoType,
+            Int128 => TypeSignature::IntType,
+            Uint128 => TypeSignature::UIntType,
+            Bool => TypeSignature::BoolType,
+            Principal | TraitReference => TypeSignature::PrincipalType,
+            Buffer { length } => TypeSignature::SequenceType(SequenceSubtype::BufferType(
+                BufferLength::try_from(*length)?,
+            )),
+            StringAscii { length } => TypeSignature::SequenceType(SequenceSubtype::StringType(
+                StringSubtype::ASCII(BufferLength::try_from(*length)?),
+            )),
+            StringUtf8 { length } => TypeSignature::SequenceType(SequenceSubtype::StringType(
+                StringSubtype::UTF8(StringUTF8Length::try_from(*length)?),
+            )),
+            Tuple(entries) => TypeSignature::TupleType(TupleTypeSignature::try_from(
+                entries
+                    .iter()
+                    .map(|entry| Ok((entry.name.clone(), (&entry.entry_type).try_into()?)))
+                    .collect::<Result<Vec<_>, Error>>()?,
+            )?),
+            Optional(inner) => TypeSignature::OptionalType(Box::new(inner.as_ref().try_into()?)),
+            Response { ok, error } => TypeSignature::ResponseType(Box::new((
+                ok.as_ref().try_into()?,
+                error.as_ref().try_into()?,
+            ))),
+            List { list_type, length } => TypeSignature::SequenceType(SequenceSubtype::ListType(
+                ListTypeData::new_list(list_type.as_ref().try_into()?, *length),
+            )),
+        };
+        Ok(sig)
+    }
+}
+
+impl StacksNodeClient {
+    pub async fn get_info(&self) -> Result<PeerInfo, Error> {
+        self.send_json(self.get("/v2/info")?).await
+    }
+
+    pub async fn get_pox_info(&self) -> Result<PoxInfo, Error> {
+        self.send_json(self.get("/v2/pox")?).await
+    }
+
+    /// The balance and nonce of a standard or contract principal.
+    pub async fn get_account(&self, principal: &PrincipalData) -> Result<AccountInfo, Error> {
+        self.send_json(self.get(&format!("/v2/accounts/{principal}?proof=0"))?)
+            .await
+    }
+
+    /// Broadcasts a signed transaction, returning its txid.
+    pub async fn broadcast_transaction(&self, tx: &StacksTransaction) -> Result<Txid, Error> {
+        let request = self
+            .post("/v2/transactions")?
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(tx.serialize_to_vec()?);
+        let txid: String = self.send_json(request).await?;
+
+        let bytes = hex::decode(txid.strip_prefix("0x").unwrap_or(&txid))
+            .map_err(|e| Error::RpcError(format!("Invalid txid '{txid}': {e}")))?;
+        let txid = <[u8; 32]>::try_from(bytes.as_slice())
+            .map_err(|_| Error::RpcError(format!("Invalid txid '{txid}'")))?;
+        Ok(Txid(txid))
+    }
+
+    /// Calls a read-only function, returning its result. Fails with
+    /// [`Error::RpcError`] if the node couldn't evaluate the call.
+    pub async fn call_read_only(
+        &self,
+        contract: &QualifiedContractIdentifier,
+        function_name: &str,
+        sender: &PrincipalData,
+        arguments: &[Value],
+    ) -> Result<Value, Error> {
+        let body = ReadOnlyCallRequest {
+            sender: sender.to_string(),
+            arguments: arguments
+                .iter()
+                .map(|arg| Ok(format!("0x{}", arg.serialize_to_hex()?)))
+                .collect::<Result<_, Error>>()?,
+        };
+        let path = format!(
+            "/v2/contracts/call-read/{}/{}/{function_name}",
+            contract.issuer, contract.name
+        );
+        let response: ReadOnlyCallResponse = self.send_json(self.post(&path)?.json(&body)).await?;
+
+        match response {
+            ReadOnlyCallResponse {
+                okay: true,
+                result: Some(result),
+                ..
+            } => Ok(result),
+            ReadOnlyCallResponse { cause, .. } => Err(Error::RpcError(
+                cause.unwrap_or_else(|| "Read-only call failed".into()),
+            )),
+        }
+    }
+
+    /// The current value of a data variable.
+    pub async fn get_data_var(
+        &self,
+        contract: &QualifiedContractIdentifier,
+        var_name: &str,
+    ) -> Result<Value, Error> {
+        let path = format!(
+            "/v2/data_var/{}/{}/{var_name}?proof=0",
+            contract.issuer, contract.name
+        );
+        let response: DataResponse = self.send_json(self.get(&path)?).await?;
+        Ok(response.data)
+    }
+
+    /// Looks up `key` in a map, returning `(some value)` or `none`.
+    pub async fn get_map_entry(
+        &self,
+        contract: &QualifiedContractIdentifier,
+        map_name: &str,
+        key: &Value,
+    ) -> Result<Value, Error> {
+        let path = format!(
+            "/v2/map_entry/{}/{}/{map_name}?proof=0",
+            contract.issuer, contract.name
+        );
+        let key = format!("0x{}", key.serialize_to_hex()?);
+        let response: DataResponse = self.send_json(self.post(&path)?.json(&key)).await?;
+        Ok(response.data)
+    }
+
+    pub async fn get_contract_interface(
+        &self,
+        contract: &QualifiedContractIdentifier,
+    ) -> Result<ContractInterface, Error> {
+        let path = format!(
+            "/v2/contracts/interface/{}/{}",
+            contract.issuer, contract.name
+        );
+        self.send_json(self.get(&path)?).await
+    }
+
+    /// The estimated fee rate for STX transfers, in micro-STX per byte.
+    pub async fn get_transfer_fee_rate(&self) -> Result<u64, Error> {
+        self.send_json(self.get("/v2/fees/transfer")?).await
+    }
+}
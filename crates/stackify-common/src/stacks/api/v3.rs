@@ -0,0 +1,510 @@
+//! Typed access to the Nakamoto (3.0+) `/v3` RPC endpoints, along with the
+//! Nakamoto block types which they return.
+
+use std::io::{Read, Write};
+
+use libsecp256k1::PublicKey;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    client::StacksNodeClient,
+    codec::{read_next, write_next, StacksMessageCodec},
+    errors::Error,
+    transactions::{StacksTransaction, TenureChangePayload, TransactionPayload},
+};
+use crate::stacks::{
+    BlockHeaderHash, ConsensusHash, MessageSignature, Sha512Trunc256Sum, StacksBlockId, TrieHash,
+};
+
+/// The maximum number of bits in a block's PoX treatment bit vector, i.e. the
+/// maximum number of reward set slots.
+pub const MAX_POX_TREATMENT_LEN: u16 = 4000;
+
+/// A length-prefixed bit vector, as used for a block's PoX treatment. Bits
+/// are stored least-significant first within each byte.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BitVec {
+    len: u16,
+    data: Vec<u8>,
+}
+
+impl BitVec {
+    /// A bit vector of `len` bits, all set to `false`.
+    pub fn zeros(len: u16) -> Result<BitVec, Error> {
+        if len == 0 || len > MAX_POX_TREATMENT_LEN {
+            return Err(Error::InvalidLength(len as usize));
+        }
+        Ok(BitVec {
+            len,
+            data: vec![0; (len as usize).div_ceil(8)],
+        })
+    }
+
+    pub fn len(&self) -> u16 {
+        self.len
+    }
+
+    /// Always `false`, as bit vectors hold at least one bit.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: u16) -> Option<bool> {
+        if i >= self.len {
+            return None;
+        }
+        let byte = self.data.get((i / 8) as usize)?;
+        Some(byte & (1 << (i % 8)) != 0)
+    }
+
+    pub fn set(&mut self, i: u16, value: bool) -> Result<(), Error> {
+        if i >= self.len {
+            return Err(Error::Other(format!(
+                "Bit {i} is out of range for a bit vector of length {}",
+                self.len
+            )));
+        }
+        let byte = &mut self.data[(i / 8) as usize];
+        if value {
+            *byte |= 1 << (i % 8);
+        } else {
+            *byte &= !(1 << (i % 8));
+        }
+        Ok(())
+    }
+}
+
+impl StacksMessageCodec for BitVec {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.len)?;
+        write_next(fd, &self.data)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        let len: u16 = read_next(fd)?;
+        if len == 0 || len > MAX_POX_TREATMENT_LEN {
+            return Err(Error::DeserializeError(format!(
+                "Invalid bit vector length: {len}"
+            )));
+        }
+        let data: Vec<u8> = read_next(fd)?;
+        if data.len() != (len as usize).div_ceil(8) {
+            return Err(Error::DeserializeError(format!(
+                "Bit vector of length {len} has {} data bytes",
+                data.len()
+            )));
+        }
+        Ok(BitVec { len, data })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NakamotoBlockHeader {
+    pub version: u8,
+    /// The total number of Stacks blocks preceding this one.
+    pub chain_length: u64,
+    /// The total amount of BTC spent by all miners in the sortition which
+    /// elected this block's miner.
+    pub burn_spent: u64,
+    /// Consensus hash of the tenure (sortition) this block belongs to.
+    pub consensus_hash: ConsensusHash,
+    pub parent_block_id: StacksBlockId,
+    pub tx_merkle_root: Sha512Trunc256Sum,
+    pub state_index_root: TrieHash,
+    /// Unix timestamp at which the block was proposed.
+    pub timestamp: u64,
+    /// The miner's recoverable signature over
+    /// [`NakamotoBlockHeader::miner_signature_hash`].
+    pub miner_signature: MessageSignature,
+    /// The signers' recoverable signatures over
+    /// [`NakamotoBlockHeader::signer_signature_hash`], in reward set order.
+    pub signer_signature: Vec<MessageSignature>,
+    /// Which reward set addresses the miner rewarded (or punished).
+    pub pox_treatment: BitVec,
+}
+
+impl NakamotoBlockHeader {
+    /// Writes the fields covered by the miner's and the signers' signatures.
+    fn write_signed_fields<W: Write>(
+        &self,
+        fd: &mut W,
+        include_miner_signature: bool,
+    ) -> Result<(), Error> {
+        write_next(fd, &self.version)?;
+        write_next(fd, &self.chain_length)?;
+        write_next(fd, &self.burn_spent)?;
+        write_next(fd, &self.consensus_hash)?;
+        write_next(fd, &self.parent_block_id)?;
+        write_next(fd, &self.tx_merkle_root)?;
+        write_next(fd, &self.state_index_root)?;
+        write_next(fd, &self.timestamp)?;
+        if include_miner_signature {
+            write_next(fd, &self.miner_signature)?;
+        }
+        write_next(fd, &self.pox_treatment)
+    }
+
+    /// The hash signed by the miner: every field except the signatures.
+    pub fn miner_signature_hash(&self) -> Result<Sha512Trunc256Sum, Error> {
+        let mut bytes = vec![];
+        self.write_signed_fields(&mut bytes, false)?;
+        Ok(Sha512Trunc256Sum::from_data(&bytes))
+    }
+
+    /// The hash signed by the signers: every field except the signers'
+    /// signatures themselves.
+    pub fn signer_signature_hash(&self) -> Result<Sha512Trunc256Sum, Error> {
+        let mut bytes = vec![];
+        self.write_signed_fields(&mut bytes, true)?;
+        Ok(Sha512Trunc256Sum::from_data(&bytes))
+    }
+
+    /// The block hash, which is the same as the signer signature hash.
+    pub fn block_hash(&self) -> Result<BlockHeaderHash, Error> {
+        Ok(BlockHeaderHash(self.signer_signature_hash()?.0))
+    }
+
+    /// The id under which the node indexes this block, e.g. for
+    /// `/v3/blocks/{id}`.
+    pub fn block_id(&self) -> Result<StacksBlockId, Error> {
+        Ok(StacksBlockId::new(
+            &self.consensus_hash,
+            &self.block_hash()?,
+        ))
+    }
+
+    pub fn recover_miner_key(&self) -> Result<PublicKey, Error> {
+        self.miner_signature
+            .recover_public_key(&self.miner_signature_hash()?.0)
+    }
+
+    /// Recovers the public keys of the signers which signed this block, in
+    /// signature order.
+    pub fn recover_signer_keys(&self) -> Result<Vec<PublicKey>, Error> {
+        let hash = self.signer_signature_hash()?;
+        self.signer_signature
+            .iter()
+            .map(|signature| signature.recover_public_key(&hash.0))
+            .collect()
+    }
+}
+
+impl StacksMessageCodec for NakamotoBlockHeader {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.version)?;
+        write_next(fd, &self.chain_length)?;
+        write_next(fd, &self.burn_spent)?;
+        write_next(fd, &self.consensus_hash)?;
+        write_next(fd, &self.parent_block_id)?;
+        write_next(fd, &self.tx_merkle_root)?;
+        write_next(fd, &self.state_index_root)?;
+        write_next(fd, &self.timestamp)?;
+        write_next(fd, &self.miner_signature)?;
+        write_next(fd, &self.signer_signature)?;
+        write_next(fd, &self.pox_treatment)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        Ok(NakamotoBlockHeader {
+            version: read_next(fd)?,
+            chain_length: read_next(fd)?,
+            burn_spent: read_next(fd)?,
+            consensus_hash: read_next(fd)?,
+            parent_block_id: read_next(fd)?,
+            tx_merkle_root: read_next(fd)?,
+            state_index_root: read_next(fd)?,
+            timestamp: read_next(fd)?,
+            miner_signature: read_next(fd)?,
+            signer_signature: read_next(fd)?,
+            pox_treatment: read_next(fd)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NakamotoBlock {
+    pub header: NakamotoBlockHeader,
+    pub txs: Vec<StacksTransaction>,
+}
+
+impl NakamotoBlock {
+    /// The tenure change carried by this block, if it starts or extends a
+    /// tenure.
+    pub fn tenure_change(&self) -> Option<&TenureChangePayload> {
+        self.txs.iter().find_map(|tx| match &tx.payload {
+            TransactionPayload::TenureChange(payload) => Some(payload),
+            _ => None,
+        })
+    }
+}
+
+impl StacksMessageCodec for NakamotoBlock {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), Error> {
+        write_next(fd, &self.header)?;
+        write_next(fd, &self.txs)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, Error> {
+        Ok(NakamotoBlock {
+            header: read_next(fd)?,
+            txs: read_next(fd)?,
+        })
+    }
+}
+
+/// `GET /v3/tenures/info`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TenureInfo {
+    #[serde(with = "hex_str")]
+    pub consensus_hash: ConsensusHash,
+    #[serde(with = "hex_str")]
+    pub tenure_start_block_id: StacksBlockId,
+    #[serde(with = "hex_str")]
+    pub parent_consensus_hash: ConsensusHash,
+    #[serde(with = "hex_str")]
+    pub parent_tenure_start_block_id: StacksBlockId,
+    #[serde(with = "hex_str")]
+    pub tip_block_id: StacksBlockId,
+    pub tip_height: u64,
+    pub reward_cycle: u64,
+}
+
+/// `GET /v3/sortitions/...`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SortitionInfo {
+    pub burn_block_hash: String,
+    pub burn_block_height: u64,
+    pub burn_header_timestamp: u64,
+    pub sortition_id: String,
+    pub parent_sortition_id: String,
+    #[serde(with = "hex_str")]
+    pub consensus_hash: ConsensusHash,
+    /// Whether a miner won this sortition; the remaining fields are only
+    /// set if one did.
+    pub was_sortition: bool,
+    #[serde(default)]
+    pub miner_pk_hash160: Option<String>,
+    #[serde(default, with = "opt_hex_str")]
+    pub stacks_parent_ch: Option<ConsensusHash>,
+    #[serde(default, with = "opt_hex_str")]
+    pub last_sortition_ch: Option<ConsensusHash>,
+    #[serde(default, with = "opt_hex_str")]
+    pub committed_block_hash: Option<BlockHeaderHash>,
+    #[serde(default)]
+    pub vrf_seed: Option<String>,
+}
+
+/// Which sortition(s) to look up with [`StacksNodeClient::get_sortitions`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortitionQuery {
+    /// The latest sortition.
+    Latest,
+    /// The latest sortition, plus the last one which elected a miner.
+    LatestAndLast,
+    ConsensusHash(ConsensusHash),
+    BurnHeight(u64),
+}
+
+impl SortitionQuery {
+    fn path(&self) -> String {
+        match self {
+            SortitionQuery::Latest => "/v3/sortitions".into(),
+            SortitionQuery::LatestAndLast => "/v3/sortitions/latest_and_last".into(),
+            SortitionQuery::ConsensusHash(ch) => format!("/v3/sortitions/consensus/{ch}"),
+            SortitionQuery::BurnHeight(height) => format!("/v3/sortitions/burn_height/{height}"),
+        }
+    }
+}
+
+/// `GET /v3/stacker_set/{cycle}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StackerSet {
+    /// The signers of the reward cycle. Only set for cycles which use
+    /// Nakamoto signing.
+    #[serde(default)]
+    pub signers: Option<Vec<StackerSetSigner>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StackerSetSigner {
+    /// The signer's compressed public key, hex-encoded with a `0x` prefix.
+    pub signing_key: String,
+    pub weight: u32,
+    pub stacked_amt: u128,
+}
+
+impl StackerSet {
+    /// The entry of the signer with the given public key, if it's part of the
+    /// set.
+    pub fn signer(&self, signer_key: &PublicKey) -> Option<&StackerSetSigner> {
+        let key = hex::encode(signer_key.serialize_compressed());
+        self.signers
+            .iter()
+            .flatten()
+            .find(|signer| signer.signing_key.trim_start_matches("0x") == key)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct StackerSetResponse {
+    stacker_set: StackerSet,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignerBlocksSigned {
+    blocks_signed: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct BlockProposalRequest {
+    /// The hex-encoded, consensus-serialized block.
+    block: String,
+    chain_id: u32,
+}
+
+/// The node's acknowledgement of a block proposal. The validation result
+/// itself is delivered to the node's event observers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockProposalResponse {
+    pub result: String,
+    pub message: String,
+}
+
+/// Serde helpers for hashes which the RPC interface encodes as hex strings.
+mod hex_str {
+    use std::{fmt::Display, str::FromStr};
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+mod opt_hex_str {
+    use std::{fmt::Display, str::FromStr};
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| s.parse().map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+impl StacksNodeClient {
+    pub async fn get_nakamoto_block(
+        &self,
+        block_id: &StacksBlockId,
+    ) -> Result<NakamotoBlock, Error> {
+        let bytes = self
+            .send_bytes(self.get(&format!("/v3/blocks/{block_id}"))?)
+            .await?;
+        NakamotoBlock::deserialize_from_slice(&bytes)
+    }
+
+    pub async fn get_tenure_info(&self) -> Result<TenureInfo, Error> {
+        self.send_json(self.get("/v3/tenures/info")?).await
+    }
+
+    /// The blocks of a tenure, starting at `block_id` and walking back to the
+    /// start of its tenure, or until `stop` (exclusive) if given.
+    pub async fn get_tenure_blocks(
+        &self,
+        block_id: &StacksBlockId,
+        stop: Option<&StacksBlockId>,
+    ) -> Result<Vec<NakamotoBlock>, Error> {
+        let path = match stop {
+            Some(stop) => format!("/v3/tenures/{block_id}?stop={stop}"),
+            None => format!("/v3/tenures/{block_id}"),
+        };
+        let bytes = self.send_bytes(self.get(&path)?).await?;
+
+        // The blocks are streamed back-to-back, without a length prefix.
+        let mut cursor = bytes.as_slice();
+        let mut blocks = vec![];
+        while !cursor.is_empty() {
+            blocks.push(read_next(&mut cursor)?);
+        }
+        Ok(blocks)
+    }
+
+    pub async fn get_sortitions(
+        &self,
+        query: &SortitionQuery,
+    ) -> Result<Vec<SortitionInfo>, Error> {
+        self.send_json(self.get(&query.path())?).await
+    }
+
+    /// The stacker set, including the registered signers, of a reward cycle.
+    /// The node returns an error for cycles whose set isn't known yet.
+    pub async fn get_stacker_set(&self, reward_cycle: u64) -> Result<StackerSet, Error> {
+        let response: StackerSetResponse = self
+            .send_json(self.get(&format!("/v3/stacker_set/{reward_cycle}"))?)
+            .await?;
+        Ok(response.stacker_set)
+    }
+
+    /// The number of blocks signed by the given signer in a reward cycle.
+    pub async fn get_signer_blocks_signed(
+        &self,
+        signer_key: &PublicKey,
+        reward_cycle: u64,
+    ) -> Result<u64, Error> {
+        let path = format!(
+            "/v3/signer/{}/{reward_cycle}",
+            hex::encode(signer_key.serialize_compressed())
+        );
+        let response: SignerBlocksSigned = self.send_json(self.get(&path)?).await?;
+        Ok(response.blocks_signed)
+    }
+
+    /// Submits a block for validation. This endpoint is meant for signers and
+    /// requires the node's configured auth token.
+    pub async fn propose_block(
+        &self,
+        block: &NakamotoBlock,
+        chain_id: u32,
+        auth_token: &str,
+    ) -> Result<BlockProposalResponse, Error> {
+        let body = BlockProposalRequest {
+            block: hex::encode(block.serialize_to_vec()?),
+            chain_id,
+        };
+        let request = self
+            .post("/v3/block_proposal")?
+            .header(reqwest::header::AUTHORIZATION, auth_token)
+            .json(&body);
+        self.send_json(request).await
+    }
+}
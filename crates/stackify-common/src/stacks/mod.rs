@@ -1,8 +1,9 @@
-use api::{hash::Hash160, transactions::AddressHashMode};
-use libsecp256k1::PublicKey;
+use api::{c32, errors::Error, hash::Hash160, transactions::AddressHashMode};
+use libsecp256k1::{Message, PublicKey, RecoveryId, SecretKey, Signature};
+use rand::thread_rng;
 use serde::{Deserialize, Serialize};
 use serde_big_array::BigArray;
-use sha2::{Digest, Sha256};
+use sha2::{Digest, Sha256, Sha512_256};
 
 use crate::bitcoin::{opcodes, script::Script};
 
@@ -13,18 +14,97 @@ pub const C32_ADDRESS_VERSION_MAINNET_MULTISIG: u8 = 20; // M
 pub const C32_ADDRESS_VERSION_TESTNET_SINGLESIG: u8 = 26; // T
 pub const C32_ADDRESS_VERSION_TESTNET_MULTISIG: u8 = 21; // N
 
+pub const CHAIN_ID_MAINNET: u32 = 0x00000001;
+pub const CHAIN_ID_TESTNET: u32 = 0x80000000;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ConsensusHash(pub [u8; 20]);
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StacksBlockId(pub [u8; 32]);
 
+impl StacksBlockId {
+    /// The index block hash of a block, which is what the node uses to
+    /// identify blocks across forks.
+    pub fn new(consensus_hash: &ConsensusHash, block_hash: &BlockHeaderHash) -> StacksBlockId {
+        let mut hasher = Sha512_256::new();
+        hasher.update(block_hash.0);
+        hasher.update(consensus_hash.0);
+        let mut ret = [0u8; 32];
+        ret.copy_from_slice(hasher.finalize().as_slice());
+        StacksBlockId(ret)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Sha512Trunc256Sum(#[serde(with = "BigArray")] pub [u8; 32]);
 
+impl Sha512Trunc256Sum {
+    pub fn from_data(data: &[u8]) -> Sha512Trunc256Sum {
+        let mut ret = [0u8; 32];
+        ret.copy_from_slice(Sha512_256::digest(data).as_slice());
+        Sha512Trunc256Sum(ret)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BlockHeaderHash(#[serde(with = "BigArray")] pub [u8; 32]);
 
+/// The root hash of the node's MARF (chainstate index) after a block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrieHash(pub [u8; 32]);
+
+/// Hex `Display`/`FromStr` for the fixed-size hashes which the node's RPC
+/// interface reports (and accepts in paths) as hex strings.
+macro_rules! impl_byte_array_hex {
+    ($name:ident, $len:expr) => {
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", hex::encode(self.0))
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let bytes = hex::decode(s.strip_prefix("0x").unwrap_or(s)).map_err(|e| {
+                    Error::DeserializeError(format!("Invalid {}: {e}", stringify!($name)))
+                })?;
+                let bytes = <[u8; $len]>::try_from(bytes.as_slice())
+                    .map_err(|_| Error::InvalidLength(bytes.len()))?;
+                Ok($name(bytes))
+            }
+        }
+    };
+}
+
+impl_byte_array_hex!(ConsensusHash, 20);
+impl_byte_array_hex!(StacksBlockId, 32);
+impl_byte_array_hex!(BlockHeaderHash, 32);
+impl_byte_array_hex!(TrieHash, 32);
+
+/// A Stacks transaction ID, which is the SHA512/256 hash of the
+/// consensus-serialized transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Txid(pub [u8; 32]);
+
+impl Txid {
+    pub fn from_data(data: &[u8]) -> Txid {
+        Txid(Sha512Trunc256Sum::from_data(data).0)
+    }
+}
+
+impl std::fmt::Display for Txid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+/// A VRF proof, as carried by Nakamoto coinbase transactions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VRFProof(#[serde(with = "BigArray")] pub [u8; 80]);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MessageSignature(#[serde(with = "BigArray")] pub [u8; 65]);
 
@@ -33,6 +113,25 @@ impl MessageSignature {
         // NOTE: this cannot be a valid signature
         MessageSignature([0u8; 65])
     }
+
+    /// Encodes a recoverable secp256k1 signature the way Stacks does: the
+    /// recovery id followed by the 64-byte compact signature.
+    pub fn from_recoverable(signature: &Signature, recovery_id: &RecoveryId) -> MessageSignature {
+        let mut ret = [0u8; 65];
+        ret[0] = recovery_id.serialize();
+        ret[1..].copy_from_slice(&signature.serialize());
+        MessageSignature(ret)
+    }
+
+    /// Recovers the public key which produced this signature over `message_hash`.
+    pub fn recover_public_key(&self, message_hash: &[u8; 32]) -> Result<PublicKey, Error> {
+        let recovery_id = RecoveryId::parse(self.0[0])
+            .map_err(|e| Error::VerifyingError(format!("Invalid recovery id: {e}")))?;
+        let signature = Signature::parse_standard_slice(&self.0[1..])
+            .map_err(|e| Error::VerifyingError(format!("Invalid signature: {e}")))?;
+        libsecp256k1::recover(&Message::parse(message_hash), &signature, &recovery_id)
+            .map_err(|e| Error::VerifyingError(format!("Failed to recover public key: {e}")))
+    }
 }
 
 #[repr(u8)]
@@ -43,6 +142,104 @@ pub enum TransactionPublicKeyEncoding {
     Uncompressed = 0x01,
 }
 
+/// A secp256k1 private key, along with whether its public key should be
+/// compressed. In hex form, compressed keys carry a trailing `01` byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StacksPrivateKey {
+    key: SecretKey,
+    compress_public: bool,
+}
+
+impl StacksPrivateKey {
+    /// Generates a new random private key with a compressed public key.
+    pub fn new() -> StacksPrivateKey {
+        StacksPrivateKey {
+            key: SecretKey::random(&mut thread_rng()),
+            compress_public: true,
+        }
+    }
+
+    pub fn from_secret_key(key: SecretKey, compress_public: bool) -> StacksPrivateKey {
+        StacksPrivateKey {
+            key,
+            compress_public,
+        }
+    }
+
+    /// Parses a hex-encoded private key. 32-byte keys have uncompressed
+    /// public keys, 33-byte keys ending in `01` have compressed public keys.
+    pub fn from_hex(hex_str: &str) -> Result<StacksPrivateKey, Error> {
+        let bytes = hex::decode(hex_str.trim_start_matches("0x"))
+            .map_err(|e| Error::InvalidKey(format!("Invalid hex: {e}")))?;
+        let compress_public = match bytes.len() {
+            32 => false,
+            33 if bytes[32] == 0x01 => true,
+            33 => {
+                return Err(Error::InvalidKey(
+                    "33-byte private keys must end with 0x01".into(),
+                ))
+            }
+            len => return Err(Error::InvalidKey(format!("Invalid key length: {len}"))),
+        };
+        let key = SecretKey::parse_slice(&bytes[..32])
+            .map_err(|e| Error::InvalidKey(format!("Invalid private key: {e}")))?;
+        Ok(StacksPrivateKey {
+            key,
+            compress_public,
+        })
+    }
+
+    pub fn to_hex(&self) -> String {
+        let mut hex_str = hex::encode(self.key.serialize());
+        if self.compress_public {
+            hex_str.push_str("01");
+        }
+        hex_str
+    }
+
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.key
+    }
+
+    pub fn compress_public(&self) -> bool {
+        self.compress_public
+    }
+
+    pub fn key_encoding(&self) -> TransactionPublicKeyEncoding {
+        if self.compress_public {
+            TransactionPublicKeyEncoding::Compressed
+        } else {
+            TransactionPublicKeyEncoding::Uncompressed
+        }
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from_secret_key(&self.key)
+    }
+
+    /// The public key, serialized compressed or uncompressed as the key says.
+    /// This is what P2PKH addresses are the hash of.
+    pub fn serialize_public_key(&self) -> Vec<u8> {
+        if self.compress_public {
+            self.public_key().serialize_compressed().to_vec()
+        } else {
+            self.public_key().serialize().to_vec()
+        }
+    }
+
+    /// Signs a 32-byte message hash, producing a recoverable signature.
+    pub fn sign(&self, message_hash: &[u8; 32]) -> MessageSignature {
+        let (signature, recovery_id) = libsecp256k1::sign(&Message::parse(message_hash), &self.key);
+        MessageSignature::from_recoverable(&signature, &recovery_id)
+    }
+}
+
+impl Default for StacksPrivateKey {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub struct StacksAddress {
     pub version: u8,
@@ -125,6 +322,35 @@ impl StacksAddress {
     }
 }
 
+impl std::fmt::Display for StacksAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match c32::c32_address(self.version, &self.bytes.0) {
+            Ok(addr) => write!(f, "{addr}"),
+            // c32check versions are 5 bits wide, but `version` is a public u8.
+            // Returning an error here would make `to_string()` panic, so show
+            // the raw parts instead.
+            Err(_) => write!(
+                f,
+                "<invalid address: version {}, hash {}>",
+                self.version,
+                hex::encode(self.bytes.0)
+            ),
+        }
+    }
+}
+
+impl std::str::FromStr for StacksAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (version, bytes) = c32::c32_address_decode(s)?;
+        if bytes.len() != 20 {
+            return Err(Error::InvalidLength(bytes.len()));
+        }
+        Ok(StacksAddress::new(version, Hash160::from(bytes.as_slice())))
+    }
+}
+
 /// Convert a number of required signatures and a list of public keys into a byte-vec to hash to an
 /// address.  Validity of the hash_flag vis a vis the num_sigs and pubkeys will _NOT_ be checked.
 /// This is a low-level method.  Consider using StacksAdress::from_public_keys() if you can.
@@ -135,12 +361,44 @@ pub fn public_keys_to_address_hash(
 ) -> Hash160 {
     match *hash_flag {
         AddressHashMode::SerializeP2PKH => to_bits_p2pkh(&pubkeys[0]),
-        AddressHashMode::SerializeP2SH => to_bits_p2sh(num_sigs, pubkeys),
+        AddressHashMode::SerializeP2SH => to_bits_p2sh(
+            num_sigs,
+            &pubkeys
+                .iter()
+                .map(|pubk| pubk.serialize_compressed().to_vec())
+                .collect::<Vec<_>>(),
+        ),
         AddressHashMode::SerializeP2WPKH => to_bits_p2sh_p2wpkh(&pubkeys[0]),
         AddressHashMode::SerializeP2WSH => to_bits_p2sh_p2wsh(num_sigs, pubkeys),
     }
 }
 
+/// Like `public_keys_to_address_hash()`, but each public key is serialized with
+/// its own encoding. Only (non-segwit) P2PKH and P2SH hashes can commit to
+/// uncompressed keys; the segwit modes always use the compressed form.
+pub fn encoded_public_keys_to_address_hash(
+    hash_flag: &AddressHashMode,
+    num_sigs: usize,
+    pubkeys: &[(TransactionPublicKeyEncoding, PublicKey)],
+) -> Hash160 {
+    let serialize =
+        |(key_encoding, pubk): &(TransactionPublicKeyEncoding, PublicKey)| match key_encoding {
+            TransactionPublicKeyEncoding::Compressed => pubk.serialize_compressed().to_vec(),
+            TransactionPublicKeyEncoding::Uncompressed => pubk.serialize().to_vec(),
+        };
+    match *hash_flag {
+        AddressHashMode::SerializeP2PKH => Hash160::from_data(&serialize(&pubkeys[0])),
+        AddressHashMode::SerializeP2SH => {
+            to_bits_p2sh(num_sigs, &pubkeys.iter().map(serialize).collect::<Vec<_>>())
+        }
+        _ => public_keys_to_address_hash(
+            hash_flag,
+            num_sigs,
+            &pubkeys.iter().map(|(_, pubk)| *pubk).collect(),
+        ),
+    }
+}
+
 /// Internally, the Stacks blockchain encodes address the same as Bitcoin
 /// single-sig address (p2pkh)
 /// Get back the hash of the address
@@ -236,11 +494,11 @@ impl BitcoinBuilder {
 
 /// Internally, the Stacks blockchain encodes address the same as Bitcoin
 /// multi-sig address (p2sh)
-fn to_bits_p2sh(num_sigs: usize, pubkeys: &Vec<PublicKey>) -> Hash160 {
+fn to_bits_p2sh(num_sigs: usize, pubkeys: &[Vec<u8>]) -> Hash160 {
     let mut bldr = BitcoinBuilder::new();
     bldr = bldr.push_int(num_sigs as i64);
     for pubk in pubkeys {
-        bldr = bldr.push_slice(&pubk.serialize_compressed());
+        bldr = bldr.push_slice(pubk);
     }
     bldr = bldr.push_int(pubkeys.len() as i64);
     bldr = bldr.push_opcode(opcodes::All::OP_CHECKMULTISIG);
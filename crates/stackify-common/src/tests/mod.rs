@@ -1 +1 @@
-
+mod types;
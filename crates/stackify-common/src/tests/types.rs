@@ -0,0 +1,69 @@
+use crate::types::{Environment, EnvironmentEpoch, EnvironmentName, Epoch};
+
+fn epoch(id: i32, name: &str, starts_at: u32, ends_at: Option<u32>) -> EnvironmentEpoch {
+    EnvironmentEpoch {
+        id: id + 100,
+        epoch: Epoch {
+            id,
+            name: name.to_string(),
+            default_block_height: starts_at,
+        },
+        starts_at_block_height: starts_at,
+        ends_at_block_height: ends_at,
+    }
+}
+
+fn environment(epochs: Vec<EnvironmentEpoch>) -> Environment {
+    Environment {
+        id: 1,
+        name: EnvironmentName::new("test").unwrap(),
+        bitcoin_block_speed: 30,
+        description: None,
+        publish_ports: false,
+        services: vec![],
+        epochs,
+        keychains: vec![],
+    }
+}
+
+fn names(env: &Environment) -> Vec<String> {
+    env.ordered_epochs()
+        .unwrap()
+        .iter()
+        .map(|e| e.epoch.name.clone())
+        .collect()
+}
+
+#[test]
+fn test_ordered_epochs() {
+    let env = environment(vec![
+        epoch(2, "2.05", 2, None),
+        epoch(0, "1.0", 0, Some(0)),
+        epoch(1, "2.0", 0, None),
+        epoch(3, "2.1", 3, None),
+    ]);
+    assert_eq!(names(&env), ["1.0", "2.0", "2.05", "2.1"]);
+
+    assert!(environment(vec![]).ordered_epochs().unwrap().is_empty());
+}
+
+#[test]
+fn test_ordered_epochs_gap() {
+    let env = environment(vec![epoch(3, "2.1", 3, None), epoch(5, "2.3", 5, None)]);
+    let err = env.ordered_epochs().unwrap_err().to_string();
+    assert!(err.contains("not contiguous"), "{err}");
+}
+
+#[test]
+fn test_ordered_epochs_unsorted() {
+    let env = environment(vec![epoch(3, "2.1", 10, None), epoch(4, "2.2", 5, None)]);
+    let err = env.ordered_epochs().unwrap_err().to_string();
+    assert!(err.contains("not sorted"), "{err}");
+}
+
+#[test]
+fn test_ordered_epochs_end_mismatch() {
+    let env = environment(vec![epoch(3, "2.1", 3, Some(4)), epoch(4, "2.2", 5, None)]);
+    let err = env.ordered_epochs().unwrap_err().to_string();
+    assert!(err.contains("ends at block 4"), "{err}");
+}
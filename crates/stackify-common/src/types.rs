@@ -83,6 +83,9 @@ pub struct PortMap {
     pub host_port: u16,
     pub container_port: u16,
     pub protocol: NetworkProtocol,
+    /// Whether the port was published because the environment publishes its
+    /// services' default ports, rather than by the user.
+    pub is_auto_published: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -117,7 +120,16 @@ pub struct EnvironmentService {
     pub port_mappings: Vec<PortMap>,
 }
 
-impl EnvironmentService {}
+impl EnvironmentService {
+    /// The value of the parameter with the given key, if it has been set for
+    /// this service.
+    pub fn param_value(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|param| param.param.key == key)
+            .map(|param| param.value.as_str())
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EnvironmentEpoch {
@@ -185,6 +197,13 @@ impl Display for Epoch {
 pub struct Environment {
     pub id: i32,
     pub name: EnvironmentName,
+    /// The number of seconds between Bitcoin blocks, unless overridden by the
+    /// Bitcoin miner's `bitcoin_block_frequency` parameter.
+    pub bitcoin_block_speed: u32,
+    pub description: Option<String>,
+    /// Whether the default ports of services which don't have any ports of
+    /// their own are published on the host when their containers are created.
+    pub publish_ports: bool,
     pub services: Vec<EnvironmentService>,
     pub epochs: Vec<EnvironmentEpoch>,
     pub keychains: Vec<EnvironmentKeychain>,
@@ -204,6 +223,47 @@ impl Environment {
             .filter(|svc| &svc.name != name)
             .collect::<Vec<_>>()
     }
+
+    /// Returns the environment's epochs in order, validating that they form a
+    /// contiguous sequence of epochs (e.g. no 2.2 between 2.1 and 2.3) whose
+    /// start heights never decrease, as required by the Stacks node.
+    pub fn ordered_epochs(&self) -> Result<Vec<&EnvironmentEpoch>> {
+        let mut epochs = self.epochs.iter().collect::<Vec<_>>();
+        epochs.sort_by_key(|e| e.epoch.id);
+
+        for pair in epochs.windows(2) {
+            let (prev, next) = (pair[0], pair[1]);
+            if next.epoch.id != prev.epoch.id + 1 {
+                bail!(
+                    "The epoch-map is not contiguous: epoch {} is followed by epoch {}",
+                    prev.epoch.name,
+                    next.epoch.name
+                );
+            }
+            if next.starts_at_block_height < prev.starts_at_block_height {
+                bail!(
+                    "The epoch-map is not sorted: epoch {} starts at block {}, before epoch {} at block {}",
+                    next.epoch.name,
+                    next.starts_at_block_height,
+                    prev.epoch.name,
+                    prev.starts_at_block_height
+                );
+            }
+            if let Some(ends_at) = prev.ends_at_block_height {
+                if ends_at != next.starts_at_block_height {
+                    bail!(
+                        "The epoch-map is not contiguous: epoch {} ends at block {}, but epoch {} starts at block {}",
+                        prev.epoch.name,
+                        ends_at,
+                        next.epoch.name,
+                        next.starts_at_block_height
+                    );
+                }
+            }
+        }
+
+        Ok(epochs)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -324,5 +384,7 @@ pub struct EnvironmentKeychain {
     pub private_key: String,
     pub public_key: String,
     pub btc_address: String,
+    /// The next nonce to use for the account, as far as Stackify knows.
+    pub nonce: u64,
     pub remark: Option<String>,
 }